@@ -0,0 +1,105 @@
+use crate::error::CallbotError;
+use crate::shell_history::Shell;
+
+/// Command line options
+#[derive(Debug, Default, Clone)]
+pub struct Cli {
+    // never execute anything; actions produce their configured fake output instead
+    pub simulate: bool,
+    // read-only viewer of the jobs other instances mirror into `follow_dir`
+    pub follow: bool,
+    // named workspace restored on start and saved on quit
+    pub session: Option<String>,
+    // file every run is appended to, see `Transcript`
+    pub record: Option<String>,
+    // print something and exit instead of starting the UI
+    pub command: Option<Subcommand>,
+}
+
+/// Commands that run instead of the UI
+#[derive(Debug, Clone)]
+pub enum Subcommand {
+    /// `completions <shell>`: print the completion script of the shell
+    Completions(Shell),
+    /// `__complete <kind>`: candidates for a completion script, one per line
+    Complete(String),
+}
+
+const USAGE: &str = "Usage: callbot [--simulate] [--follow] [--session NAME] [--record FILE]
+       callbot completions <bash|zsh|fish>
+
+Options:
+  --simulate   Do not run commands; show each action's `simulate` output and exit code
+  --follow     Watch the jobs of other callbot instances (read-only, needs `follow_dir`)
+  --session NAME
+               Restore the named workspace (parameter values, layout) and save it on quit
+  --record FILE
+               Append every run (command, timings, exit code, background output) to FILE
+               as JSON lines, a transcript of the session; `jq -r .command FILE` replays it
+  -h, --help   Show this help
+
+Commands:
+  completions SHELL
+               Print the completion script for bash, zsh or fish, e.g.
+               `callbot completions bash > ~/.local/share/bash-completion/completions/callbot`
+
+Control socket:
+  While the UI runs, $XDG_RUNTIME_DIR/callbot.sock takes one JSON request per line
+  and answers with one JSON line, e.g. with `socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/callbot.sock`:
+    {\"cmd\": \"run\", \"column\": \"ID\", \"action\": \"LABEL\", \"params\": {\"NAME\": \"value\"}}
+    {\"cmd\": \"reload\"}   {\"cmd\": \"jobs\"}   {\"cmd\": \"job\", \"id\": N}
+
+Environment:
+  CALLBOT_LOG  Log level: error, warn, info (default), debug, trace or off. Logs go to a
+               daily file in the user data dir; F12 shows the latest lines in the app";
+
+impl Cli {
+    /// Parse the process arguments. `--help` prints the usage and exits.
+    pub fn parse() -> Result<Self, CallbotError> {
+        let mut cli = Cli::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--simulate" => cli.simulate = true,
+                "--follow" => cli.follow = true,
+                "--session" => {
+                    let name = args.next().ok_or_else(|| {
+                        CallbotError::Usage(format!("--session needs a name\n\n{}", USAGE))
+                    })?;
+                    cli.session = Some(name);
+                }
+                "--record" => {
+                    let file = args.next().ok_or_else(|| {
+                        CallbotError::Usage(format!("--record needs a file\n\n{}", USAGE))
+                    })?;
+                    cli.record = Some(file);
+                }
+                "completions" => {
+                    let name = args.next().unwrap_or_default();
+                    let shell = Shell::from_name(&name).ok_or_else(|| {
+                        CallbotError::Usage(format!(
+                            "completions needs a shell: bash, zsh or fish\n\n{}",
+                            USAGE
+                        ))
+                    })?;
+                    cli.command = Some(Subcommand::Completions(shell));
+                }
+                // called back by the completion scripts, not documented
+                "__complete" => {
+                    cli.command = Some(Subcommand::Complete(args.next().unwrap_or_default()));
+                }
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                other => {
+                    return Err(CallbotError::Usage(format!(
+                        "Unknown argument '{}'\n\n{}",
+                        other, USAGE
+                    )))
+                }
+            }
+        }
+        Ok(cli)
+    }
+}
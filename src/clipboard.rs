@@ -0,0 +1,15 @@
+use ::clipboard::{ClipboardContext, ClipboardProvider};
+use std::error::Error;
+
+/// Text of the system clipboard (X11 on Linux); fails without a display
+pub fn contents() -> Result<String, Box<dyn Error>> {
+    let mut context: ClipboardContext = ClipboardProvider::new()?;
+    context.get_contents()
+}
+
+/// Replace the text of the system clipboard; on X11 it stays available while
+/// callbot runs
+pub fn set_contents(text: String) -> Result<(), Box<dyn Error>> {
+    let mut context: ClipboardContext = ClipboardProvider::new()?;
+    context.set_contents(text)
+}
@@ -0,0 +1,87 @@
+use crate::cli::Subcommand;
+use crate::error::CallbotError;
+use crate::session::Session;
+use crate::shell_history::Shell;
+
+// Session names are completed by calling back `callbot __complete sessions`, so
+// sessions saved after the script was installed are offered too
+
+const BASH: &str = r#"# bash completion for callbot
+_callbot() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$prev" in
+        --session)
+            COMPREPLY=($(compgen -W "$(callbot __complete sessions 2>/dev/null)" -- "$cur"))
+            return ;;
+        --record)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return ;;
+        completions)
+            COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur"))
+            return ;;
+    esac
+    COMPREPLY=($(compgen -W "--simulate --follow --session --record --help completions" -- "$cur"))
+}
+complete -F _callbot callbot
+"#;
+
+const ZSH: &str = r#"#compdef callbot
+# zsh completion for callbot
+_callbot() {
+    local state
+    _arguments \
+        '--simulate[Do not run commands, show their simulated output]' \
+        '--follow[Watch the jobs of other callbot instances]' \
+        '--session[Restore the named workspace and save it on quit]:session:->sessions' \
+        '--record[Append every run to a transcript file]:file:_files' \
+        '(- *)'{-h,--help}'[Show the help]' \
+        '1::command:(completions)' \
+        '2::shell:(bash zsh fish)'
+    case $state in
+        sessions) compadd -- ${(f)"$(callbot __complete sessions 2>/dev/null)"} ;;
+    esac
+}
+_callbot "$@"
+"#;
+
+const FISH: &str = r#"# fish completion for callbot
+complete -c callbot -f
+complete -c callbot -l simulate -d "Do not run commands, show their simulated output"
+complete -c callbot -l follow -d "Watch the jobs of other callbot instances"
+complete -c callbot -l session -x -a "(callbot __complete sessions 2>/dev/null)" -d "Restore the named workspace"
+complete -c callbot -l record -r -F -d "Append every run to a transcript file"
+complete -c callbot -s h -l help -d "Show the help"
+complete -c callbot -n __fish_use_subcommand -a completions -d "Print a completion script"
+complete -c callbot -n "__fish_seen_subcommand_from completions" -a "bash zsh fish"
+"#;
+
+/// Completion script of `shell` for the command line options
+pub fn script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+    }
+}
+
+/// Print what a subcommand asks for
+pub fn run(command: &Subcommand) -> Result<(), CallbotError> {
+    match command {
+        Subcommand::Completions(shell) => print!("{}", script(*shell)),
+        Subcommand::Complete(kind) => match kind.as_str() {
+            "sessions" => {
+                for name in Session::names() {
+                    println!("{}", name);
+                }
+            }
+            other => {
+                return Err(CallbotError::Usage(format!(
+                    "Nothing to complete for '{}'",
+                    other
+                )))
+            }
+        },
+    }
+    Ok(())
+}
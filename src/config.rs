@@ -1,13 +1,74 @@
-use serde::Deserialize;
-use std::error::Error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::error::{line_col, CallbotError};
+use crate::sources;
 
 /// Root configuration structure
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub app: AppConfig,
+    // may be empty when providers supply the columns
+    #[serde(default)]
     pub columns: Vec<Column>,
+    // programs printing more columns as JSON, run at load
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+    // values referenced as `{var:NAME}` in templates, defaults and options
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    // command fragments included in templates as `{snippet:NAME}`
+    #[serde(default)]
+    pub snippets: HashMap<String, String>,
+    // select shown in the header whose value every action's templates can use
+    #[serde(default)]
+    pub selector: Option<Parameter>,
+    // URLs posted to after runs, see `webhook`
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+/// An external program whose JSON output (`{"columns": [...]}`, each column shaped
+/// like `[[columns]]`) is merged into the config: columns with a known id get their
+/// actions appended, others are added after the configured ones.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Provider {
+    // run with `sh -c` from the config file's directory
+    pub command: String,
+}
+
+/// A URL posted to after the runs it matches, e.g. a Slack incoming webhook
+#[derive(Debug, Deserialize, Clone)]
+pub struct Webhook {
+    pub url: String,
+    // Handlebars template of the JSON body, `webhook::DEFAULT_PAYLOAD` when unset
+    #[serde(default)]
+    pub payload: Option<String>,
+    #[serde(default)]
+    pub on: WebhookOn,
+    // labels of the actions it fires for ("Deploy*" matches a prefix); all when empty
+    #[serde(default)]
+    pub actions: Vec<String>,
+    // last lines of output passed to the payload
+    #[serde(default = "default_tail_lines")]
+    pub tail_lines: usize,
+}
+
+/// Which runs a webhook fires for, by outcome
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookOn {
+    #[default]
+    All,
+    Failure,
+    Success,
+}
+
+fn default_tail_lines() -> usize {
+    20
 }
 
 /// Application-level settings (title, subtitle, etc.)
@@ -15,6 +76,154 @@ pub struct Config {
 pub struct AppConfig {
     pub title: String,
     pub subtitle: String,
+    // append executed commands to the user's shell history file (bash/zsh/fish)
+    #[serde(default)]
+    pub shell_history: bool,
+    // override the history file; the format still follows $SHELL
+    #[serde(default)]
+    pub history_file: Option<String>,
+    // number of recently run actions shown in the header (0 disables the strip)
+    #[serde(default = "default_recent_limit")]
+    pub recent_limit: usize,
+    // file updated with the runner state (idle/running/failed) for status lines
+    #[serde(default)]
+    pub status_file: Option<String>,
+    // also publish the runner state as the tmux user option @callbot_status
+    #[serde(default)]
+    pub tmux_status: bool,
+    // disable blinking and other animations, rendering static equivalents instead
+    #[serde(default)]
+    pub reduced_motion: bool,
+    // color theme: dark, light, solarized or high-contrast (cycled at runtime with `t`)
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    // banner color overriding the theme's ("#rrggbb" or a color name)
+    #[serde(default)]
+    pub banner_color: Option<String>,
+    // second color of a top-to-bottom gradient across the banner rows
+    #[serde(default)]
+    pub banner_gradient: Option<String>,
+    // figlet banner, or a single title line to leave room for the lists
+    #[serde(default)]
+    pub banner: BannerMode,
+    // session profile matched by `hidden_when.profile` (CALLBOT_PROFILE overrides it)
+    #[serde(default)]
+    pub profile: Option<String>,
+    // how action labels wider than their column are rendered
+    #[serde(default)]
+    pub label_overflow: LabelOverflow,
+    // directory where background job output is mirrored for `callbot --follow`
+    #[serde(default)]
+    pub follow_dir: Option<String>,
+    // show key hints next to the focused parameter of the details view
+    #[serde(default = "default_inline_hints")]
+    pub inline_hints: bool,
+    // dimmed summary of the parameter values after each action label
+    #[serde(default = "default_list_values")]
+    pub list_values: bool,
+    // how the actions marked with Space are run by `R`
+    #[serde(default)]
+    pub batch: BatchMode,
+    // reopen on the column, selections, filter and theme of the last exit
+    #[serde(default = "default_restore_ui")]
+    pub restore_ui: bool,
+    // checkpoint edited values to offer them back after a crash
+    #[serde(default = "default_crash_recovery")]
+    pub crash_recovery: bool,
+    // where password values are kept between runs; nowhere by default
+    #[serde(default)]
+    pub secret_store: SecretStore,
+    // help line overrides, per mode
+    #[serde(default)]
+    pub help: HelpText,
+    // color of select option values, by value ("prod*" matches a prefix); none by default
+    #[serde(default)]
+    pub option_colors: HashMap<String, String>,
+}
+
+/// Where the values of password parameters are kept when a session is saved
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SecretStore {
+    /// Not kept: they are typed again after a restart
+    #[default]
+    None,
+    /// Encrypted with `age` to the public key of an identity file, decrypted with it
+    Age { identity: String },
+    /// In the desktop keyring through `secret-tool` (libsecret)
+    Keyring,
+}
+
+/// Help line shown in each mode; unset modes keep the built-in hints
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HelpText {
+    #[serde(default)]
+    pub browse: Option<String>,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub edit: Option<String>,
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// Header drawn above the columns
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BannerMode {
+    /// Figlet banner, collapsed to a single title line on short terminals
+    #[default]
+    Auto,
+    /// Always the figlet banner
+    Full,
+    /// Always a single title line
+    Compact,
+}
+
+/// How a batch of marked actions is run
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchMode {
+    /// One after the other, stopping at the first failure
+    #[default]
+    Sequential,
+    /// All at once
+    Parallel,
+}
+
+/// Rendering of action labels that don't fit their column
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelOverflow {
+    /// Cut with "…" and show the full label in the status bar when selected
+    #[default]
+    Ellipsis,
+    /// Continue on indented extra lines
+    Wrap,
+}
+
+fn default_recent_limit() -> usize {
+    5
+}
+
+fn default_theme() -> String {
+    "dark".to_string()
+}
+
+fn default_list_values() -> bool {
+    true
+}
+
+fn default_restore_ui() -> bool {
+    true
+}
+
+fn default_crash_recovery() -> bool {
+    true
+}
+
+fn default_inline_hints() -> bool {
+    true
 }
 
 /// A column in the UI (e.g., Projects, Servers, Tools)
@@ -22,7 +231,136 @@ pub struct AppConfig {
 pub struct Column {
     pub id: String,
     pub title: String,
+    // fixed width in cells; columns without one share the remaining space
+    #[serde(default)]
+    pub width: Option<u16>,
+    // share of the remaining space relative to the other columns, 1 by default
+    #[serde(default)]
+    pub weight: Option<u32>,
+    // generate actions from an external description, appended to `actions`
+    #[serde(default)]
+    pub source: Option<ColumnSource>,
+    #[serde(default)]
+    pub layout: ColumnLayout,
+    #[serde(default)]
+    pub sort: ColumnSort,
+    #[serde(default)]
     pub actions: Vec<Action>,
+    // labels of the actions as written, to go back to that order, see `sort_actions`
+    #[serde(skip)]
+    pub config_order: Vec<String>,
+    // actions dropped by `apply_session_context`, to explain an empty column
+    #[serde(skip)]
+    pub hidden_actions: usize,
+}
+
+/// Rendering of the actions of a column
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnLayout {
+    /// One label per row
+    #[default]
+    List,
+    /// Label, description and last run status in aligned table columns
+    Table,
+}
+
+/// Order of the actions of a column
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColumnSort {
+    /// As written in the config
+    #[default]
+    Config,
+    /// By label, ignoring case
+    Alpha,
+    /// Most recently run first, then those never run
+    Recent,
+    /// Most run first, counted when the config is loaded
+    MostUsed,
+}
+
+impl ColumnSort {
+    /// The next order, cycling back to the first
+    pub fn next(self) -> Self {
+        match self {
+            ColumnSort::Config => ColumnSort::Alpha,
+            ColumnSort::Alpha => ColumnSort::Recent,
+            ColumnSort::Recent => ColumnSort::MostUsed,
+            ColumnSort::MostUsed => ColumnSort::Config,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            ColumnSort::Config => "in config order",
+            ColumnSort::Alpha => "alphabetically",
+            ColumnSort::Recent => "by last run",
+            ColumnSort::MostUsed => "by number of runs",
+        }
+    }
+}
+
+/// Where a column gets generated actions from
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ColumnSource {
+    /// One curl action per operation of an OpenAPI 3 or Swagger 2 spec (JSON or YAML)
+    OpenApi {
+        // spec file, relative to the config file
+        path: String,
+        // overrides the server URL declared in the spec
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    /// One action per executable file of a directory
+    Dir {
+        // relative to the config file, or starting with ~/
+        path: String,
+    },
+    /// One action per target of a Makefile
+    Make { path: String },
+    /// One action per recipe of a justfile
+    Just { path: String },
+    /// Start, stop, restart and status actions per systemd unit
+    Systemd {
+        units: Vec<String>,
+        // user units, managed with `systemctl --user`
+        #[serde(default)]
+        user: bool,
+    },
+    /// Up, down, logs and restart actions per service of a docker compose file
+    Compose { path: String },
+    /// One action per script of a package.json
+    Npm {
+        // directory of the package.json
+        path: String,
+        #[serde(default)]
+        manager: PackageManager,
+    },
+}
+
+/// Package manager running the scripts of a package.json
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    #[default]
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    /// Name of its executable
+    pub fn command(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
 }
 
 /// An action within a column
@@ -30,23 +368,105 @@ pub struct Column {
 pub struct Action {
     pub label: String,
     pub template: String,
+    // safe variant run with D, with the same parameters, e.g. `terraform plan`
+    #[serde(default)]
+    pub dry_template: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
     pub parameters: Vec<Parameter>,
+    // fake result used instead of running the command with `--simulate`
+    #[serde(default)]
+    pub simulate: Option<Simulation>,
+    // key that runs the action from the columns, e.g. "F5" or "Ctrl+r"
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    #[serde(default)]
+    pub engine: TemplateEngine,
+    // named sets of values applied at once from the details view
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+    // seconds of countdown, cancellable with Esc, between pressing run and running
+    #[serde(default)]
+    pub delay_secs: u64,
+    // r runs it in the background too, its output streaming into the output pane
+    #[serde(default)]
+    pub background: bool,
+    // run again every that many seconds once started, like watch(1)
+    #[serde(default)]
+    pub watch_secs: u64,
+    #[serde(default)]
+    pub confirm: Confirm,
+    // what to type with `confirm = "typed"`, the label by default
+    #[serde(default)]
+    pub confirm_phrase: Option<String>,
 }
 
-/// Parameter type: text input or dropdown select
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+impl Action {
+    /// What must be typed before each run, None when the action runs straight away
+    pub fn typed_confirmation(&self) -> Option<&str> {
+        match self.confirm {
+            Confirm::None => None,
+            Confirm::Typed => Some(self.confirm_phrase.as_deref().unwrap_or(&self.label)),
+        }
+    }
+}
+
+/// Values of some parameters of an action, by parameter name, saved under a name
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub values: BTreeMap<String, String>,
+}
+
+/// What stands between running an action and its command starting
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Confirm {
+    #[default]
+    None,
+    /// Its label, or `confirm_phrase`, typed out in a modal: for the most destructive
+    /// actions, where a yes is given too easily
+    Typed,
+}
+
+/// How the template of an action is turned into a command
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateEngine {
+    /// Placeholders replaced by their values
+    #[default]
+    Plain,
+    /// Rendered by Handlebars first, with the parameters as variables, then the
+    /// placeholders are replaced as with `plain`
+    Handlebars,
+}
+
+/// Simulated outcome of an action (see `--simulate`)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Simulation {
+    #[serde(default)]
+    pub exit: i32,
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// Parameter type: text input, dropdown select, or text input for a secret
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ParameterType {
+    #[default]
     Text,
     Select,
+    /// Typed like text but masked, kept out of history and logs, and saved only
+    /// through the `secret_store`
+    Password,
 }
 
-impl Default for ParameterType {
-    fn default() -> Self {
-        ParameterType::Text
+impl ParameterType {
+    /// Whether the value is typed in rather than picked
+    pub fn is_typed(&self) -> bool {
+        *self != ParameterType::Select
     }
 }
 
@@ -65,6 +485,94 @@ pub struct Parameter {
     pub options: Vec<ParameterOption>,
     #[serde(default)]
     pub default: Option<String>,
+    // regular expression a text value must match entirely
+    #[serde(default)]
+    pub pattern: Option<String>,
+    // bounds of a numeric value; setting either makes the value a number
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    // comma-separated value expanded to one `{value}` formatted entry each, e.g.
+    // "--exclude {value}", joined by spaces
+    #[serde(default)]
+    pub repeat_format: Option<String>,
+    // options of a select queried each time the details view opens, instead of `options`
+    #[serde(default)]
+    pub source: Option<OptionSource>,
+    // repository of the `git-branches` source; the working directory by default
+    #[serde(default)]
+    pub repo: Option<String>,
+    // context and namespace queried by the kube sources, the kubeconfig's by default;
+    // placeholders of other parameters are replaced by their values, e.g. "{NS}"
+    #[serde(default)]
+    pub kube_context: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    // where the value is read from when the action runs, e.g. "op://vault/item/field";
+    // the reference is what is shown, see `secrets::resolve`
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// Built-in source of the options of a select
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OptionSource {
+    /// Local then remote branches of a git repository, most recently committed first
+    GitBranches,
+    /// Contexts of the kubeconfig
+    KubeContexts,
+    /// Namespaces of a cluster
+    KubeNamespaces,
+    /// Pods of a namespace
+    KubePods,
+}
+
+impl Parameter {
+    /// Whether the value is a secret: a password, or read from a secret manager
+    pub fn is_secret(&self) -> bool {
+        self.param_type == ParameterType::Password || self.secret.is_some()
+    }
+
+    /// Check a value against the constraints of the parameter, describing the first
+    /// problem found. Empty optional values are always valid.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return if self.required {
+                Err("required".to_string())
+            } else {
+                Ok(())
+            };
+        }
+        if self.param_type == ParameterType::Select {
+            return Ok(());
+        }
+        if let Some(ref pattern) = self.pattern {
+            // patterns are checked by `Config::validate`, so this only fails on a bad config
+            let matches = Regex::new(&format!("^(?:{})$", pattern))
+                .map(|re| re.is_match(value))
+                .unwrap_or(true);
+            if !matches {
+                return Err(format!("must match {}", pattern));
+            }
+        }
+        if self.min.is_some() || self.max.is_some() {
+            let number: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| "must be a number".to_string())?;
+            match (self.min, self.max) {
+                (Some(min), Some(max)) if number < min || number > max => {
+                    return Err(format!("must be between {} and {}", min, max));
+                }
+                (Some(min), _) if number < min => return Err(format!("must be at least {}", min)),
+                (_, Some(max)) if number > max => return Err(format!("must be at most {}", max)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Option for select-type parameters
@@ -72,63 +580,351 @@ pub struct Parameter {
 pub struct ParameterOption {
     pub value: String,
     pub label: String,
+    #[serde(default)]
+    pub hidden_when: Option<OptionCondition>,
+    // overrides the `option_colors` entry for this value
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// Session conditions under which an option is hidden; every key given must match
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OptionCondition {
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// Who is running the launcher and from where, used to filter options
+#[derive(Debug, Clone, Default)]
+pub struct SessionContext {
+    pub user: Option<String>,
+    pub profile: Option<String>,
+}
+
+impl SessionContext {
+    /// Build the context from $USER and the profile (CALLBOT_PROFILE, then app.profile)
+    pub fn current(app: &AppConfig) -> Self {
+        Self {
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok(),
+            profile: std::env::var("CALLBOT_PROFILE")
+                .ok()
+                .or_else(|| app.profile.clone()),
+        }
+    }
+}
+
+impl OptionCondition {
+    fn matches(&self, ctx: &SessionContext) -> bool {
+        // an empty condition never hides anything
+        if self.user.is_none() && self.profile.is_none() {
+            return false;
+        }
+        let user_ok = self.user.is_none() || self.user == ctx.user;
+        let profile_ok = self.profile.is_none() || self.profile == ctx.profile;
+        user_ok && profile_ok
+    }
+}
+
+/// Expand a leading `~/` in a configured path to the user's home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(dirs) = directories::BaseDirs::new() {
+            return dirs.home_dir().join(rest);
+        }
+    }
+    PathBuf::from(path)
 }
 
 impl Config {
     /// Load configuration from a TOML file
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CallbotError> {
         let path = path.as_ref();
 
         if !path.exists() {
-            return Err(format!(
-                "Configuration file not found: {}\n\
-                 Please create a config.toml file in the same directory as the executable.",
-                path.display()
-            )
-            .into());
+            return Err(CallbotError::ConfigNotFound {
+                searched: vec![path.to_path_buf()],
+            });
         }
 
-        let content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read config file '{}': {}", path.display(), e))?;
+        let content = fs::read_to_string(path).map_err(|source| CallbotError::ConfigRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
 
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| format!("Failed to parse config file '{}': {}", path.display(), e))?;
+        let mut config: Config = toml::from_str(&content).map_err(|e| {
+            let (line, col) = e
+                .span()
+                .map(|span| line_col(&content, span.start))
+                .unwrap_or((1, 1));
+            CallbotError::ConfigParse {
+                path: path.to_path_buf(),
+                line,
+                col,
+                message: e.message().to_string(),
+            }
+        })?;
+
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for provider in config.providers.clone() {
+            let columns = sources::provider::load(&provider, base_dir).map_err(|e| {
+                CallbotError::Provider {
+                    command: provider.command.clone(),
+                    message: e.to_string(),
+                }
+            })?;
+            tracing::debug!(command = %provider.command, columns = columns.len(), "provider ran");
+            config.merge_columns(columns);
+        }
+
+        // Generate the actions of columns backed by a source before validating them
+        for column in &mut config.columns {
+            if let Some(ref source) = column.source {
+                let generated =
+                    sources::load(source, base_dir).map_err(|e| CallbotError::Source {
+                        column: column.id.clone(),
+                        message: e.to_string(),
+                    })?;
+                tracing::debug!(column = %column.id, actions = generated.len(), "generated actions");
+                column.actions.extend(generated);
+            }
+        }
 
+        config
+            .expand_references()
+            .map_err(CallbotError::Validation)?;
         // Validate the config
-        config.validate()?;
+        config.validate().map_err(CallbotError::Validation)?;
 
+        config.apply_session_context(&SessionContext::current(&config.app));
+        if config.columns.iter().all(|c| c.actions.is_empty()) {
+            return Err(CallbotError::Validation(
+                "No actions are available for this user/profile".to_string(),
+            ));
+        }
+
+        tracing::info!(
+            path = %path.display(),
+            columns = config.columns.len(),
+            actions = config.columns.iter().map(|c| c.actions.len()).sum::<usize>(),
+            "config loaded"
+        );
         Ok(config)
     }
 
+    // Replace the `{snippet:NAME}` references by the fragments of `[snippets]`, then
+    // the `{var:NAME}` ones (snippets may use variables) by the values of `[vars]`
+    fn expand_references(&mut self) -> Result<(), String> {
+        let (vars, snippets) = (&self.vars, &self.snippets);
+        let expand = |text: &mut String, action: &str| {
+            *text = crate::template::expand(text, "var", |name| vars.get(name).cloned()).map_err(
+                |name| format!("Action '{}' uses an unknown variable '{}'", action, name),
+            )?;
+            Ok::<_, String>(())
+        };
+        let expand_template = |text: &mut String, action: &str| {
+            *text = crate::template::expand(text, "snippet", |name| snippets.get(name).cloned())
+                .map_err(|name| {
+                    format!("Action '{}' uses an unknown snippet '{}'", action, name)
+                })?;
+            expand(text, action)
+        };
+        for column in &mut self.columns {
+            for action in &mut column.actions {
+                expand_template(&mut action.template, &action.label)?;
+                if let Some(ref mut dry) = action.dry_template {
+                    expand_template(dry, &action.label)?;
+                }
+                for param in &mut action.parameters {
+                    if let Some(ref mut default) = param.default {
+                        expand(default, &action.label)?;
+                    }
+                    for option in &mut param.options {
+                        expand(&mut option.value, &action.label)?;
+                        expand(&mut option.label, &action.label)?;
+                    }
+                }
+            }
+        }
+        if let Some(ref mut selector) = self.selector {
+            for option in &mut selector.options {
+                for text in [&mut option.value, &mut option.label] {
+                    *text = crate::template::expand(text, "var", |name| vars.get(name).cloned())
+                        .map_err(|name| {
+                            format!(
+                                "Selector '{}' uses an unknown variable '{}'",
+                                selector.name, name
+                            )
+                        })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Add provided columns: actions of a known column id are appended to it
+    fn merge_columns(&mut self, columns: Vec<Column>) {
+        for column in columns {
+            match self.columns.iter_mut().find(|c| c.id == column.id) {
+                Some(existing) => existing.actions.extend(column.actions),
+                None => self.columns.push(column),
+            }
+        }
+    }
+
+    /// Drop select options hidden for this session. Actions left with a select that has
+    /// no visible option are dropped too; their columns stay, showing why they're empty.
+    /// A global selector left without options is dropped.
+    pub fn apply_session_context(&mut self, ctx: &SessionContext) {
+        let visible = |o: &ParameterOption| {
+            !o.hidden_when
+                .as_ref()
+                .map(|cond| cond.matches(ctx))
+                .unwrap_or(false)
+        };
+        if let Some(ref mut selector) = self.selector {
+            selector.options.retain(visible);
+        }
+        self.selector.take_if(|s| s.options.is_empty());
+        for column in &mut self.columns {
+            for action in &mut column.actions {
+                for param in &mut action.parameters {
+                    param.options.retain(visible);
+                }
+            }
+            let before = column.actions.len();
+            column.actions.retain(|a| {
+                a.parameters.iter().all(|p| {
+                    p.param_type != ParameterType::Select
+                        || !p.options.is_empty()
+                        || p.source.is_some()
+                })
+            });
+            column.hidden_actions = before - column.actions.len();
+        }
+    }
+
     /// Validate the configuration
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), String> {
         if self.columns.is_empty() {
             return Err("Configuration must have at least one column".into());
         }
+        for (value, color) in &self.app.option_colors {
+            if !crate::ui::is_color(color) {
+                return Err(format!("Invalid color '{}' for option '{}'", color, value));
+            }
+        }
+        for (key, value) in [
+            ("banner_color", &self.app.banner_color),
+            ("banner_gradient", &self.app.banner_gradient),
+        ] {
+            if let Some(color) = value {
+                if crate::ui::parse_color(color).is_none() {
+                    return Err(format!(
+                        "Invalid {} '{}': expected \"#rrggbb\" or a color name",
+                        key, color
+                    ));
+                }
+            }
+        }
+
+        if let Some(ref selector) = self.selector {
+            if selector.param_type != ParameterType::Select
+                || selector.options.is_empty()
+                || selector.placeholder.is_empty()
+            {
+                return Err(format!(
+                    "Selector '{}' must be a select with a placeholder and options",
+                    selector.name
+                ));
+            }
+        }
+
+        for hook in &self.webhooks {
+            if hook.url.trim().is_empty() {
+                return Err("Webhook url cannot be empty".into());
+            }
+            if let Some(ref payload) = hook.payload {
+                crate::template::check_handlebars(payload)
+                    .map_err(|e| format!("Invalid payload of webhook '{}': {}", hook.url, e))?;
+            }
+        }
 
+        // hotkeys already bound, with the action they run
+        let mut hotkeys = Vec::new();
         for column in &self.columns {
             if column.id.is_empty() {
                 return Err("Column id cannot be empty".into());
             }
             if column.title.is_empty() {
-                return Err(format!("Column '{}' must have a title", column.id).into());
+                return Err(format!("Column '{}' must have a title", column.id));
             }
-            if column.actions.is_empty() {
-                return Err(format!("Column '{}' must have at least one action", column.id).into());
+            if column.weight == Some(0) {
+                return Err(format!(
+                    "Column '{}' must have a weight of at least 1",
+                    column.id
+                ));
+            }
+            // a source may legitimately come up empty; the column then says so
+            if column.actions.is_empty() && column.source.is_none() {
+                return Err(format!(
+                    "Column '{}' must have at least one action",
+                    column.id
+                ));
             }
 
             for action in &column.actions {
                 if action.label.is_empty() {
-                    return Err(
-                        format!("Action in column '{}' must have a label", column.id).into(),
-                    );
+                    return Err(format!(
+                        "Action in column '{}' must have a label",
+                        column.id
+                    ));
                 }
                 if action.template.is_empty() {
                     return Err(format!(
                         "Action '{}' in column '{}' must have a template",
                         action.label, column.id
-                    )
-                    .into());
+                    ));
+                }
+
+                if action.engine == TemplateEngine::Handlebars {
+                    for template in templates(action) {
+                        crate::template::check_handlebars(template).map_err(|e| {
+                            format!(
+                                "Action '{}' in column '{}' has an invalid template: {}",
+                                action.label, column.id, e
+                            )
+                        })?;
+                    }
+                }
+
+                if let Some(ref hotkey) = action.hotkey {
+                    let key = crate::ui::parse_key(hotkey).ok_or_else(|| {
+                        format!(
+                            "Action '{}' in column '{}' has an invalid hotkey '{}'",
+                            action.label, column.id, hotkey
+                        )
+                    })?;
+                    if let Some((_, other)) = hotkeys.iter().find(|(k, _)| *k == key) {
+                        return Err(format!(
+                            "Hotkey '{}' of action '{}' is already used by '{}'",
+                            hotkey, action.label, other
+                        ));
+                    }
+                    hotkeys.push((key, &action.label));
+                }
+
+                if action
+                    .typed_confirmation()
+                    .is_some_and(|phrase| phrase.trim().is_empty())
+                {
+                    return Err(format!(
+                        "Action '{}' in column '{}' has an empty confirm_phrase",
+                        action.label, column.id
+                    ));
                 }
 
                 // Validate parameters
@@ -137,23 +933,77 @@ impl Config {
                         return Err(format!(
                             "Parameter in action '{}' must have a name",
                             action.label
-                        )
-                        .into());
+                        ));
                     }
                     if param.placeholder.is_empty() {
                         return Err(format!(
                             "Parameter '{}' in action '{}' must have a placeholder",
                             param.name, action.label
-                        )
-                        .into());
+                        ));
+                    }
+                    if let Some(filter) = templates(action)
+                        .flat_map(|t| crate::template::unknown_filters(t, &param.placeholder))
+                        .next()
+                    {
+                        return Err(format!(
+                            "Parameter '{}' in action '{}' uses an unknown filter '{}' (known: {})",
+                            param.name,
+                            action.label,
+                            filter,
+                            crate::template::FILTERS.join(", ")
+                        ));
+                    }
+                    if let Some(ref format) = param.repeat_format {
+                        if !format.contains("{value}") {
+                            return Err(format!(
+                                "Parameter '{}' in action '{}' has a repeat_format without {{value}}",
+                                param.name, action.label
+                            ));
+                        }
+                    }
+                    if let Some(ref pattern) = param.pattern {
+                        Regex::new(pattern).map_err(|e| {
+                            format!(
+                                "Parameter '{}' in action '{}' has an invalid pattern: {}",
+                                param.name, action.label, e
+                            )
+                        })?;
+                    }
+                    if let Some(ref reference) = param.secret {
+                        if !param.param_type.is_typed() || !crate::secrets::is_reference(reference)
+                        {
+                            return Err(format!(
+                                "Parameter '{}' in action '{}' has an invalid secret '{}': \
+                                 expected a text parameter and op://, pass: or vault:",
+                                param.name, action.label, reference
+                            ));
+                        }
+                    }
+                    if param.source.is_some() && param.param_type != ParameterType::Select {
+                        return Err(format!(
+                            "Parameter '{}' in action '{}' has a source but is not a select",
+                            param.name, action.label
+                        ));
                     }
                     // Select type must have options
-                    if param.param_type == ParameterType::Select && param.options.is_empty() {
+                    if param.param_type == ParameterType::Select
+                        && param.options.is_empty()
+                        && param.source.is_none()
+                    {
                         return Err(format!(
                             "Parameter '{}' in action '{}' is type 'select' but has no options",
                             param.name, action.label
-                        )
-                        .into());
+                        ));
+                    }
+                    for option in &param.options {
+                        if let Some(ref color) = option.color {
+                            if !crate::ui::is_color(color) {
+                                return Err(format!(
+                                    "Option '{}' of parameter '{}' in action '{}' has an invalid color '{}'",
+                                    option.value, param.name, action.label, color
+                                ));
+                            }
+                        }
                     }
                 }
             }
@@ -162,3 +1012,11 @@ impl Config {
         Ok(())
     }
 }
+
+// The template of an action and its dry-run variant
+fn templates(action: &Action) -> impl Iterator<Item = &str> {
+    std::iter::once(action.template.as_str()).chain(action.dry_template.as_deref())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,220 @@
+//! Config loading errors, matched on their `CallbotError` variant
+
+use std::time::Duration;
+
+use super::{Config, SessionContext};
+use crate::error::CallbotError;
+use crate::transcript::Entry;
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn missing_file_is_not_found() {
+    let err = Config::load(fixture("missing.toml")).unwrap_err();
+    assert!(
+        matches!(err, CallbotError::ConfigNotFound { .. }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn syntax_error_reports_its_position() {
+    let err = Config::load(fixture("broken.toml")).unwrap_err();
+    let CallbotError::ConfigParse { line, .. } = err else {
+        panic!("expected a parse error, got {:?}", err);
+    };
+    assert_eq!(line, 5);
+}
+
+#[test]
+fn select_without_options_is_invalid() {
+    let err = Config::load(fixture("invalid.toml")).unwrap_err();
+    let CallbotError::Validation(message) = err else {
+        panic!("expected a validation error, got {:?}", err);
+    };
+    assert!(message.contains("has no options"), "{}", message);
+}
+
+#[test]
+fn provider_columns_are_merged() {
+    let config = Config::load(fixture("providers.toml")).unwrap();
+    let ids: Vec<&str> = config.columns.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["projects", "hosts"]);
+    let labels: Vec<&str> = config.columns[0]
+        .actions
+        .iter()
+        .map(|a| a.label.as_str())
+        .collect();
+    assert_eq!(labels, ["Build", "Test"]);
+}
+
+#[test]
+fn unknown_filter_is_invalid() {
+    let err = Config::load(fixture("unknown_filter.toml")).unwrap_err();
+    let CallbotError::Validation(message) = err else {
+        panic!("expected a validation error, got {:?}", err);
+    };
+    assert!(message.contains("unknown filter 'kebab'"), "{}", message);
+}
+
+#[test]
+fn vars_and_snippets_are_expanded() {
+    let config = Config::load(fixture("vars.toml")).unwrap();
+    let action = &config.columns[0].actions[0];
+    assert_eq!(action.template, "docker pull --quiet ghcr.io/acme/{IMAGE}");
+    assert_eq!(
+        action.parameters[0].options[0].label,
+        "API from ghcr.io/acme"
+    );
+    let selector = config.selector.as_ref().unwrap();
+    assert_eq!(selector.options[0].value, "ghcr.io/acme");
+}
+
+#[test]
+fn hidden_selector_options_are_dropped() {
+    let mut config = Config::load(fixture("vars.toml")).unwrap();
+    config.apply_session_context(&SessionContext {
+        user: None,
+        profile: Some("ci".to_string()),
+    });
+    let selector = config.selector.as_ref().unwrap();
+    let labels: Vec<_> = selector.options.iter().map(|o| o.label.as_str()).collect();
+    assert_eq!(labels, ["Main"]);
+}
+
+#[test]
+fn unknown_snippet_is_invalid() {
+    let err = Config::load(fixture("unknown_snippet.toml")).unwrap_err();
+    let CallbotError::Validation(message) = err else {
+        panic!("expected a validation error, got {:?}", err);
+    };
+    assert!(message.contains("unknown snippet 'ssh'"), "{}", message);
+}
+
+#[test]
+fn scripts_directory_column() {
+    let config = Config::load(fixture("scripts.toml")).unwrap();
+    let actions = &config.columns[0].actions;
+    let labels: Vec<_> = actions.iter().map(|a| a.label.as_str()).collect();
+    // README is not executable
+    assert_eq!(labels, ["clean", "deploy"]);
+    assert_eq!(
+        actions[0].description.as_deref(),
+        Some("Remove build artifacts")
+    );
+    assert!(actions[1]
+        .template
+        .ends_with("/tests/fixtures/scripts/deploy'"));
+}
+
+#[test]
+fn task_runner_columns() {
+    let config = Config::load(fixture("tasks.toml")).unwrap();
+    let make = &config.columns[0].actions;
+    let targets: Vec<_> = make.iter().map(|a| a.label.as_str()).collect();
+    assert_eq!(targets, ["build", "test", "clean", "lint"]);
+    assert_eq!(make[0].description.as_deref(), Some("Compile everything"));
+    assert_eq!(make[1].description.as_deref(), Some("Run the test suite"));
+    assert!(make[1]
+        .template
+        .ends_with("/tests/fixtures/tasks' -f 'Makefile' test"));
+
+    let just = &config.columns[1].actions;
+    let recipes: Vec<_> = just.iter().map(|a| a.label.as_str()).collect();
+    assert_eq!(recipes, ["build", "deploy"]);
+    let deploy = &just[1];
+    assert!(deploy
+        .template
+        .ends_with("justfile' deploy {branch} {env} {flags}"));
+    let params: Vec<_> = deploy
+        .parameters
+        .iter()
+        .map(|p| (p.name.as_str(), p.required, p.default.as_deref()))
+        .collect();
+    assert_eq!(
+        params,
+        [
+            ("branch", true, None),
+            ("env", false, Some("qlf")),
+            ("flags", false, None)
+        ]
+    );
+}
+
+#[test]
+fn package_scripts_column() {
+    let config = Config::load(fixture("npm.toml")).unwrap();
+    let actions = &config.columns[0].actions;
+    let scripts: Vec<_> = actions.iter().map(|a| a.label.as_str()).collect();
+    // prebuild runs with build; preview is not a hook, there is no "view" script
+    assert_eq!(scripts, ["build", "dev", "preview"]);
+    assert!(actions[0]
+        .template
+        .ends_with("/tests/fixtures/web' && pnpm run 'build'"));
+    assert_eq!(actions[0].description.as_deref(), Some("vite build"));
+}
+
+#[test]
+fn compose_services_column() {
+    let config = Config::load(fixture("compose.toml")).unwrap();
+    let actions = &config.columns[0].actions;
+    let labels: Vec<_> = actions.iter().map(|a| a.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        [
+            "db up",
+            "db down",
+            "db logs",
+            "db restart",
+            "api up",
+            "api down",
+            "api logs",
+            "api restart"
+        ]
+    );
+    assert_eq!(
+        actions[4].template,
+        "docker compose -f {COMPOSE_FILE} up -d api"
+    );
+    let file = &actions[4].parameters[0];
+    assert!(file.default.as_ref().unwrap().ends_with("/compose.yaml"));
+}
+
+#[test]
+fn systemd_units_column() {
+    let config = Config::load(fixture("systemd.toml")).unwrap();
+    let actions = &config.columns[0].actions;
+    let labels: Vec<_> = actions.iter().map(|a| a.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        [
+            "syncthing start",
+            "syncthing stop",
+            "syncthing restart",
+            "syncthing status"
+        ]
+    );
+    assert_eq!(actions[0].template, "systemctl --user start syncthing");
+    assert!(!actions[0].background);
+    assert!(actions[3].background);
+}
+
+#[test]
+fn webhooks() {
+    let config = Config::load(fixture("webhooks.toml")).unwrap();
+    let hook = &config.webhooks[0];
+    assert!(hook.matches("Deploy api", Some(1)));
+    assert!(hook.matches("Rollback", None));
+    assert!(!hook.matches("Deploy api", Some(0)));
+    assert!(!hook.matches("Rollback web", Some(1)));
+
+    let mut run = Entry::new("projects", "Deploy api", "deploy.sh api", Duration::ZERO);
+    run.exit_code = Some(3);
+    run.output = Some(vec!["step 1".into(), "say \"hi\"".into(), "boom".into()]);
+    let body: serde_json::Value = serde_json::from_str(&hook.body(&run).unwrap()).unwrap();
+    assert_eq!(body["text"], "Deploy api failed (exit 3)");
+    assert_eq!(body["log"], "say \"hi\"\nboom");
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::jobs::{Job, JobStatus};
+
+// Protocol: one JSON request per line, answered by one JSON line, e.g.
+//   {"cmd": "run", "column": "projects", "action": "Deploy", "params": {"ENV": "prod"}}
+//   {"ok": true, "job": 3}
+// Failures are answered with {"ok": false, "error": "..."}.
+
+/// A request sent to the running instance over its control socket
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    // run an action in the background, with parameter values by name
+    Run {
+        column: String,
+        action: String,
+        #[serde(default)]
+        params: HashMap<String, String>,
+    },
+    // load the config file again, like F5
+    Reload,
+    // status of every job
+    Jobs,
+    // status and output of one job
+    Job {
+        id: usize,
+    },
+}
+
+// parameter values may be secrets: only their names are shown, e.g. in the log
+impl fmt::Debug for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::Run {
+                column,
+                action,
+                params,
+            } => f
+                .debug_struct("Run")
+                .field("column", column)
+                .field("action", action)
+                .field("params", &params.keys().collect::<Vec<_>>())
+                .finish(),
+            Request::Reload => f.write_str("Reload"),
+            Request::Jobs => f.write_str("Jobs"),
+            Request::Job { id } => f.debug_struct("Job").field("id", id).finish(),
+        }
+    }
+}
+
+/// Where the socket is created: `$XDG_RUNTIME_DIR/callbot.sock`
+pub fn socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("callbot.sock"))
+}
+
+/// Successful reply with the given fields added
+pub fn ok(fields: Value) -> Value {
+    let mut reply = json!({ "ok": true });
+    if let (Some(reply), Value::Object(fields)) = (reply.as_object_mut(), fields) {
+        reply.extend(fields);
+    }
+    reply
+}
+
+pub fn error(message: impl Into<String>) -> Value {
+    json!({ "ok": false, "error": message.into() })
+}
+
+/// Summary of a job, with its output when `output` is set
+pub fn job_info(job: &Job, output: bool) -> Value {
+    let (status, code) = match job.status {
+        JobStatus::Running => ("running", None),
+        JobStatus::Exited(code) => ("exited", Some(code)),
+        JobStatus::Failed(_) => ("failed", None),
+        JobStatus::Aborted => ("aborted", None),
+    };
+    let mut info = json!({
+        "id": job.id,
+        "label": job.label,
+        "command": job.command,
+        "status": status,
+        "code": code,
+        "seconds": job.run_time().as_secs_f64(),
+    });
+    if output {
+        info["output"] = json!(job.output);
+    }
+    info
+}
+
+/// Removes the socket file when the instance that created it exits
+pub struct Socket {
+    path: PathBuf,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Listen on the control socket. Each request is passed to `forward` with the sender
+/// of its reply, and `forward` returns false once the app is gone. Returns None
+/// without a runtime dir, or when another instance is already listening.
+pub fn listen<F>(forward: F) -> Option<Socket>
+where
+    F: Fn(Request, mpsc::Sender<Value>) -> bool + Clone + Send + 'static,
+{
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        tracing::warn!(path = %path.display(), "control socket in use by another instance");
+        return None;
+    }
+    // left behind by an instance that did not exit cleanly
+    let _ = fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), "could not create control socket: {}", err);
+            return None;
+        }
+    };
+    tracing::info!(path = %path.display(), "control socket listening");
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let forward = forward.clone();
+            thread::spawn(move || serve(stream, forward));
+        }
+    });
+    Some(Socket { path })
+}
+
+// Answer the requests of one client until it disconnects or the app exits
+fn serve(stream: UnixStream, forward: impl Fn(Request, mpsc::Sender<Value>) -> bool) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                tracing::debug!(?request, "control request");
+                let (tx, rx) = mpsc::channel();
+                if !forward(request, tx) {
+                    break;
+                }
+                match rx.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => break,
+                }
+            }
+            Err(err) => error(format!("invalid request: {}", err)),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
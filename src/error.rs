@@ -0,0 +1,84 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Errors of the command line, loading the config and running commands
+#[derive(Debug, Error)]
+pub enum CallbotError {
+    #[error(
+        "Configuration file not found.\nSearched:\n{}\n\nPlease create a config.toml file in one of these locations.",
+        searched_list(.searched)
+    )]
+    ConfigNotFound { searched: Vec<PathBuf> },
+
+    #[error("Failed to read config file '{}': {source}", path.display())]
+    ConfigRead { path: PathBuf, source: io::Error },
+
+    #[error("Failed to parse config file '{}' at line {line}, column {col}: {message}", path.display())]
+    ConfigParse {
+        path: PathBuf,
+        // 1-based position of the error in the file
+        line: usize,
+        col: usize,
+        message: String,
+    },
+
+    // a column source (OpenAPI spec...) could not generate its actions
+    #[error("Column '{column}': {message}")]
+    Source { column: String, message: String },
+
+    // a `[[providers]]` program failed or printed something else than columns
+    #[error("Provider '{command}': {message}")]
+    Provider { command: String, message: String },
+
+    // the config parsed but is not usable, e.g. a select without options
+    #[error("{0}")]
+    Validation(String),
+
+    // bad command line arguments, with the usage appended
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("Session: {0}")]
+    Session(String),
+
+    #[error("Failed to start '{command}': {source}")]
+    Spawn { command: String, source: io::Error },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl CallbotError {
+    /// Short title of the popup reporting the error
+    pub fn title(&self) -> &'static str {
+        match self {
+            CallbotError::ConfigNotFound { .. } => "Config not found",
+            CallbotError::ConfigRead { .. } | CallbotError::Io(_) => "I/O error",
+            CallbotError::ConfigParse { .. } => "Config syntax error",
+            CallbotError::Source { .. } => "Column source failed",
+            CallbotError::Provider { .. } => "Provider failed",
+            CallbotError::Validation(_) => "Invalid config",
+            CallbotError::Usage(_) => "Usage",
+            CallbotError::Session(_) => "Session error",
+            CallbotError::Spawn { .. } => "Command failed",
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset in `text`
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count() + 1;
+    let col = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, col)
+}
+
+fn searched_list(searched: &[PathBuf]) -> String {
+    searched
+        .iter()
+        .map(|dir| format!("- {}", dir.display()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
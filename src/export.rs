@@ -0,0 +1,59 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use crate::state::now_secs;
+use crate::template::{shell_quote, slug};
+
+/// Standalone script running `command` from `dir`, where callbot runs it
+pub fn script(label: &str, description: Option<&str>, command: &str, dir: &Path) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(&format!("# {}\n", label));
+    if let Some(description) = description {
+        script.push_str(&format!("# {}\n", description));
+    }
+    script.push_str("# Exported from callbot\n\n");
+    script.push_str(&format!(
+        "cd {} || exit 1\n",
+        shell_quote(&dir.to_string_lossy())
+    ));
+    script.push_str(command);
+    script.push('\n');
+    script
+}
+
+/// Shell alias running `command`, named after the label, e.g.
+/// `alias deploy-api='deploy.sh api'`
+pub fn alias(label: &str, command: &str) -> String {
+    format!("alias {}={}", slug(label), shell_quote(command))
+}
+
+/// Default file name of the script of an action
+pub fn script_name(label: &str) -> String {
+    format!("{}.sh", slug(label))
+}
+
+/// Default file name of the output of a run, e.g. `deploy-api-1760612400.log`
+pub fn log_name(label: &str) -> String {
+    format!("{}-{}.log", slug(label), now_secs())
+}
+
+/// Write the output of a run to `path`, one line each; an existing file is left alone
+pub fn write_output(path: &Path, output: &[String]) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    for line in output {
+        writeln!(file, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write an executable script to `path`; an existing file is left alone
+pub fn write_script(path: &Path, script: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o755)
+        .open(path)?;
+    file.write_all(script.as_bytes())
+}
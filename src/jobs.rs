@@ -0,0 +1,515 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::runner::exit_code;
+use crate::state::now_secs;
+
+// lines kept per job; the oldest are dropped first
+const MAX_OUTPUT_LINES: usize = 10_000;
+// spool files shown by `--follow`, the newest ones
+const MAX_FOLLOWED: usize = 20;
+
+/// State of a background job
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Exited(i32),
+    // the command could not be waited on (killed by a signal, I/O error...)
+    Failed(String),
+    // stopped from the app, see `Jobs::abort_running`
+    Aborted,
+}
+
+/// A command running (or run) in the background with its captured output
+#[derive(Debug)]
+pub struct Job {
+    pub id: usize,
+    pub label: String,
+    pub command: String,
+    pub status: JobStatus,
+    // stdout and stderr interleaved in arrival order
+    pub output: Vec<String>,
+    pub started: Instant,
+    // set once the job is no longer running
+    pub elapsed: Option<Duration>,
+    // copy of the output in the follow directory and its path, see `Jobs::with_spool`
+    spool: Option<(File, PathBuf)>,
+    // process of a job started by this instance (not simulated or followed), leading
+    // its own process group
+    pid: Option<u32>,
+    // set by `Jobs::abort_running`, the job is reported aborted once it ends
+    aborted: bool,
+}
+
+impl Job {
+    pub fn is_running(&self) -> bool {
+        self.status == JobStatus::Running
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status == JobStatus::Exited(0)
+    }
+
+    /// Time the job has been running for, or ran for once finished
+    pub fn run_time(&self) -> Duration {
+        self.elapsed.unwrap_or_else(|| self.started.elapsed())
+    }
+}
+
+/// What changed in the jobs since the last `Jobs::poll`
+#[derive(Debug, Default)]
+pub struct JobUpdates {
+    // lines of output received
+    pub lines: usize,
+    // ids of the jobs that finished
+    pub finished: Vec<usize>,
+}
+
+impl JobUpdates {
+    pub fn is_empty(&self) -> bool {
+        self.lines == 0 && self.finished.is_empty()
+    }
+}
+
+// Messages sent by the reader threads of a job
+enum JobEvent {
+    Line(usize, String),
+    Done(usize, JobStatus),
+}
+
+// Called by the reader threads after each message, see `Jobs::on_update`
+type Notify = Arc<dyn Fn() + Send + Sync>;
+
+// Sending side of the job messages, waking up the UI after each one
+#[derive(Clone)]
+struct JobSender {
+    tx: Sender<JobEvent>,
+    notify: Option<Notify>,
+}
+
+impl JobSender {
+    // false once `Jobs` is gone
+    fn send(&self, event: JobEvent) -> bool {
+        let sent = self.tx.send(event).is_ok();
+        if let Some(ref notify) = self.notify {
+            notify();
+        }
+        sent
+    }
+}
+
+/// Background jobs of the session. Output is streamed by one thread per job over a
+/// channel and collected by `poll`, so the UI thread never blocks on a child.
+pub struct Jobs {
+    jobs: Vec<Job>,
+    next_id: usize,
+    tx: Sender<JobEvent>,
+    rx: Receiver<JobEvent>,
+    // where job output is mirrored for other terminals running `--follow`
+    spool_dir: Option<PathBuf>,
+    notify: Option<Notify>,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            jobs: Vec::new(),
+            next_id: 1,
+            tx,
+            rx,
+            spool_dir: None,
+            notify: None,
+        }
+    }
+}
+
+impl Jobs {
+    /// Jobs whose output is also written to a file in `dir`, if given
+    pub fn with_spool(dir: Option<PathBuf>) -> Self {
+        Self {
+            spool_dir: dir,
+            ..Self::default()
+        }
+    }
+
+    /// Call `notify` from the job threads whenever there is something to `poll`, so
+    /// the UI can sleep until then
+    pub fn on_update(&mut self, notify: impl Fn() + Send + Sync + 'static) {
+        self.notify = Some(Arc::new(notify));
+    }
+
+    /// Start `command` with `sh -c`, detached from the terminal in a process group of
+    /// its own, and return its job id.
+    /// The job shows, logs and mirrors `shown` instead, the command with its secrets
+    /// masked.
+    pub fn spawn(
+        &mut self,
+        label: &str,
+        command: &str,
+        shown: &str,
+    ) -> Result<usize, Box<dyn Error>> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .map_err(|e| format!("Failed to start '{}': {}", shown, e))?;
+
+        let id = self.push(label, shown, JobStatus::Running, Vec::new());
+        tracing::info!(id, label, pid = child.id(), "job started: {}", shown);
+        let spool = self.open_spool(id, label, shown);
+        if let Some(job) = self.get_mut(id) {
+            job.spool = spool;
+            job.pid = Some(child.id());
+        }
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let tx = JobSender {
+            tx: self.tx.clone(),
+            notify: self.notify.clone(),
+        };
+        thread::spawn(move || {
+            let stderr_reader = stderr.map(|err| {
+                let tx = tx.clone();
+                thread::spawn(move || forward_lines(id, err, &tx))
+            });
+            if let Some(out) = stdout {
+                forward_lines(id, out, &tx);
+            }
+            if let Some(handle) = stderr_reader {
+                let _ = handle.join();
+            }
+            let status = match child.wait() {
+                Ok(status) => JobStatus::Exited(exit_code(status)),
+                Err(err) => JobStatus::Failed(err.to_string()),
+            };
+            tx.send(JobEvent::Done(id, status));
+        });
+        Ok(id)
+    }
+
+    /// Record a job that already finished, e.g. a simulated run
+    pub fn push_finished(&mut self, label: &str, command: &str, output: &str, code: i32) -> usize {
+        let lines = output.lines().map(clean_line).collect();
+        let id = self.push(label, command, JobStatus::Exited(code), lines);
+        if let Some(job) = self.jobs.last_mut() {
+            job.elapsed = Some(Duration::ZERO);
+        }
+        id
+    }
+
+    fn push(
+        &mut self,
+        label: &str,
+        command: &str,
+        status: JobStatus,
+        output: Vec<String>,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id,
+            label: label.to_string(),
+            command: command.to_string(),
+            status,
+            output,
+            started: Instant::now(),
+            elapsed: None,
+            spool: None,
+            pid: None,
+            aborted: false,
+        });
+        id
+    }
+
+    // Create the spool file of a job: a header with the label and command, then
+    // the output as it arrives. The outcome goes to a `.status` file next to it once
+    // the job ends, so no output line can pass for one. Spooling is best effort and
+    // never prevents a job from running.
+    fn open_spool(&self, id: usize, label: &str, command: &str) -> Option<(File, PathBuf)> {
+        let dir = self.spool_dir.as_ref()?;
+        fs::create_dir_all(dir).ok()?;
+        prune_spools(dir);
+        let path = dir.join(format!("{}-{}-{}.log", now_secs(), std::process::id(), id));
+        let mut file = File::create(&path).ok()?;
+        writeln!(file, "# {}\n$ {}", label, command).ok()?;
+        Some((file, path))
+    }
+
+    /// Collect pending output and report what changed since the last call
+    pub fn poll(&mut self) -> JobUpdates {
+        let mut updates = JobUpdates::default();
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                JobEvent::Line(id, line) => {
+                    updates.lines += 1;
+                    if let Some(job) = self.get_mut(id) {
+                        if let Some((ref mut spool, _)) = job.spool {
+                            let _ = writeln!(spool, "{}", line);
+                        }
+                        job.output.push(line);
+                        if job.output.len() > MAX_OUTPUT_LINES {
+                            let excess = job.output.len() - MAX_OUTPUT_LINES;
+                            job.output.drain(..excess);
+                        }
+                    }
+                }
+                JobEvent::Done(id, status) => {
+                    if let Some(job) = self.get_mut(id) {
+                        let status = if job.aborted {
+                            JobStatus::Aborted
+                        } else {
+                            status
+                        };
+                        if let Some((_, path)) = job.spool.take() {
+                            let outcome = match status {
+                                JobStatus::Exited(code) => format!("exit {}", code),
+                                JobStatus::Failed(ref err) => format!("failed: {}", err),
+                                JobStatus::Aborted => "aborted".to_string(),
+                                JobStatus::Running => String::new(),
+                            };
+                            let _ = fs::write(path.with_extension("status"), outcome);
+                        }
+                        let elapsed = job.started.elapsed();
+                        job.elapsed = Some(elapsed);
+                        tracing::info!(
+                            id,
+                            label = %job.label,
+                            elapsed_ms = elapsed.as_millis() as u64,
+                            "job finished: {:?}",
+                            status
+                        );
+                        job.status = status;
+                        updates.finished.push(id);
+                    }
+                }
+            }
+        }
+        updates
+    }
+
+    /// Send SIGTERM to the process group of every job still running, e.g. before
+    /// exiting on a signal. Their threads report them as finished as usual.
+    pub fn kill_running(&self) {
+        for job in self.jobs.iter().filter(|j| j.is_running()) {
+            terminate(job);
+        }
+    }
+
+    /// Send SIGTERM to the whole process group of every running job, what the
+    /// commands started included, and have them reported aborted once they end.
+    /// Returns how many were.
+    pub fn abort_running(&mut self) -> usize {
+        let mut aborted = 0;
+        for job in self
+            .jobs
+            .iter_mut()
+            .filter(|j| j.is_running() && !j.aborted)
+        {
+            if terminate(job) {
+                job.aborted = true;
+                aborted += 1;
+            }
+        }
+        aborted
+    }
+
+    /// Drop a finished job and its output; running jobs are kept
+    pub fn forget(&mut self, id: usize) {
+        self.jobs.retain(|j| j.id != id || j.is_running());
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    /// The last `count` jobs, oldest first
+    pub fn recent(&self, count: usize) -> &[Job] {
+        &self.jobs[self.jobs.len().saturating_sub(count)..]
+    }
+
+    /// Most recently started job
+    pub fn latest(&self) -> Option<&Job> {
+        self.jobs.last()
+    }
+
+    /// Last finished job started before `job` under the same label, e.g. the run
+    /// before of a watched action
+    pub fn previous(&self, job: &Job) -> Option<&Job> {
+        self.jobs
+            .iter()
+            .rev()
+            .filter(|j| j.id < job.id && j.label == job.label)
+            .find(|j| !j.is_running())
+    }
+
+    /// Jobs still running, oldest first
+    pub fn running(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|j| j.is_running())
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.jobs.iter().filter(|j| j.is_running()).count()
+    }
+
+    /// Running job that was started first
+    pub fn oldest_running(&self) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.is_running())
+    }
+
+    /// Replace the jobs with the ones other instances mirrored in `dir` (`--follow`)
+    pub fn follow(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+            .collect();
+        // names start with the start time, so this is oldest first
+        paths.sort();
+        let start = paths.len().saturating_sub(MAX_FOLLOWED);
+        self.jobs = paths[start..]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, path)| read_spool(i + 1, path))
+            .collect();
+    }
+}
+
+// SIGTERM the process group of a job started by this instance; whether there was one
+fn terminate(job: &Job) -> bool {
+    let Some(pid) = job.pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) else {
+        return false;
+    };
+    tracing::info!(id = job.id, pid, "killing job");
+    // SAFETY: kill(2) has no memory effects; at worst the group is gone already
+    unsafe {
+        libc::kill(-pid, libc::SIGTERM);
+    }
+    true
+}
+
+// Parse a spool file written by `Jobs::open_spool`, with the status file of a
+// finished job
+fn read_spool(id: usize, path: &Path) -> Option<Job> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+    let label = lines.next()?.strip_prefix("# ")?.to_string();
+    let command = lines.next()?.strip_prefix("$ ")?.to_string();
+    let output: Vec<String> = lines.map(str::to_string).collect();
+    let outcome = fs::read_to_string(path.with_extension("status")).ok();
+    let status = match outcome.as_deref() {
+        None => JobStatus::Running,
+        Some("aborted") => JobStatus::Aborted,
+        Some(outcome) => match outcome.strip_prefix("failed: ") {
+            Some(err) => JobStatus::Failed(err.to_string()),
+            None => JobStatus::Exited(
+                outcome
+                    .strip_prefix("exit ")
+                    .and_then(|code| code.parse().ok())
+                    .unwrap_or(-1),
+            ),
+        },
+    };
+    // the file name starts with the unix time the job was started at
+    let started_secs: u64 = path
+        .file_name()?
+        .to_str()?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()?;
+    let age = Duration::from_secs(now_secs().saturating_sub(started_secs));
+    Some(Job {
+        id,
+        label,
+        command,
+        status,
+        output,
+        started: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+        elapsed: None,
+        spool: None,
+        pid: None,
+        aborted: false,
+    })
+}
+
+// Remove the spools of finished jobs older than the `MAX_FOLLOWED` newest, which
+// `--follow` no longer shows; those of running jobs stay
+fn prune_spools(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    // the spool about to be created counts among the newest
+    paths.sort();
+    let end = paths.len().saturating_sub(MAX_FOLLOWED - 1);
+    for path in &paths[..end] {
+        let status = path.with_extension("status");
+        if status.exists() {
+            let _ = fs::remove_file(path);
+            let _ = fs::remove_file(status);
+        }
+    }
+}
+
+// Send every line of `reader` to the job's channel until EOF
+fn forward_lines(id: usize, reader: impl Read, tx: &JobSender) {
+    for line in BufReader::new(reader).split(b'\n').map_while(Result::ok) {
+        let line = clean_line(&String::from_utf8_lossy(&line));
+        if !tx.send(JobEvent::Line(id, line)) {
+            break;
+        }
+    }
+}
+
+// Make a line of output safe to draw: drop ANSI escape sequences and control
+// characters, expand tabs
+fn clean_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => {
+                // CSI sequences end with a letter; other escapes are two characters long
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                } else {
+                    chars.next();
+                }
+            }
+            '\t' => out.push_str("    "),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,54 @@
+//! Jobs spooled for `--follow` and read back from their spools
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{JobStatus, Jobs};
+
+// An empty spool directory of its own, so parallel or repeated runs don't meet
+fn spool_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let dir =
+        std::env::temp_dir().join(format!("callbot-{}-{}-{}", name, std::process::id(), nanos));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn followed_jobs_keep_their_outcome_apart_from_the_output() {
+    let dir = spool_dir("follow");
+    // finished spools of an earlier session, more than `--follow` shows
+    for i in 0..30 {
+        let name = format!("{}-1-{}", 1000 + i, i);
+        fs::write(dir.join(format!("{}.log", name)), "# Old\n$ true\n").unwrap();
+        fs::write(dir.join(format!("{}.status", name)), "exit 0").unwrap();
+    }
+
+    let mut jobs = Jobs::with_spool(Some(dir.clone()));
+    // the last line looks like the outcome lines spools used to end with
+    let command = "echo '# exit 3'";
+    let id = jobs.spawn("Echo", command, command).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while jobs.get(id).unwrap().is_running() && Instant::now() < deadline {
+        jobs.poll();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let logs = fs::read_dir(&dir)
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension().unwrap() == "log")
+        .count();
+    assert_eq!(logs, 20);
+    let mut follower = Jobs::default();
+    follower.follow(&dir);
+    // followed jobs are numbered oldest first
+    let job = follower.get(20).unwrap();
+    assert_eq!(job.label, "Echo");
+    assert_eq!(job.status, JobStatus::Exited(0));
+    assert_eq!(job.output, ["# exit 3"]);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt;
+use tracing_subscriber::prelude::*;
+
+// lines kept in memory for the debug overlay
+const TAIL_LINES: usize = 500;
+
+// level used when CALLBOT_LOG is unset or not a level
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+// most recent log lines, oldest first
+static TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Directory of the log files (e.g. ~/.local/share/callbot/logs), one per day
+pub fn dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "callbot").map(|dirs| dirs.data_local_dir().join("logs"))
+}
+
+/// Send `tracing` events to a daily log file and to the in-memory tail shown by the
+/// debug overlay. The level comes from CALLBOT_LOG (error, warn, info, debug, trace,
+/// off). Keep the returned guard alive until exit so buffered lines are flushed.
+pub fn init() -> Option<WorkerGuard> {
+    let level = std::env::var("CALLBOT_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(DEFAULT_LEVEL);
+
+    // logging is best effort: without a log dir only the overlay gets the lines
+    let (file, guard) = match dir().filter(|dir| fs::create_dir_all(dir).is_ok()) {
+        Some(dir) => {
+            let appender = tracing_appender::rolling::daily(dir, "callbot.log");
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer().with_writer(writer).with_ansi(false);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    let tail = fmt::layer()
+        .with_writer(|| TailWriter)
+        .with_ansi(false)
+        .with_target(false)
+        .with_timer(fmt::time::uptime());
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(tail)
+        .with(file)
+        .try_init()
+        .ok()?;
+    guard
+}
+
+/// The last `count` log lines, oldest first
+pub fn tail(count: usize) -> Vec<String> {
+    let tail = TAIL.lock().unwrap_or_else(|e| e.into_inner());
+    tail.iter()
+        .skip(tail.len().saturating_sub(count))
+        .cloned()
+        .collect()
+}
+
+// Appends each formatted event to TAIL, dropping the oldest lines past TAIL_LINES
+struct TailWriter;
+
+impl Write for TailWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut tail = TAIL.lock().unwrap_or_else(|e| e.into_inner());
+        for line in text.lines() {
+            tail.push_back(line.to_string());
+        }
+        let excess = tail.len().saturating_sub(TAIL_LINES);
+        tail.drain(..excess);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
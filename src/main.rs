@@ -1,60 +1,109 @@
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::error::Error;
 use std::io;
 use std::path::PathBuf;
 
+mod cli;
+mod clipboard;
+mod completions;
 mod config;
+mod control;
+mod error;
+mod export;
+mod jobs;
+mod logging;
+mod recovery;
 mod runner;
+mod secrets;
+mod session;
+mod shell_history;
+mod sources;
+mod state;
+mod status;
+mod template;
+mod transcript;
 mod ui;
+mod webhook;
 
+use cli::Cli;
 use config::Config;
+use error::CallbotError;
+use runner::{enter_tui, leave_tui};
+use session::Session;
+use state::State;
+use status::RunnerStatus;
+use transcript::Transcript;
 use ui::run_app as ui_run_app;
 use ui::App as UiApp;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
+    // flushes the log file when dropped at exit
+    let _log_guard = logging::init();
+    if let Err(err) = run() {
+        tracing::error!("{}", err);
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), CallbotError> {
+    let cli = Cli::parse()?;
+    if let Some(ref command) = cli.command {
+        return completions::run(command);
+    }
+
     // Load configuration before initializing the terminal
     // Try multiple locations: current directory first, then next to executable
     let config_path = find_config_file()?;
     let config = Config::load(&config_path)?;
+    // load the session before taking over the terminal so errors are readable
+    let session = cli
+        .session
+        .as_deref()
+        .map(Session::load)
+        .transpose()
+        .map_err(|e| CallbotError::Session(e.to_string()))?;
+    if cli.follow && config.app.follow_dir.is_none() {
+        return Err(CallbotError::Usage(
+            "--follow needs `follow_dir` in the [app] section of config.toml".to_string(),
+        ));
+    }
+    // a follower only watches; the status line belongs to the instance running jobs
+    if !cli.follow {
+        status::publish(&config.app, RunnerStatus::Idle, None);
+    }
 
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    // switch to the alternate screen and enable mouse capture so the app does not
-    // leave UI artifacts on the main terminal when it exits
-    crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
-    // ensure the alternate screen is cleared and hide the cursor while the app runs
-    terminal.clear()?;
-    terminal.hide_cursor()?;
+    // raw mode, alternate screen and mouse capture so the app does not leave UI
+    // artifacts on the main terminal when it exits
+    enter_tui(&mut terminal)?;
 
     // create the UI app and hand off to the ui module
-    let app = UiApp::new(config);
+    let mut app = UiApp::new(config, State::load());
+    app.restore_ui_state();
+    app.simulate = cli.simulate;
+    app.follow = cli.follow;
+    app.split_view = cli.follow;
+    if let Some(mut session) = session {
+        app.open_secrets(&mut session);
+        app.restore_session(&session);
+    }
+    app.session = cli.session;
+    app.transcript = cli.record.as_deref().map(Transcript::new);
+    app.config_path = Some(config_path);
+    app.offer_recovery();
     let res = ui_run_app(&mut terminal, app);
 
     // restore terminal state
-    disable_raw_mode()?;
-    crossterm::execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
-    if let Err(err) = res {
-        println!("Error: {}", err);
-    }
+    leave_tui(&mut terminal)?;
 
+    res?;
     Ok(())
 }
 
 /// Find config.toml in current directory or next to executable
-fn find_config_file() -> Result<PathBuf, Box<dyn Error>> {
+fn find_config_file() -> Result<PathBuf, CallbotError> {
     // Try current working directory first
     let cwd_config = PathBuf::from("config.toml");
     if cwd_config.exists() {
@@ -63,24 +112,14 @@ fn find_config_file() -> Result<PathBuf, Box<dyn Error>> {
 
     // Try next to executable
     let exe_path = std::env::current_exe()?;
-    if let Some(exe_dir) = exe_path.parent() {
-        let exe_config = exe_dir.join("config.toml");
+    let exe_config = exe_path.parent().map(|dir| dir.join("config.toml"));
+    if let Some(ref exe_config) = exe_config {
         if exe_config.exists() {
-            return Ok(exe_config);
+            return Ok(exe_config.clone());
         }
     }
 
-    Err(format!(
-        "Configuration file 'config.toml' not found.\n\
-         Searched in:\n\
-         - Current directory: {}\n\
-         - Executable directory: {}\n\n\
-         Please create a config.toml file in one of these locations.",
-        std::env::current_dir()?.display(),
-        exe_path
-            .parent()
-            .map(|p| p.display().to_string())
-            .unwrap_or_default()
-    )
-    .into())
+    let mut searched = vec![std::env::current_dir()?.join("config.toml")];
+    searched.extend(exe_config);
+    Err(CallbotError::ConfigNotFound { searched })
 }
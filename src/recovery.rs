@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+use crate::session::SavedParam;
+
+/// Unsaved work checkpointed while the UI runs: parameter values changed from
+/// their defaults and the value being typed. Each instance writes its own file and
+/// removes it on a clean exit, so a file whose process is gone was left by a crash.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Recovery {
+    // process that wrote the checkpoint
+    pub pid: u32,
+    #[serde(default)]
+    pub params: Vec<SavedParam>,
+    // parameter in edit mode, its value being the text typed so far
+    #[serde(default)]
+    pub edit: Option<SavedEdit>,
+}
+
+/// A parameter edit in progress
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedEdit {
+    #[serde(flatten)]
+    pub param: SavedParam,
+    // value before editing, restored on Esc
+    pub original: String,
+}
+
+impl Recovery {
+    pub fn new(params: Vec<SavedParam>, edit: Option<SavedEdit>) -> Self {
+        Self {
+            pid: std::process::id(),
+            params,
+            edit,
+        }
+    }
+
+    /// Directory of the checkpoints (e.g. ~/.local/share/callbot/recovery)
+    pub fn dir() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "callbot")
+            .map(|dirs| dirs.data_local_dir().join("recovery"))
+    }
+
+    fn path(pid: u32) -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join(format!("{}.json", pid)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.edit.is_none()
+    }
+
+    /// The most recent checkpoint left by an instance that is no longer running
+    pub fn orphaned() -> Option<Self> {
+        let entries = fs::read_dir(Self::dir()?).ok()?;
+        entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let modified = path.metadata().ok()?.modified().ok()?;
+                let recovery: Recovery =
+                    serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+                (!is_alive(recovery.pid)).then_some((modified, recovery))
+            })
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, recovery)| recovery)
+    }
+
+    /// Write the checkpoint, readable by the user only as values may be secrets.
+    /// The file is replaced in one step so a crash while writing keeps the last one.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(self.pid).ok_or("Could not determine the user data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.partial");
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&partial)?;
+        file.write_all(serde_json::to_string(self)?.as_bytes())?;
+        fs::rename(&partial, &path)
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Remove the checkpoint once it is restored, declined or no longer needed
+    pub fn discard(&self) {
+        if let Some(path) = Self::path(self.pid) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// One line per value, for the restore prompt
+    pub fn describe(&self) -> Vec<String> {
+        let editing = |p: &SavedParam| {
+            self.edit.as_ref().is_some_and(|edit| {
+                (&edit.param.column, &edit.param.action, &edit.param.param)
+                    == (&p.column, &p.action, &p.param)
+            })
+        };
+        let mut lines: Vec<String> = self
+            .params
+            .iter()
+            .filter(|p| !editing(p))
+            .map(|p| format!("{} › {}: {}", p.action, p.param, p.value))
+            .collect();
+        if let Some(ref edit) = self.edit {
+            lines.push(format!(
+                "{} › {}: {} (being edited)",
+                edit.param.action, edit.param.param, edit.param.value
+            ));
+        }
+        lines
+    }
+}
+
+// Whether a process with this id still exists
+fn is_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 sends nothing, it only checks that the process exists
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    // EPERM: it exists but belongs to another user
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
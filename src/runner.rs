@@ -1,34 +1,146 @@
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
-use ratatui::backend::CrosstermBackend;
+use ratatui::backend::{Backend, CrosstermBackend, TestBackend};
 use ratatui::Terminal;
-use std::error::Error;
-use std::process::Command;
+use std::io::{self, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus};
+
+use crate::config::{Config, Simulation};
+use crate::error::CallbotError;
+use crate::shell_history;
+use crate::status::{self, RunnerStatus};
+
+/// Terminal modes of a backend around the TUI: raw mode, alternate screen, mouse
+/// capture and bracketed paste. A command run in the foreground gets the terminal between `leave` and
+/// `enter`.
+pub trait Handover {
+    fn enter(&mut self) -> io::Result<()>;
+    fn leave(&mut self) -> io::Result<()>;
+}
+
+impl<W: Write> Handover for CrosstermBackend<W> {
+    fn enter(&mut self) -> io::Result<()> {
+        enable_raw_mode()?;
+        execute!(
+            self,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )
+    }
+
+    fn leave(&mut self) -> io::Result<()> {
+        disable_raw_mode()?;
+        execute!(
+            self,
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste
+        )
+    }
+}
+
+// an in-memory buffer has no terminal modes
+impl Handover for TestBackend {
+    fn enter(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn leave(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
-// dry-run removed: run directly with `run_command` to execute actions
+/// Take over the terminal for the TUI, initially or after a handover that didn't
+/// end the process
+pub fn enter_tui<B: Backend + Handover>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    terminal.backend_mut().enter()?;
+    // start from a clean screen and hide the cursor while the app runs
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+    Ok(())
+}
 
-pub fn run_command(
-    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+/// Give the terminal back in normal mode
+pub fn leave_tui<B: Backend + Handover>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    terminal.backend_mut().leave()?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Hand the terminal over to `command` and wait for it. The TUI is left in normal
+/// mode; callers are expected to exit rather than redraw afterwards.
+pub fn run_command<B: Backend + Handover>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    label: &str,
     command: &str,
-) -> Result<(), Box<dyn Error>> {
+    shown: &str,
+) -> Result<ExitStatus, CallbotError> {
     // Restore terminal to normal mode and hand over TTY to child process
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    leave_tui(terminal)?;
+
+    // Record the command in the parent shell's history before running it so it is
+    // recallable even if the command is long-running or interrupted
+    if config.app.shell_history {
+        if let Err(err) = shell_history::append(shown, config.app.history_file.as_deref()) {
+            eprintln!("Could not write shell history: {}", err);
+        }
+    }
+
+    status::publish(&config.app, RunnerStatus::Running, Some(label));
+    tracing::info!(label, "foreground run: {}", shown);
 
     // Spawn a shell to run the command so shell features are available
-    let status = Command::new("sh").arg("-c").arg(command).status()?;
+    let status = match Command::new("sh").arg("-c").arg(command).status() {
+        Ok(status) => status,
+        Err(err) => {
+            tracing::error!(label, "could not start: {}", err);
+            status::publish(&config.app, RunnerStatus::Failed, Some(label));
+            return Err(CallbotError::Spawn {
+                command: shown.to_string(),
+                source: err,
+            });
+        }
+    };
+
+    if status.success() {
+        status::publish(&config.app, RunnerStatus::Idle, None);
+    } else {
+        status::publish(&config.app, RunnerStatus::Failed, Some(label));
+    }
 
+    tracing::info!(label, "foreground run finished: {}", status);
     eprintln!("Command exited with: {}", status);
-    // Do not re-enter the TUI. Exit the process with the same status code so
-    // the user remains in the spawned shell environment after the command.
-    let code = status.code().unwrap_or(0);
-    std::process::exit(code);
+    Ok(status)
 }
+
+/// Exit code of a finished command as a shell reports it: 128 plus the signal for a
+/// command killed by one (130 after Ctrl+C)
+pub fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+/// Fake run for `--simulate`: nothing is executed, the action's configured output and
+/// exit code (or a successful empty run) are returned instead
+pub fn simulate_command(command: &str, simulation: Option<&Simulation>) -> (String, i32) {
+    let default = Simulation::default();
+    let simulation = simulation.unwrap_or(&default);
+    let output = simulation
+        .output
+        .clone()
+        .unwrap_or_else(|| format!("(simulated) {}", command));
+    (output, simulation.exit)
+}
+
+#[cfg(test)]
+mod tests;
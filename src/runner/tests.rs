@@ -0,0 +1,17 @@
+//! Exit codes of finished commands
+
+use super::exit_code;
+
+#[test]
+fn commands_killed_by_a_signal_fail() {
+    let status = |command: &str| {
+        let status = std::process::Command::new("sh")
+            .args(["-c", command])
+            .status()
+            .expect("sh runs");
+        exit_code(status)
+    };
+    assert_eq!(status("exit 3"), 3);
+    // what a shell reports after Ctrl+C
+    assert_eq!(status("kill -INT $$"), 130);
+}
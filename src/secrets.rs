@@ -0,0 +1,144 @@
+//! Secrets of parameters. Password values only reach the disk through the
+//! configured `secret_store`, sealed: a sealed value is what is written in their
+//! place, and opening it gives the value back.
+//!
+//! A parameter can also hold a reference to a secret manager instead of a value,
+//! read only when the action runs:
+//! - `op://vault/item/field`, with the 1Password CLI (`op read`)
+//! - `pass:path/to/entry`, the first line of a `pass` entry
+//! - `vault:path/to/secret#field`, a field of a HashiCorp Vault KV secret
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::{expand_home, Parameter, SecretStore};
+
+/// Shown instead of a password value
+pub const MASK: &str = "••••••";
+
+// keyring attribute the values are stored under, with the parameter id as value
+const KEYRING_ATTRIBUTE: &str = "callbot-param";
+
+/// Whether `value` of `param` must not be shown or saved as is: a secret, as opposed
+/// to a reference to one
+pub fn hides(param: &Parameter, value: &str) -> bool {
+    param.is_secret() && !value.is_empty() && !is_reference(value)
+}
+
+/// Whether `value` is a reference to a secret manager rather than a value
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with("op://")
+        || value
+            .strip_prefix("pass:")
+            .is_some_and(|path| !path.is_empty())
+        || value
+            .strip_prefix("vault:")
+            .and_then(|rest| rest.split_once('#'))
+            .is_some_and(|(path, field)| !path.is_empty() && !field.is_empty())
+}
+
+/// The secret a reference points to, read from its manager
+pub fn resolve(reference: &str) -> Result<String, String> {
+    if reference.starts_with("op://") {
+        let value = run(
+            Command::new("op").args(["read", "--no-newline", reference]),
+            "",
+        )?;
+        Ok(value)
+    } else if let Some(path) = reference.strip_prefix("pass:") {
+        let entry = run(Command::new("pass").args(["show", path]), "")?;
+        Ok(entry.lines().next().unwrap_or_default().to_string())
+    } else if let Some((path, field)) = reference
+        .strip_prefix("vault:")
+        .and_then(|rest| rest.split_once('#'))
+    {
+        let value = run(
+            Command::new("vault").args(["kv", "get", &format!("-field={}", field), path]),
+            "",
+        )?;
+        Ok(value.trim_end_matches('\n').to_string())
+    } else {
+        Err(format!("'{}' is not a secret reference", reference))
+    }
+}
+
+/// What to write for the value of the password parameter `id` (e.g.
+/// "servers/Connect/PASSWORD"), None when it must not be written at all
+pub fn seal(store: &SecretStore, id: &str, value: &str) -> Option<String> {
+    let sealed = match store {
+        SecretStore::None => return None,
+        SecretStore::Age { identity } => {
+            let identity = expand_home(identity);
+            let recipient = run_logged(Command::new("age-keygen").arg("-y").arg(&identity), "")?;
+            let encrypted = run_logged(
+                Command::new("age").args(["--encrypt", "--armor", "--recipient", recipient.trim()]),
+                value,
+            )?;
+            format!("age:{}", encrypted)
+        }
+        SecretStore::Keyring => {
+            run_logged(
+                Command::new("secret-tool")
+                    .args(["store", "--label", &format!("callbot {}", id)])
+                    .args([KEYRING_ATTRIBUTE, id]),
+                value,
+            )?;
+            "keyring:".to_string()
+        }
+    };
+    Some(sealed)
+}
+
+/// The value sealed by `seal`, None when it can't be opened (other store, lost key...)
+pub fn open(store: &SecretStore, id: &str, sealed: &str) -> Option<String> {
+    match store {
+        SecretStore::None => None,
+        SecretStore::Age { identity } => {
+            let encrypted = sealed.strip_prefix("age:")?;
+            run_logged(
+                Command::new("age")
+                    .args(["--decrypt", "--identity"])
+                    .arg(expand_home(identity)),
+                encrypted,
+            )
+        }
+        SecretStore::Keyring => {
+            sealed.strip_prefix("keyring:")?;
+            run_logged(
+                Command::new("secret-tool").args(["lookup", KEYRING_ATTRIBUTE, id]),
+                "",
+            )
+        }
+    }
+}
+
+// stdout of a command given `input`; the error is the last line it printed on stderr
+fn run(command: &mut Command, input: &str) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr
+            .lines()
+            .rfind(|l| !l.trim().is_empty())
+            .unwrap_or_default();
+        return Err(format!("{} failed: {}", program, last.trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+// `run` for the secret store, whose failures are only logged
+fn run_logged(command: &mut Command, input: &str) -> Option<String> {
+    run(command, input)
+        .inspect_err(|err| tracing::warn!("secret store: {}", err))
+        .ok()
+}
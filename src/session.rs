@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// A named workspace (`--session NAME`): parameter values and UI layout, restored
+/// on start and saved on quit. Everything is keyed by column id, action label and
+/// parameter name so edits to the config don't shift values onto other actions.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct Session {
+    #[serde(default)]
+    pub params: Vec<SavedParam>,
+    #[serde(default)]
+    pub focused_column: Option<String>,
+    #[serde(default)]
+    pub focused_action: Option<String>,
+    #[serde(default)]
+    pub details_open: bool,
+    #[serde(default)]
+    pub split_view: bool,
+    #[serde(default)]
+    pub columns: Vec<SavedColumn>,
+    // actions marked for a batch run
+    #[serde(default)]
+    pub marked: Vec<SavedAction>,
+}
+
+/// An action, by column id and label
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedAction {
+    pub column: String,
+    pub action: String,
+}
+
+/// Value of one parameter of an action
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedParam {
+    pub column: String,
+    pub action: String,
+    pub param: String,
+    pub value: String,
+}
+
+/// Layout of a column
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedColumn {
+    pub id: String,
+    #[serde(default)]
+    pub collapsed: bool,
+    #[serde(default)]
+    pub width: Option<u16>,
+    #[serde(default)]
+    pub weight: Option<u32>,
+}
+
+impl Session {
+    /// Location of a session file (e.g. ~/.config/callbot/sessions/incident-42.json)
+    pub fn path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("Invalid session name '{}'", name).into());
+        }
+        let dirs = directories::ProjectDirs::from("", "", "callbot")
+            .ok_or("Could not determine the user config directory")?;
+        Ok(dirs
+            .config_dir()
+            .join("sessions")
+            .join(format!("{}.json", name)))
+    }
+
+    /// Names of the saved sessions, sorted
+    pub fn names() -> Vec<String> {
+        let Some(dirs) = directories::ProjectDirs::from("", "", "callbot") else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(dirs.config_dir().join("sessions")) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                Some(name.strip_suffix(".json")?.to_string())
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Load a session; one that was never saved starts empty
+    pub fn load(name: &str) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path(name)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read session '{}': {}", path.display(), e))?;
+        let session = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse session '{}': {}", path.display(), e))?;
+        Ok(session)
+    }
+
+    pub fn save(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let path = Self::path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write session '{}': {}", path.display(), e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::expand_home;
+
+/// Shells whose history file format we know how to write
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Detect the user's shell from $SHELL (e.g. /usr/bin/zsh -> Zsh)
+    pub fn detect() -> Option<Shell> {
+        let shell = std::env::var("SHELL").ok()?;
+        Self::from_name(shell.rsplit('/').next().unwrap_or_default())
+    }
+
+    /// Shell by its program name ("bash", "zsh" or "fish")
+    pub fn from_name(name: &str) -> Option<Shell> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// Default history file location for this shell
+    fn default_history_file(&self) -> Option<PathBuf> {
+        let dirs = directories::BaseDirs::new()?;
+        match self {
+            // HISTFILE is usually a shell variable rather than an exported one, but honour
+            // it when it does reach us
+            Shell::Bash => Some(
+                std::env::var("HISTFILE")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| dirs.home_dir().join(".bash_history")),
+            ),
+            Shell::Zsh => Some(
+                std::env::var("HISTFILE")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| dirs.home_dir().join(".zsh_history")),
+            ),
+            Shell::Fish => Some(dirs.data_dir().join("fish").join("fish_history")),
+        }
+    }
+
+    /// Format a single history entry in the shell's native file format
+    fn format_entry(&self, command: &str, timestamp: u64) -> String {
+        match self {
+            // bash keeps the "#<epoch>" comment as the entry's time whether or not
+            // HISTTIMEFORMAT is set; every other line is an entry of its own
+            Shell::Bash => format!("#{}\n{}\n", timestamp, one_line(command)),
+            // zsh extended history: ": <start>:<elapsed>;<command>", continuation lines
+            // are escaped with a trailing backslash
+            Shell::Zsh => format!(": {}:0;{}\n", timestamp, command.replace('\n', "\\\n")),
+            Shell::Fish => {
+                let escaped = command.replace('\\', "\\\\").replace('\n', "\\n");
+                format!("- cmd: {}\n  when: {}\n", escaped, timestamp)
+            }
+        }
+    }
+}
+
+// A multi-line command joined into one, as bash's `cmdhist` does: lines are separated
+// with "; ", or a space after a continuation (`\`) or what can't be followed by
+// one (`then`, `do`, `|`, `&&`, ...). A newline inside quotes is part of an argument
+// and stays as written.
+fn one_line(command: &str) -> String {
+    let mut joined = String::new();
+    let mut open = false;
+    let mut quote = None;
+    for line in command.lines() {
+        let quoted = quote.is_some();
+        let line = if quoted { line } else { line.trim_start() };
+        quote = quote_after(line, quote);
+        let line = if quote.is_some() {
+            line
+        } else {
+            line.trim_end()
+        };
+        if quoted {
+            joined.push('\n');
+        } else if line.is_empty() {
+            continue;
+        } else if !joined.is_empty() {
+            joined.push_str(if open { " " } else { "; " });
+        }
+        if quote.is_some() {
+            joined.push_str(line);
+            continue;
+        }
+        let (line, continued) = match line.strip_suffix('\\') {
+            Some(line) => (line.trim_end(), true),
+            None => (line, false),
+        };
+        let last_word = line.rsplit(char::is_whitespace).next().unwrap_or_default();
+        open = continued
+            || line.ends_with(['|', '&', ';', '(', '{'])
+            || matches!(last_word, "then" | "do" | "else");
+        joined.push_str(line);
+    }
+    joined
+}
+
+// The quote still open at the end of `line`, given the one open at its start
+fn quote_after(line: &str, mut quote: Option<char>) -> Option<char> {
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some('"'), '"') => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            _ => {}
+        }
+    }
+    quote
+}
+
+/// Append `command` to the user's shell history so it can be recalled with Ctrl+R
+pub fn append(command: &str, file_override: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let shell = Shell::detect().ok_or("Unsupported or unknown $SHELL for history export")?;
+    let path = match file_override {
+        Some(p) => expand_home(p),
+        None => shell
+            .default_history_file()
+            .ok_or("Could not determine the shell history file")?,
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history file '{}': {}", path.display(), e))?;
+    file.write_all(shell.format_entry(command, timestamp).as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,59 @@
+//! History entries in each shell's file format
+
+use super::{one_line, Shell};
+
+#[test]
+fn bash_entries_are_one_line_after_their_time() {
+    assert_eq!(
+        Shell::Bash.format_entry("make \\\n  build\ncd /tmp", 42),
+        "#42\nmake build; cd /tmp\n"
+    );
+}
+
+#[test]
+fn zsh_entries_escape_their_newlines() {
+    assert_eq!(
+        Shell::Zsh.format_entry("echo a\nls", 42),
+        ": 42:0;echo a\\\nls\n"
+    );
+}
+
+#[test]
+fn fish_entries_escape_backslashes_and_newlines() {
+    assert_eq!(
+        Shell::Fish.format_entry("echo a\\b\nls", 42),
+        "- cmd: echo a\\\\b\\nls\n  when: 42\n"
+    );
+}
+
+#[test]
+fn continuation_lines_join_with_a_space() {
+    assert_eq!(
+        one_line("cargo build \\\n    --release \\\n    --locked"),
+        "cargo build --release --locked"
+    );
+    assert_eq!(
+        one_line("ls |\n  wc -l\n\necho done"),
+        "ls | wc -l; echo done"
+    );
+}
+
+#[test]
+fn compound_commands_join_after_then_and_do() {
+    assert_eq!(
+        one_line("if true; then\n  echo yes\nelse\n  echo no\nfi"),
+        "if true; then echo yes; else echo no; fi"
+    );
+    assert_eq!(
+        one_line("for f in *; do\n  echo $f\ndone"),
+        "for f in *; do echo $f; done"
+    );
+}
+
+#[test]
+fn quoted_newlines_are_kept() {
+    assert_eq!(one_line("echo \"a\n  b\"\nls"), "echo \"a\n  b\"; ls");
+    assert_eq!(one_line("echo 'it\"s\n  ' \\\n  x"), "echo 'it\"s\n  ' x");
+    // an escaped quote opens nothing
+    assert_eq!(one_line("echo \\\"a\nls"), "echo \\\"a; ls");
+}
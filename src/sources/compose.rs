@@ -0,0 +1,69 @@
+use serde_yaml::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::action;
+use crate::config::{Action, Parameter, ParameterType};
+
+// what is generated for each service: label suffix and `docker compose` arguments
+const COMMANDS: [(&str, &str); 4] = [
+    ("up", "up -d"),
+    // stops and removes the containers of the service only, unlike `down`
+    ("down", "rm --stop --force"),
+    ("logs", "logs --follow --tail 200"),
+    ("restart", "restart"),
+];
+
+/// Up, down, logs and restart actions for each service of a compose file, in file
+/// order. The compose file is a parameter, defaulting to `path`, so the same
+/// actions work on an override or another checkout.
+pub fn load(path: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read compose file '{}': {}", path.display(), e))?;
+    let compose: Value = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Failed to parse compose file '{}': {}", path.display(), e))?;
+    let services = compose
+        .get("services")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| format!("Compose file '{}' has no services", path.display()))?;
+
+    let mut actions = Vec::new();
+    for service in services.keys().filter_map(Value::as_str) {
+        for (name, args) in COMMANDS {
+            actions.push(Action {
+                parameters: vec![file_parameter(path)],
+                ..action(
+                    format!("{} {}", service, name),
+                    format!("docker compose -f {{COMPOSE_FILE}} {} {}", args, service),
+                    Some(format!(
+                        "docker compose {} of the {} service",
+                        name, service
+                    )),
+                )
+            });
+        }
+    }
+    Ok(actions)
+}
+
+fn file_parameter(path: &Path) -> Parameter {
+    Parameter {
+        name: "COMPOSE_FILE".to_string(),
+        placeholder: "{COMPOSE_FILE}".to_string(),
+        param_type: ParameterType::Text,
+        required: true,
+        description: Some("Compose file".to_string()),
+        options: Vec::new(),
+        default: Some(path.to_string_lossy().into_owned()),
+        pattern: None,
+        min: None,
+        max: None,
+        repeat_format: None,
+        source: None,
+        repo: None,
+        kube_context: None,
+        namespace: None,
+        secret: None,
+    }
+}
@@ -0,0 +1,51 @@
+use std::error::Error;
+use std::path::Path;
+
+use crate::config::{expand_home, Action, ColumnSource, Confirm, TemplateEngine};
+
+mod compose;
+mod npm;
+mod openapi;
+pub mod options;
+pub mod provider;
+mod scripts;
+mod systemd;
+mod tasks;
+
+/// Generate the actions described by a column source. Relative paths are resolved
+/// against `base_dir`, the directory of the config file.
+pub fn load(source: &ColumnSource, base_dir: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
+    match source {
+        ColumnSource::OpenApi { path, base_url } => {
+            openapi::load(&base_dir.join(path), base_url.as_deref())
+        }
+        ColumnSource::Dir { path } => scripts::load(&base_dir.join(expand_home(path))),
+        ColumnSource::Make { path } => tasks::make(&base_dir.join(expand_home(path))),
+        ColumnSource::Just { path } => tasks::just(&base_dir.join(expand_home(path))),
+        ColumnSource::Systemd { units, user } => Ok(systemd::load(units, *user)),
+        ColumnSource::Compose { path } => compose::load(&base_dir.join(expand_home(path))),
+        ColumnSource::Npm { path, manager } => {
+            npm::load(&base_dir.join(expand_home(path)), *manager)
+        }
+    }
+}
+
+// Action without parameters or any of the optional settings
+fn action(label: String, template: String, description: Option<String>) -> Action {
+    Action {
+        label,
+        template,
+        dry_template: None,
+        description,
+        parameters: Vec::new(),
+        simulate: None,
+        hotkey: None,
+        engine: TemplateEngine::Plain,
+        presets: Vec::new(),
+        delay_secs: 0,
+        background: false,
+        watch_secs: 0,
+        confirm: Confirm::None,
+        confirm_phrase: None,
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::action;
+use crate::config::{Action, PackageManager};
+use crate::template::shell_quote;
+
+#[derive(Deserialize)]
+struct Package {
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+}
+
+/// One action per script of the package.json in `dir`, by name, run from `dir`
+/// with the package manager and described by the script's command. The `pre` and
+/// `post` hooks of other scripts are left out, the manager runs them anyway.
+pub fn load(dir: &Path, manager: PackageManager) -> Result<Vec<Action>, Box<dyn Error>> {
+    let path = dir.join("package.json");
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let package: Package = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse '{}': {}", path.display(), e))?;
+    let is_hook = |name: &str| {
+        ["pre", "post"].iter().any(|prefix| {
+            name.strip_prefix(prefix)
+                .is_some_and(|script| package.scripts.contains_key(script))
+        })
+    };
+    Ok(package
+        .scripts
+        .iter()
+        .filter(|(name, _)| !is_hook(name))
+        .map(|(name, command)| {
+            action(
+                name.clone(),
+                format!(
+                    "cd {} && {} run {}",
+                    shell_quote(&dir.to_string_lossy()),
+                    manager.command(),
+                    shell_quote(name)
+                ),
+                Some(command.clone()),
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,239 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::action;
+use crate::config::{Action, Parameter, ParameterOption, ParameterType};
+
+// operations looked up under each path, in the order actions are generated
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// One curl action per operation of an OpenAPI 3 / Swagger 2 spec. Path and query
+/// parameters become parameters (selects when they declare an enum), a request body
+/// becomes a JSON text parameter, and the server URL an editable BASE_URL.
+pub fn load(path: &Path, base_url: Option<&str>) -> Result<Vec<Action>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read OpenAPI spec '{}': {}", path.display(), e))?;
+    let is_yaml = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml") | Some("yml")
+    );
+    let spec: Value = if is_yaml {
+        serde_yaml::from_str(&content)
+            .map_err(|e| format!("Failed to parse OpenAPI spec '{}': {}", path.display(), e))?
+    } else {
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse OpenAPI spec '{}': {}", path.display(), e))?
+    };
+
+    let paths = spec
+        .get("paths")
+        .and_then(Value::as_object)
+        .ok_or_else(|| format!("OpenAPI spec '{}' has no paths", path.display()))?;
+    let base_url = base_url
+        .map(str::to_string)
+        .or_else(|| server_url(&spec))
+        .unwrap_or_default();
+
+    let mut actions = Vec::new();
+    for (route, item) in paths {
+        // parameters declared on the path apply to all of its operations
+        let shared = item.get("parameters").and_then(Value::as_array);
+        for method in METHODS {
+            let Some(op) = item.get(method) else {
+                continue;
+            };
+            let declared = shared
+                .into_iter()
+                .flatten()
+                .chain(
+                    op.get("parameters")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten(),
+                )
+                .map(|p| resolve(&spec, p));
+            actions.push(operation_action(method, route, op, declared, &base_url));
+        }
+    }
+    Ok(actions)
+}
+
+fn operation_action<'a>(
+    method: &str,
+    route: &str,
+    op: &Value,
+    declared: impl Iterator<Item = &'a Value>,
+    base_url: &str,
+) -> Action {
+    let mut parameters = Vec::new();
+    let mut query = Vec::new();
+    let mut has_body = op.get("requestBody").is_some();
+    for param in declared {
+        let Some(name) = param.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        match param.get("in").and_then(Value::as_str) {
+            Some("path") => parameters.push(parameter(name, param, true)),
+            Some("query") => {
+                query.push(format!("{}={{{}}}", name, name));
+                parameters.push(parameter(name, param, false));
+            }
+            // Swagger 2 request bodies are declared as a parameter
+            Some("body") => has_body = true,
+            _ => {}
+        }
+    }
+
+    let mut template = format!(
+        "curl -sS -X {} '{{BASE_URL}}{}",
+        method.to_uppercase(),
+        route
+    );
+    if !query.is_empty() {
+        template.push('?');
+        template.push_str(&query.join("&"));
+    }
+    template.push('\'');
+    if has_body {
+        template.push_str(" -H 'Content-Type: application/json' -d '{BODY}'");
+        parameters.push(Parameter {
+            name: "body".to_string(),
+            placeholder: "{BODY}".to_string(),
+            param_type: ParameterType::Text,
+            required: true,
+            description: Some("JSON request body".to_string()),
+            options: Vec::new(),
+            default: None,
+            pattern: None,
+            min: None,
+            max: None,
+            repeat_format: None,
+            source: None,
+            repo: None,
+            kube_context: None,
+            namespace: None,
+            secret: None,
+        });
+    }
+    parameters.push(Parameter {
+        name: "BASE_URL".to_string(),
+        placeholder: "{BASE_URL}".to_string(),
+        param_type: ParameterType::Text,
+        required: true,
+        description: Some("Server URL".to_string()),
+        options: Vec::new(),
+        default: Some(base_url.to_string()),
+        pattern: None,
+        min: None,
+        max: None,
+        repeat_format: None,
+        source: None,
+        repo: None,
+        kube_context: None,
+        namespace: None,
+        secret: None,
+    });
+
+    let summary = op
+        .get("summary")
+        .or_else(|| op.get("operationId"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Action {
+        parameters,
+        ..action(
+            format!("{} {}", method.to_uppercase(), route),
+            template,
+            summary,
+        )
+    }
+}
+
+// A path or query parameter; an enum in its schema makes it a select
+fn parameter(name: &str, param: &Value, path: bool) -> Parameter {
+    // OpenAPI 3 nests the type under `schema`, Swagger 2 declares it inline
+    let schema = param.get("schema").unwrap_or(param);
+    let options: Vec<ParameterOption> = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(scalar)
+        .map(|value| ParameterOption {
+            label: value.clone(),
+            value,
+            hidden_when: None,
+            color: None,
+        })
+        .collect();
+    Parameter {
+        name: name.to_string(),
+        placeholder: format!("{{{}}}", name),
+        param_type: if options.is_empty() {
+            ParameterType::Text
+        } else {
+            ParameterType::Select
+        },
+        required: path
+            || param
+                .get("required")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        description: param
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        options,
+        default: schema.get("default").map(scalar),
+        // spec patterns are ECMAScript regexes, only the numeric bounds carry over
+        pattern: None,
+        min: schema.get("minimum").and_then(Value::as_f64),
+        max: schema.get("maximum").and_then(Value::as_f64),
+        repeat_format: None,
+        source: None,
+        repo: None,
+        kube_context: None,
+        namespace: None,
+        secret: None,
+    }
+}
+
+// Server URL: the first of `servers` (OpenAPI 3) or scheme, host and basePath (Swagger 2)
+fn server_url(spec: &Value) -> Option<String> {
+    if let Some(url) = spec.pointer("/servers/0/url").and_then(Value::as_str) {
+        return Some(url.trim_end_matches('/').to_string());
+    }
+    let host = spec.get("host").and_then(Value::as_str)?;
+    let scheme = spec
+        .pointer("/schemes/0")
+        .and_then(Value::as_str)
+        .unwrap_or("https");
+    let base_path = spec.get("basePath").and_then(Value::as_str).unwrap_or("");
+    Some(format!(
+        "{}://{}{}",
+        scheme,
+        host,
+        base_path.trim_end_matches('/')
+    ))
+}
+
+// Follow a local `$ref` such as "#/components/parameters/limit"
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    match value.get("$ref").and_then(Value::as_str) {
+        Some(reference) => reference
+            .strip_prefix('#')
+            .and_then(|pointer| spec.pointer(pointer))
+            .unwrap_or(value),
+        None => value,
+    }
+}
+
+// Render an enum value or default as the text substituted into the command
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
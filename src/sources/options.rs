@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::config::{expand_home, OptionSource, Parameter, ParameterOption};
+
+// how long kubectl answers are reused: a query takes up to a few seconds
+const KUBE_CACHE_TTL: Duration = Duration::from_secs(30);
+// give up on an unreachable cluster rather than freeze the UI
+const KUBE_TIMEOUT: &str = "--request-timeout=5s";
+
+/// Options already queried from the sources that are slow to answer, by command line
+#[derive(Debug, Default)]
+pub struct OptionCache {
+    entries: HashMap<Vec<String>, (Instant, Vec<ParameterOption>)>,
+}
+
+/// Query the options of a select from its built-in source. `kube_context` and
+/// `namespace` are taken as they are, their placeholders already replaced.
+pub fn load(
+    source: OptionSource,
+    param: &Parameter,
+    cache: &mut OptionCache,
+) -> Result<Vec<ParameterOption>, Box<dyn Error>> {
+    match source {
+        OptionSource::GitBranches => git_branches(param.repo.as_deref()),
+        OptionSource::KubeContexts => {
+            kubectl(&["config", "get-contexts", "-o", "name"], None, None, cache)
+        }
+        OptionSource::KubeNamespaces => kubectl(
+            &["get", "namespaces", "-o", "name"],
+            param.kube_context.as_deref(),
+            None,
+            cache,
+        ),
+        OptionSource::KubePods => kubectl(
+            &["get", "pods", "-o", "name"],
+            param.kube_context.as_deref(),
+            param.namespace.as_deref(),
+            cache,
+        ),
+    }
+}
+
+// Local branches, then remote ones, each most recently committed first
+fn git_branches(repo: Option<&str>) -> Result<Vec<ParameterOption>, Box<dyn Error>> {
+    let mut command = Command::new("git");
+    if let Some(repo) = repo {
+        command.arg("-C").arg(expand_home(repo));
+    }
+    command.args([
+        "for-each-ref",
+        "--sort=-committerdate",
+        "--format=%(refname)",
+        "refs/heads",
+        "refs/remotes",
+    ]);
+    let refs = run(command, "git")?;
+    let local = refs.lines().filter_map(|r| r.strip_prefix("refs/heads/"));
+    let remote = refs
+        .lines()
+        .filter_map(|r| r.strip_prefix("refs/remotes/"))
+        // the symbolic ref to the default branch of a remote
+        .filter(|r| !r.ends_with("/HEAD"));
+    Ok(local.chain(remote).map(option).collect())
+}
+
+// Names printed by a kubectl command, without the `kind/` prefix of `get -o name`.
+// Empty context and namespace are left to the kubeconfig.
+fn kubectl(
+    args: &[&str],
+    context: Option<&str>,
+    namespace: Option<&str>,
+    cache: &mut OptionCache,
+) -> Result<Vec<ParameterOption>, Box<dyn Error>> {
+    let mut line: Vec<String> = Vec::new();
+    if let Some(context) = context.filter(|c| !c.is_empty()) {
+        line.extend(["--context".to_string(), context.to_string()]);
+    }
+    if let Some(namespace) = namespace.filter(|n| !n.is_empty()) {
+        line.extend(["--namespace".to_string(), namespace.to_string()]);
+    }
+    line.extend(args.iter().map(|a| a.to_string()));
+    if let Some((at, options)) = cache.entries.get(&line) {
+        if at.elapsed() < KUBE_CACHE_TTL {
+            return Ok(options.clone());
+        }
+    }
+
+    let mut command = Command::new("kubectl");
+    command.arg(KUBE_TIMEOUT).args(&line);
+    let names = run(command, "kubectl")?;
+    // context names may contain slashes, only resources are prefixed
+    let prefixed = args.first() == Some(&"get");
+    let options: Vec<ParameterOption> = names
+        .lines()
+        .map(|name| match name.split_once('/') {
+            Some((_, name)) if prefixed => name,
+            _ => name,
+        })
+        .filter(|name| !name.is_empty())
+        .map(option)
+        .collect();
+    cache
+        .entries
+        .insert(line, (Instant::now(), options.clone()));
+    Ok(options)
+}
+
+// Standard output of a command; a failure is an error carrying the last line of
+// its stderr, which says what went wrong after any logged retries
+fn run(mut command: Command, program: &str) -> Result<String, Box<dyn Error>> {
+    let output = command
+        .output()
+        .map_err(|e| format!("could not start {}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rfind(|l| !l.trim().is_empty()).unwrap_or("");
+        return Err(format!("{}: {}", output.status, reason.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn option(name: &str) -> ParameterOption {
+    ParameterOption {
+        value: name.to_string(),
+        label: name.to_string(),
+        hidden_when: None,
+        color: None,
+    }
+}
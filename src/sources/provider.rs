@@ -0,0 +1,31 @@
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{Column, Provider};
+
+// What a provider prints: columns in the same shape as `[[columns]]` in config.toml
+#[derive(Deserialize)]
+struct ProviderOutput {
+    #[serde(default)]
+    columns: Vec<Column>,
+}
+
+/// Run a provider with `sh -c` from `base_dir` and parse the columns it prints as
+/// JSON on stdout. A non-zero exit is an error carrying its stderr.
+pub fn load(provider: &Provider, base_dir: &Path) -> Result<Vec<Column>, Box<dyn Error>> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(&provider.command)
+        .current_dir(base_dir)
+        .output()
+        .map_err(|e| format!("could not start: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", output.status, stderr.trim()).into());
+    }
+    let parsed: ProviderOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("invalid JSON output: {}", e))?;
+    Ok(parsed.columns)
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use super::action;
+use crate::config::Action;
+use crate::template::shell_quote;
+
+/// One action per executable file of a directory, sorted by name: labelled after
+/// the file name without its extension, described by the first comment after the
+/// shebang, running the file by its full path. Hidden files are skipped.
+pub fn load(dir: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
+    let entries = fs::read_dir(dir).map_err(|e| {
+        format!(
+            "Failed to read scripts directory '{}': {}",
+            dir.display(),
+            e
+        )
+    })?;
+    let mut scripts: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            // follows symlinks, so linked scripts are listed too
+            let executable = fs::metadata(path)
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+            !hidden && executable
+        })
+        .collect();
+    scripts.sort();
+    Ok(scripts
+        .iter()
+        .map(|path| {
+            let label = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            action(
+                label,
+                shell_quote(&path.to_string_lossy()),
+                description(path),
+            )
+        })
+        .collect())
+}
+
+// First comment of a script, skipping the shebang and blank lines
+fn description(path: &Path) -> Option<String> {
+    // binaries have no comment to read
+    let content = fs::read_to_string(path).ok()?;
+    content
+        .lines()
+        .skip_while(|line| line.starts_with("#!"))
+        .find(|line| !line.trim().is_empty())?
+        .strip_prefix('#')
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty())
+}
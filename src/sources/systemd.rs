@@ -0,0 +1,32 @@
+use super::action;
+use crate::config::Action;
+
+/// Start, stop, restart and status actions for each unit, through `systemctl`
+/// (`--user` for user units). Status runs in the background so it shows in the
+/// output pane.
+pub fn load(units: &[String], user: bool) -> Vec<Action> {
+    let systemctl = if user {
+        "systemctl --user"
+    } else {
+        "systemctl"
+    };
+    let mut actions = Vec::new();
+    for unit in units {
+        for verb in ["start", "stop", "restart"] {
+            actions.push(action(
+                format!("{} {}", unit, verb),
+                format!("{} {} {}", systemctl, verb, unit),
+                None,
+            ));
+        }
+        actions.push(Action {
+            background: true,
+            ..action(
+                format!("{} status", unit),
+                format!("{} status --no-pager --lines 50 {}", systemctl, unit),
+                None,
+            )
+        });
+    }
+    actions
+}
@@ -0,0 +1,155 @@
+use regex::Regex;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use super::action;
+use crate::config::{Action, Parameter, ParameterType};
+use crate::template::shell_quote;
+
+/// One action per target of a Makefile, in file order, described by the comment
+/// right above it or a `## ...` comment after it. Special targets (`.PHONY`) and
+/// pattern rules are skipped.
+pub fn make(path: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
+    let content = read(path, "Makefile")?;
+    let (dir, file) = split(path);
+    // `name other: deps ## help`, but not `name := value`
+    let rule = Regex::new(r"^([^\s:=#][^:=#]*?)\s*::?(?:[^=:]|$)").unwrap();
+    let mut actions: Vec<Action> = Vec::new();
+    let mut comment = None;
+    for line in content.lines() {
+        if let Some(text) = line.strip_prefix('#') {
+            comment = Some(text.trim().to_string());
+            continue;
+        }
+        let described = comment.take();
+        // recipe lines start with a tab, and the rest are not rules
+        let Some(targets) = rule.captures(line).map(|c| c[1].to_string()) else {
+            continue;
+        };
+        let help = line
+            .split_once("##")
+            .map(|(_, help)| help.trim().to_string())
+            .or(described)
+            .filter(|help| !help.is_empty());
+        for target in targets.split_whitespace() {
+            if target.starts_with('.')
+                || target.contains('%')
+                || target.contains('$')
+                || actions.iter().any(|a| a.label == target)
+            {
+                continue;
+            }
+            actions.push(action(
+                target.to_string(),
+                format!("make -C {} -f {} {}", dir, file, target),
+                help.clone(),
+            ));
+        }
+    }
+    Ok(actions)
+}
+
+/// One action per public recipe of a justfile, in file order, described by the
+/// comment right above it. Recipe parameters become text parameters, required
+/// unless they have a default.
+pub fn just(path: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
+    let content = read(path, "justfile")?;
+    // `name param='x' +rest: deps`, but not `name := value`
+    let recipe = Regex::new(r"^@?([A-Za-z_][A-Za-z0-9_-]*)((?:\s+[^:]*?)?)\s*:(?:[^=]|$)").unwrap();
+    let mut actions = Vec::new();
+    let mut comment = None;
+    let mut private = false;
+    for line in content.lines() {
+        if let Some(text) = line.strip_prefix('#') {
+            // `#!` lines are the shebangs of recipes
+            if !text.starts_with('!') {
+                comment = Some(text.trim().to_string());
+            }
+            continue;
+        }
+        // attributes of the next recipe, e.g. `[private]` or `[group('ci')]`
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let described = comment.take();
+        let hidden = std::mem::take(&mut private);
+        let Some(captures) = recipe.captures(line) else {
+            continue;
+        };
+        let name = &captures[1];
+        if hidden
+            || name.starts_with('_')
+            || ["alias", "set", "export", "import", "mod"].contains(&name)
+        {
+            continue;
+        }
+        let parameters: Vec<Parameter> = captures[2]
+            .split_whitespace()
+            .map(recipe_parameter)
+            .collect();
+        let mut template = format!(
+            "just --justfile {} {}",
+            shell_quote(&path.to_string_lossy()),
+            name
+        );
+        for param in &parameters {
+            template.push(' ');
+            template.push_str(&param.placeholder);
+        }
+        actions.push(Action {
+            parameters,
+            ..action(
+                name.to_string(),
+                template,
+                described.filter(|d| !d.is_empty()),
+            )
+        });
+    }
+    Ok(actions)
+}
+
+// `name`, `name="default"` or a variadic `+name` / `*name`
+fn recipe_parameter(declared: &str) -> Parameter {
+    let (name, default) = match declared.split_once('=') {
+        Some((name, default)) => (name, Some(default.trim_matches(['"', '\'']).to_string())),
+        None => (declared, None),
+    };
+    let variadic = name.starts_with(['+', '*']);
+    let name = name.trim_start_matches(['+', '*', '$']);
+    Parameter {
+        name: name.to_string(),
+        placeholder: format!("{{{}}}", name),
+        param_type: ParameterType::Text,
+        // `*name` takes zero or more values
+        required: default.is_none() && !declared.starts_with('*'),
+        description: variadic.then(|| "One or more values, separated by spaces".to_string()),
+        options: Vec::new(),
+        default,
+        pattern: None,
+        min: None,
+        max: None,
+        repeat_format: None,
+        source: None,
+        repo: None,
+        kube_context: None,
+        namespace: None,
+        secret: None,
+    }
+}
+
+fn read(path: &Path, kind: &str) -> Result<String, Box<dyn Error>> {
+    Ok(fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {} '{}': {}", kind, path.display(), e))?)
+}
+
+// Directory and file name of a path, each quoted for the shell
+fn split(path: &Path) -> (String, String) {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty());
+    let file = path.file_name().unwrap_or_default();
+    (
+        shell_quote(&dir.unwrap_or(Path::new(".")).to_string_lossy()),
+        shell_quote(&file.to_string_lossy()),
+    )
+}
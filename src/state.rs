@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Preset;
+use crate::session::SavedAction;
+
+/// State persisted across sessions in a small JSON file in the user's config dir
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct State {
+    // most recently run actions, newest first
+    #[serde(default)]
+    pub recent: Vec<RecentRun>,
+    // the last action run, with its exit code once it has finished
+    #[serde(default)]
+    pub last_run: Option<RecentRun>,
+    // latest run of every action ever run, for the status shown next to it
+    #[serde(default)]
+    pub outcomes: Vec<RecentRun>,
+    // where the UI was left, see `restore_ui` in the config
+    #[serde(default)]
+    pub ui: UiState,
+    // values run with, newest first, by text parameter name
+    #[serde(default)]
+    pub values: HashMap<String, Vec<String>>,
+    // presets saved from the details view, besides those of the config
+    #[serde(default)]
+    pub presets: Vec<SavedPreset>,
+    // how often and how well every action ran
+    #[serde(default)]
+    pub stats: Vec<ActionStats>,
+    // commands run, newest first, searched with Ctrl+R
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    // action orders set with Alt+Up/Down, over the config order
+    #[serde(default)]
+    pub orders: Vec<ColumnOrder>,
+}
+
+/// Labels of the actions of a column in the order the user put them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColumnOrder {
+    pub column: String,
+    pub labels: Vec<String>,
+}
+
+// commands kept in `history`
+const HISTORY_LIMIT: usize = 500;
+
+/// A command that was run, with the parameter values it was run with
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub column: String,
+    pub label: String,
+    pub command: String,
+    pub values: BTreeMap<String, String>,
+    pub timestamp: u64,
+    // the command edited by hand that ran instead of the template, with the
+    // placeholders of its secrets rather than their values
+    #[serde(default)]
+    pub edited: Option<String>,
+}
+
+/// Finished runs of an action, for the details view and `sort = "most-used"`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ActionStats {
+    pub column: String,
+    pub label: String,
+    pub runs: u32,
+    // runs that exited with another code than 0
+    pub failures: u32,
+    // run time of all the runs together
+    pub total_secs: f64,
+}
+
+impl ActionStats {
+    pub fn average(&self) -> Duration {
+        Duration::from_secs_f64(self.total_secs / self.runs.max(1) as f64)
+    }
+}
+
+/// A preset saved for the action `label` of column `column`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedPreset {
+    pub column: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub preset: Preset,
+}
+
+// values remembered per parameter name
+const VALUE_HISTORY: usize = 10;
+
+/// Focus, selections, filter, collapsed columns and theme at exit, restored on the
+/// next launch. Columns and actions are identified by id and label.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct UiState {
+    #[serde(default)]
+    pub focused_column: Option<String>,
+    // selected action of every column
+    #[serde(default)]
+    pub selected: Vec<SavedAction>,
+    // ids of the collapsed columns
+    #[serde(default)]
+    pub collapsed: Vec<String>,
+    // details view of the focused action, with its parameter filter
+    #[serde(default)]
+    pub details_open: bool,
+    #[serde(default)]
+    pub filter: String,
+    // theme cycled to with `t`, when not the configured one
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+/// An action that was run, identified by its column id and label
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentRun {
+    pub column: String,
+    pub label: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+/// Seconds since the unix epoch
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl State {
+    /// Location of the state file (e.g. ~/.config/callbot/state.json)
+    pub fn path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "callbot")
+            .map(|dirs| dirs.config_dir().join("state.json"))
+    }
+
+    /// Load the state file; a missing or unreadable file yields an empty state
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state file, creating the config dir when needed
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path().ok_or("Could not determine the user config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write state file '{}': {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Move (or insert) an action to the front of the recent list, keeping at most `limit`
+    pub fn record_run(&mut self, column: &str, label: &str, limit: usize) {
+        let run = RecentRun {
+            column: column.to_string(),
+            label: label.to_string(),
+            timestamp: now_secs(),
+            exit_code: None,
+        };
+        self.recent
+            .retain(|r| !(r.column == column && r.label == label));
+        self.recent.insert(0, run.clone());
+        self.recent.truncate(limit);
+        self.outcomes
+            .retain(|r| !(r.column == column && r.label == label));
+        self.outcomes.push(run.clone());
+        self.last_run = Some(run);
+    }
+
+    /// Store the exit code of the last run, also on its entry of the recent list
+    pub fn record_exit(&mut self, code: i32) {
+        if let Some(run) = self.last_run.clone() {
+            self.record_exit_of(&run.column, &run.label, code);
+        }
+    }
+
+    /// Store the exit code of an action's latest run, wherever that run is listed
+    pub fn record_exit_of(&mut self, column: &str, label: &str, code: i32) {
+        for run in self
+            .last_run
+            .iter_mut()
+            .chain(self.recent.iter_mut())
+            .chain(self.outcomes.iter_mut())
+            .filter(|r| r.column == column && r.label == label)
+        {
+            run.exit_code = Some(code);
+        }
+    }
+
+    /// Most recent run of an action, if it was ever run
+    pub fn last_run_of(&self, column: &str, label: &str) -> Option<&RecentRun> {
+        // state files written before `outcomes` existed only have the recent list
+        self.outcomes
+            .iter()
+            .chain(self.recent.iter())
+            .find(|r| r.column == column && r.label == label)
+    }
+
+    /// Remember a value a parameter was run with, as its most recent one
+    pub fn record_value(&mut self, param: &str, value: &str) {
+        let values = self.values.entry(param.to_string()).or_default();
+        values.retain(|v| v != value);
+        values.insert(0, value.to_string());
+        values.truncate(VALUE_HISTORY);
+    }
+
+    /// Count a finished run of an action in its statistics
+    pub fn record_stats(&mut self, column: &str, label: &str, code: i32, duration: Duration) {
+        let index = match self
+            .stats
+            .iter()
+            .position(|s| s.column == column && s.label == label)
+        {
+            Some(index) => index,
+            None => {
+                self.stats.push(ActionStats {
+                    column: column.to_string(),
+                    label: label.to_string(),
+                    ..ActionStats::default()
+                });
+                self.stats.len() - 1
+            }
+        };
+        let stats = &mut self.stats[index];
+        stats.runs += 1;
+        if code != 0 {
+            stats.failures += 1;
+        }
+        stats.total_secs += duration.as_secs_f64();
+    }
+
+    pub fn stats_of(&self, column: &str, label: &str) -> Option<&ActionStats> {
+        self.stats
+            .iter()
+            .find(|s| s.column == column && s.label == label)
+    }
+
+    /// Save a preset of an action, replacing the one of the same name
+    pub fn save_preset(&mut self, column: &str, label: &str, preset: Preset) {
+        self.delete_preset(column, label, &preset.name);
+        self.presets.push(SavedPreset {
+            column: column.to_string(),
+            label: label.to_string(),
+            preset,
+        });
+    }
+
+    pub fn delete_preset(&mut self, column: &str, label: &str, name: &str) {
+        self.presets
+            .retain(|p| !(p.column == column && p.label == label && p.preset.name == name));
+    }
+
+    /// Presets saved for an action, oldest first
+    pub fn presets_of<'a>(
+        &'a self,
+        column: &'a str,
+        label: &'a str,
+    ) -> impl Iterator<Item = &'a Preset> + 'a {
+        self.presets
+            .iter()
+            .filter(move |p| p.column == column && p.label == label)
+            .map(|p| &p.preset)
+    }
+
+    /// Put a command at the front of the history, dropping an older run of the same
+    pub fn record_command(&mut self, entry: HistoryEntry) {
+        self.history.retain(|e| e.command != entry.command);
+        self.history.insert(0, entry);
+        self.history.truncate(HISTORY_LIMIT);
+    }
+
+    /// Replace the order of the actions of a column
+    pub fn set_order(&mut self, column: &str, labels: Vec<String>) {
+        self.orders.retain(|o| o.column != column);
+        self.orders.push(ColumnOrder {
+            column: column.to_string(),
+            labels,
+        });
+    }
+
+    /// Labels of a column in the order set by the user, if any
+    pub fn order_of(&self, column: &str) -> Option<&[String]> {
+        self.orders
+            .iter()
+            .find(|o| o.column == column)
+            .map(|o| o.labels.as_slice())
+    }
+
+    /// Values a parameter of that name was run with, newest first
+    pub fn values_of(&self, param: &str) -> &[String] {
+        self.values.get(param).map_or(&[], Vec::as_slice)
+    }
+}
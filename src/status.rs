@@ -0,0 +1,49 @@
+use std::fs;
+use std::process::{Command, Stdio};
+
+use crate::config::{expand_home, AppConfig};
+
+/// Runner state published for tmux/starship status lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunnerStatus {
+    Idle,
+    Running,
+    Failed,
+}
+
+impl RunnerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunnerStatus::Idle => "idle",
+            RunnerStatus::Running => "running",
+            RunnerStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Publish the runner state to the configured status file and/or tmux option.
+/// The first word is always the state; the action label follows when known.
+/// Failures are ignored: a status line must never break the launcher.
+pub fn publish(app: &AppConfig, status: RunnerStatus, label: Option<&str>) {
+    let text = match label {
+        Some(l) => format!("{} {}", status.as_str(), l),
+        None => status.as_str().to_string(),
+    };
+
+    if let Some(ref file) = app.status_file {
+        let path = expand_home(file);
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(&path, format!("{}\n", text));
+    }
+
+    // only talk to tmux when we're actually running inside it
+    if app.tmux_status && std::env::var_os("TMUX").is_some() {
+        let _ = Command::new("tmux")
+            .args(["set-option", "-gq", "@callbot_status", &text])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
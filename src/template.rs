@@ -0,0 +1,194 @@
+//! Placeholders of command templates. Besides its plain form, a `{NAME}`
+//! placeholder can be written `{NAME|filter|...}` to transform the value, e.g.
+//! `{BRANCH|slug}`; the filters apply from left to right.
+//!
+//! Actions with `engine = "handlebars"` are rendered by Handlebars before that,
+//! with each parameter as a variable named like it, plus the `split` (value,
+//! separator) and `default` (value, fallback) helpers.
+//!
+//! `{snippet:NAME}` and `{var:NAME}` references to the `[snippets]` and `[vars]`
+//! of the config are expanded at load.
+
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde_json::{Map, Value};
+
+handlebars_helper!(split: |value: str, separator: str| value
+    .split(separator)
+    .map(str::trim)
+    .filter(|entry| !entry.is_empty())
+    .collect::<Vec<_>>());
+handlebars_helper!(default: |value: str, fallback: str| if value.is_empty() { fallback } else { value });
+
+/// Names of the filters, as written after a `|`
+pub const FILTERS: &[&str] = &[
+    "upper",
+    "lower",
+    "trim",
+    "slug",
+    "urlencode",
+    "shell-quote",
+    "basename",
+];
+
+/// A placeholder found in a template
+#[derive(Debug, PartialEq)]
+pub struct Occurrence<'a> {
+    // byte offset and length in the template
+    pub at: usize,
+    pub len: usize,
+    pub filters: Vec<&'a str>,
+}
+
+/// First occurrence of `placeholder` in `text`, plain or with filters
+pub fn find<'a>(text: &'a str, placeholder: &str) -> Option<Occurrence<'a>> {
+    let plain = text.find(placeholder).map(|at| Occurrence {
+        at,
+        len: placeholder.len(),
+        filters: Vec::new(),
+    });
+    // only `{NAME}` placeholders take filters: look for `{NAME|`
+    let filtered = placeholder
+        .strip_suffix('}')
+        .filter(|_| placeholder.starts_with('{'))
+        .and_then(|open| {
+            let open = format!("{}|", open);
+            let at = text.find(&open)?;
+            let start = at + open.len();
+            let end = start + text[start..].find('}')?;
+            Some(Occurrence {
+                at,
+                len: end + 1 - at,
+                filters: text[start..end].split('|').map(str::trim).collect(),
+            })
+        });
+    match (plain, filtered) {
+        (Some(p), Some(f)) => Some(if f.at < p.at { f } else { p }),
+        (p, f) => p.or(f),
+    }
+}
+
+/// Whether `template` is valid Handlebars
+pub fn check_handlebars(template: &str) -> Result<(), String> {
+    handlebars::Template::compile(template)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// `template` rendered by Handlebars with `values` by parameter name
+pub fn render_handlebars<'a>(
+    template: &str,
+    values: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String, String> {
+    let mut engine = Handlebars::new();
+    // commands are not HTML
+    engine.register_escape_fn(no_escape);
+    engine.register_helper("split", Box::new(split));
+    engine.register_helper("default", Box::new(default));
+    let data: Map<String, Value> = values
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), Value::from(value)))
+        .collect();
+    engine
+        .render_template(template, &data)
+        .map_err(|e| e.to_string())
+}
+
+/// `text` with every `{KIND:NAME}` reference replaced by `lookup(NAME)`, e.g.
+/// `{var:registry}`; the error is the first name `lookup` does not know
+pub fn expand(
+    text: &str,
+    kind: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let open = format!("{{{}:", kind);
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(at) = rest.find(&open) {
+        let start = at + open.len();
+        let Some(end) = rest[start..].find('}').map(|i| start + i) else {
+            break;
+        };
+        let name = rest[start..end].trim();
+        let value = lookup(name).ok_or_else(|| name.to_string())?;
+        expanded.push_str(&rest[..at]);
+        expanded.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Filters used with `placeholder` in `template` that do not exist
+pub fn unknown_filters<'a>(template: &'a str, placeholder: &str) -> Vec<&'a str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(found) = find(rest, placeholder) {
+        unknown.extend(found.filters.iter().filter(|f| !FILTERS.contains(f)));
+        rest = &rest[found.at + found.len..];
+    }
+    unknown
+}
+
+/// `value` transformed by `filters` in turn; unknown filters leave it unchanged
+pub fn apply(value: &str, filters: &[&str]) -> String {
+    filters
+        .iter()
+        .fold(value.to_string(), |value, filter| match *filter {
+            "upper" => value.to_uppercase(),
+            "lower" => value.to_lowercase(),
+            "trim" => value.trim().to_string(),
+            "slug" => slug(&value),
+            "urlencode" => urlencode(&value),
+            "shell-quote" => shell_quote(&value),
+            "basename" => value
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or("")
+                .to_string(),
+            _ => value,
+        })
+}
+
+/// Each comma-separated entry of `value` filtered and put in `format` at `{value}`,
+/// joined by spaces; empty entries are skipped
+pub fn repeat(format: &str, value: &str, filters: &[&str]) -> String {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| format.replace("{value}", &apply(entry, filters)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `value` in single quotes, safe as one word of a shell command
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Lowercase letters and digits, other runs of characters becoming one dash
+pub fn slug(value: &str) -> String {
+    let mut slug = String::new();
+    for ch in value.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// Percent-encode everything but the unreserved characters of RFC 3986
+fn urlencode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
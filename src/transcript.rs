@@ -0,0 +1,66 @@
+use serde::Serialize;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::expand_home;
+use crate::state::now_secs;
+
+/// A finished run, one JSON line of the transcript
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    pub column: String,
+    pub label: String,
+    pub command: String,
+    // unix time the run started at, and how long it took
+    pub started: u64,
+    pub duration_secs: f64,
+    // None when the command could not start
+    pub exit_code: Option<i32>,
+    // foreground runs own the terminal, their output is not captured
+    pub background: bool,
+    pub output: Option<Vec<String>>,
+}
+
+impl Entry {
+    pub fn new(column: &str, label: &str, command: &str, duration: Duration) -> Self {
+        Self {
+            column: column.to_string(),
+            label: label.to_string(),
+            command: command.to_string(),
+            started: now_secs().saturating_sub(duration.as_secs()),
+            duration_secs: duration.as_secs_f64(),
+            exit_code: None,
+            background: false,
+            output: None,
+        }
+    }
+}
+
+/// File every run of the session is appended to (`--record FILE`), one JSON object
+/// per line, e.g. to attach to an incident ticket or replay with
+/// `jq -r .command FILE | sh`
+#[derive(Debug)]
+pub struct Transcript {
+    path: PathBuf,
+}
+
+impl Transcript {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: expand_home(path),
+        }
+    }
+
+    pub fn append(&self, entry: &Entry) -> Result<(), Box<dyn Error>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open '{}': {}", self.path.display(), e))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,984 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Constraint;
+use ratatui::widgets::{ListState, TableState};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::list::ItemCache;
+use super::path_completion;
+use crate::config::{Action, Column, ColumnLayout, Parameter, ParameterType, TemplateEngine};
+use crate::template;
+
+/// Column state: tracks selection within a column
+pub struct ColumnState {
+    pub title: String,
+    pub actions: Vec<Action>,
+    pub list_state: ListState,
+    // scroll position when drawn as a table; the selection always follows `list_state`
+    pub table_state: TableState,
+    pub layout: ColumnLayout,
+    // fixed width in cells, from the config or set by resizing a fixed column
+    pub width: Option<u16>,
+    // share of the space left by fixed columns
+    pub weight: u32,
+    // collapsed to a narrow strip showing only the title
+    pub collapsed: bool,
+    // actions hidden for this session's user/profile
+    pub hidden_actions: usize,
+    // list items of the last frame
+    pub items: ItemCache,
+}
+
+// starting share of a flexible column, and the step and floor used when resizing
+const DEFAULT_WEIGHT: u32 = 10;
+const WEIGHT_STEP: u32 = 2;
+const MIN_WEIGHT: u32 = 2;
+// resize step and floor of fixed width columns, in cells
+const WIDTH_STEP: u16 = 4;
+const MIN_WIDTH: u16 = 8;
+// width of a collapsed column: its borders and one character of title per row
+pub const COLLAPSED_WIDTH: u16 = 3;
+
+/// Identifies an action by its column and its position in that column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionKey {
+    pub column: usize,
+    pub action: usize,
+}
+
+/// Columns and which column/action has focus. Every lookup is bounds checked so
+/// callers never index the column or action vectors directly.
+pub struct NavigationState {
+    pub columns: Vec<ColumnState>,
+    focused_column: usize,
+}
+
+impl NavigationState {
+    pub fn new(columns: &[Column]) -> Self {
+        let columns = columns
+            .iter()
+            .map(|col| {
+                let mut list_state = ListState::default();
+                if !col.actions.is_empty() {
+                    list_state.select(Some(0));
+                }
+                ColumnState {
+                    title: col.title.clone(),
+                    actions: col.actions.clone(),
+                    list_state,
+                    table_state: TableState::default(),
+                    layout: col.layout,
+                    hidden_actions: col.hidden_actions,
+                    width: col.width,
+                    weight: col.weight.map_or(DEFAULT_WEIGHT, |w| w * DEFAULT_WEIGHT),
+                    collapsed: false,
+                    items: ItemCache::default(),
+                }
+            })
+            .collect();
+        Self {
+            columns,
+            focused_column: 0,
+        }
+    }
+
+    pub fn focused_column(&self) -> usize {
+        self.focused_column
+    }
+
+    pub fn column(&self, c: usize) -> Option<&ColumnState> {
+        self.columns.get(c)
+    }
+
+    pub fn focused(&self) -> Option<&ColumnState> {
+        self.columns.get(self.focused_column)
+    }
+
+    pub fn focused_mut(&mut self) -> Option<&mut ColumnState> {
+        self.columns.get_mut(self.focused_column)
+    }
+
+    pub fn action(&self, key: ActionKey) -> Option<&Action> {
+        self.columns
+            .get(key.column)
+            .and_then(|col| col.actions.get(key.action))
+    }
+
+    pub fn action_mut(&mut self, key: ActionKey) -> Option<&mut Action> {
+        self.columns
+            .get_mut(key.column)
+            .and_then(|col| col.actions.get_mut(key.action))
+    }
+
+    /// Key of the selected action in the focused column
+    pub fn focused_key(&self) -> Option<ActionKey> {
+        let col = self.focused()?;
+        let action = col
+            .list_state
+            .selected()
+            .filter(|&i| i < col.actions.len())?;
+        Some(ActionKey {
+            column: self.focused_column,
+            action,
+        })
+    }
+
+    pub fn focused_action(&self) -> Option<&Action> {
+        self.focused_key().and_then(|key| self.action(key))
+    }
+
+    pub fn focus_next_column(&mut self) {
+        if !self.columns.is_empty() {
+            self.focused_column = (self.focused_column + 1) % self.columns.len();
+        }
+    }
+
+    /// Focus column `c`; out of range columns are ignored
+    pub fn focus_column(&mut self, c: usize) {
+        if c < self.columns.len() {
+            self.focused_column = c;
+        }
+    }
+
+    pub fn focus_prev_column(&mut self) {
+        if !self.columns.is_empty() {
+            self.focused_column =
+                (self.focused_column + self.columns.len() - 1) % self.columns.len();
+        }
+    }
+
+    /// Focus the column `delta` columns away, stopping at the first/last one
+    pub fn move_column(&mut self, delta: isize) {
+        let last = self.columns.len().saturating_sub(1) as isize;
+        self.focused_column = (self.focused_column as isize + delta).clamp(0, last) as usize;
+    }
+
+    /// Focus the column of `key` and select its action
+    pub fn select(&mut self, key: ActionKey) {
+        if self.action(key).is_some() {
+            self.focused_column = key.column;
+            self.columns[key.column].list_state.select(Some(key.action));
+        }
+    }
+
+    /// Move the selection of the focused column by `delta`, clamped to its actions
+    pub fn move_selection(&mut self, delta: isize) {
+        if let Some(col) = self.focused_mut() {
+            if let Some(curr) = col.list_state.selected() {
+                let last = col.actions.len().saturating_sub(1) as isize;
+                let new = (curr as isize + delta).clamp(0, last);
+                col.list_state.select(Some(new as usize));
+            }
+        }
+    }
+
+    /// Grow (`grow = true`) or shrink the focused column
+    pub fn resize_focused(&mut self, grow: bool) {
+        if let Some(col) = self.focused_mut() {
+            match col.width {
+                Some(ref mut width) if grow => *width = width.saturating_add(WIDTH_STEP),
+                Some(ref mut width) => *width = width.saturating_sub(WIDTH_STEP).max(MIN_WIDTH),
+                None if grow => col.weight += WEIGHT_STEP,
+                None => col.weight = col.weight.saturating_sub(WEIGHT_STEP).max(MIN_WEIGHT),
+            }
+        }
+    }
+
+    /// Collapse the focused column to a strip, or expand it back
+    pub fn toggle_collapsed(&mut self) {
+        if let Some(col) = self.focused_mut() {
+            col.collapsed = !col.collapsed;
+        }
+    }
+
+    /// Horizontal layout of the columns: collapsed and fixed widths first, the rest
+    /// split by weight
+    pub fn constraints(&self) -> Vec<Constraint> {
+        let total: u32 = self
+            .columns
+            .iter()
+            .filter(|c| c.width.is_none() && !c.collapsed)
+            .map(|c| c.weight)
+            .sum();
+        self.columns
+            .iter()
+            .map(|c| match c.width {
+                _ if c.collapsed => Constraint::Length(COLLAPSED_WIDTH),
+                Some(width) => Constraint::Length(width),
+                None => Constraint::Ratio(c.weight, total.max(1)),
+            })
+            .collect()
+    }
+
+    pub fn select_first(&mut self) {
+        if let Some(col) = self.focused_mut() {
+            if !col.actions.is_empty() {
+                col.list_state.select(Some(0));
+            }
+        }
+    }
+
+    pub fn select_last(&mut self) {
+        if let Some(col) = self.focused_mut() {
+            if !col.actions.is_empty() {
+                col.list_state.select(Some(col.actions.len() - 1));
+            }
+        }
+    }
+}
+
+/// Text edit in progress on a parameter of the details view (also used for the
+/// parameter filter and the final command)
+pub struct EditState {
+    pub buffer: String,
+    // value before editing, restored on Esc
+    pub original: String,
+    // position in `buffer`, in characters
+    pub cursor: usize,
+    // buffer and cursor before each change, for Ctrl+Z, and the undone ones for Ctrl+Y
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    // the last change typed a character: the next one joins it in one undo step
+    typing: bool,
+    // recent value highlighted with Up/Down, see `App::suggestions`
+    pub suggestion: Option<usize>,
+    // paths cycled with Tab and the one in the buffer, see `complete_path`
+    completion: Option<(Vec<String>, usize)>,
+}
+
+impl EditState {
+    /// Edit `value`, with the cursor after its last character
+    pub fn new(value: String) -> Self {
+        Self::resume(value.clone(), value)
+    }
+
+    /// Edit `buffer`, already changed from `original`
+    pub fn resume(buffer: String, original: String) -> Self {
+        let cursor = buffer.chars().count();
+        Self {
+            buffer,
+            original,
+            cursor,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            typing: false,
+            suggestion: None,
+            completion: None,
+        }
+    }
+
+    /// The edit with every character shown as a bullet, for drawing a password
+    pub fn masked(&self) -> Self {
+        let mut masked = Self::new("•".repeat(self.buffer.chars().count()));
+        masked.cursor = self.cursor;
+        masked
+    }
+
+    /// Apply a cursor movement, deletion or undo key; false for other keys
+    pub fn apply_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('z') if ctrl => self.undo(),
+            KeyCode::Char('y') if ctrl => self.redo(),
+            KeyCode::Left if ctrl => self.cursor = self.word_start(),
+            KeyCode::Right if ctrl => self.cursor = self.word_end(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.record(false);
+                self.cursor -= 1;
+                self.buffer.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Delete if self.cursor < self.len() => {
+                self.record(false);
+                self.buffer.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.record(false);
+                self.buffer.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        // moving the cursor ends a run of typing
+        if !matches!(key.code, KeyCode::Char(_)) {
+            self.typing = false;
+        }
+        self.completion = None;
+        true
+    }
+
+    /// Complete the path before the cursor, relative to `base`, like readline: the
+    /// first Tab inserts what the candidates share, the next ones cycle through them
+    /// (`backwards` for Shift+Tab). False when nothing matches.
+    pub fn complete_path(&mut self, base: &Path, backwards: bool) -> bool {
+        if let Some((candidates, index)) = self.completion.take() {
+            let count = candidates.len();
+            let index = if backwards {
+                (index + count - 1) % count
+            } else {
+                (index + 1) % count
+            };
+            self.replace_before_cursor(&candidates[index]);
+            self.completion = Some((candidates, index));
+            return true;
+        }
+        let (typed, _, _) = self.split();
+        let typed = typed.to_string();
+        let candidates = path_completion::candidates(&typed, base);
+        match candidates.len() {
+            0 => false,
+            1 => {
+                self.replace_before_cursor(&candidates[0]);
+                true
+            }
+            count => {
+                let common = path_completion::common_prefix(&candidates);
+                if common.len() > typed.len() {
+                    self.replace_before_cursor(&common);
+                } else {
+                    let index = if backwards { count - 1 } else { 0 };
+                    self.replace_before_cursor(&candidates[index]);
+                    self.completion = Some((candidates, index));
+                }
+                true
+            }
+        }
+    }
+
+    /// Highlight the next (`delta` 1) or previous (-1) of `count` suggestions;
+    /// going up from the first one leaves the suggestions
+    pub fn step_suggestion(&mut self, delta: isize, count: usize) {
+        self.suggestion = match (self.suggestion, delta) {
+            _ if count == 0 => None,
+            (None, 1..) => Some(0),
+            (None, _) | (Some(0), ..=-1) => None,
+            (Some(i), ..=-1) => Some(i - 1),
+            (Some(i), _) => Some((i + 1).min(count - 1)),
+        };
+    }
+
+    /// Type a character at the cursor
+    pub fn insert(&mut self, ch: char) {
+        self.record(true);
+        self.buffer.insert(self.byte_index(self.cursor), ch);
+        self.cursor += 1;
+    }
+
+    /// Insert pasted text at the cursor. Values are single lines: a trailing line
+    /// break is dropped and the others become spaces.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.trim_end_matches(['\r', '\n']).replace("\r\n", " ");
+        let text = text.replace(['\r', '\n'], " ");
+        self.record(false);
+        self.buffer.insert_str(self.byte_index(self.cursor), &text);
+        self.cursor += text.chars().count();
+    }
+
+    /// Text before the cursor, the character under it (if any) and the rest
+    pub fn split(&self) -> (&str, Option<char>, &str) {
+        let (before, after) = self.buffer.split_at(self.byte_index(self.cursor));
+        let mut rest = after.chars();
+        let under = rest.next();
+        (before, under, rest.as_str())
+    }
+
+    // Replace the text before the cursor with `text`, leaving the cursor after it
+    fn replace_before_cursor(&mut self, text: &str) {
+        self.record(false);
+        self.buffer
+            .replace_range(..self.byte_index(self.cursor), text);
+        self.cursor = text.chars().count();
+    }
+
+    // Save the state before a change; a run of typed characters is one step
+    fn record(&mut self, typing: bool) {
+        // the suggestions and the completions change with the buffer
+        self.suggestion = None;
+        self.completion = None;
+        if !(typing && self.typing) {
+            self.undo.push((self.buffer.clone(), self.cursor));
+        }
+        self.typing = typing;
+        self.redo.clear();
+    }
+
+    fn undo(&mut self) {
+        self.suggestion = None;
+        self.completion = None;
+        if let Some((buffer, cursor)) = self.undo.pop() {
+            let current = std::mem::replace(&mut self.buffer, buffer);
+            self.redo.push((current, self.cursor));
+            self.cursor = cursor;
+        }
+        self.typing = false;
+    }
+
+    fn redo(&mut self) {
+        self.suggestion = None;
+        self.completion = None;
+        if let Some((buffer, cursor)) = self.redo.pop() {
+            let current = std::mem::replace(&mut self.buffer, buffer);
+            self.undo.push((current, self.cursor));
+            self.cursor = cursor;
+        }
+        self.typing = false;
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.chars().count()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.buffer.len(), |(i, _)| i)
+    }
+
+    // Start of the word before the cursor; words are runs of letters and digits
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        let mut i = self.cursor;
+        while i > 0 && !chars[i - 1].is_alphanumeric() {
+            i -= 1;
+        }
+        while i > 0 && chars[i - 1].is_alphanumeric() {
+            i -= 1;
+        }
+        i
+    }
+
+    // End of the word after the cursor
+    fn word_end(&self) -> usize {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        let mut i = self.cursor;
+        while i < chars.len() && !chars[i].is_alphanumeric() {
+            i += 1;
+        }
+        while i < chars.len() && chars[i].is_alphanumeric() {
+            i += 1;
+        }
+        i
+    }
+}
+
+/// Details view of the focused action
+pub struct DetailsState {
+    // when true, the middle area shows the details view for the focused action
+    pub open: bool,
+    // index of the focused parameter
+    pub focused_param: usize,
+    // set while a text parameter is being edited
+    pub edit: Option<EditState>,
+    // set while the final command is being edited (`e`)
+    pub command_edit: Option<EditState>,
+    // hand-edited command run instead of the substituted template
+    pub command_override: Option<String>,
+    // blinking cursor state (toggled on ticks)
+    pub cursor_on: bool,
+    // first row shown when the parameters don't fit
+    pub scroll: usize,
+    // only parameters whose name or description contain this are listed (`/`)
+    pub filter: String,
+    // set while the filter is being typed
+    pub filter_edit: Option<EditState>,
+    // indexes of the parameters matching the filter, see `apply_filter`
+    visible: Vec<usize>,
+    // from the last render: row at which each visible parameter starts, total rows
+    // and visible height; used to page and to keep the focused parameter in view
+    param_rows: Vec<usize>,
+    total_rows: usize,
+    view_height: usize,
+}
+
+impl Default for DetailsState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            focused_param: 0,
+            edit: None,
+            command_edit: None,
+            command_override: None,
+            cursor_on: true,
+            scroll: 0,
+            filter: String::new(),
+            filter_edit: None,
+            visible: Vec::new(),
+            param_rows: Vec::new(),
+            total_rows: 0,
+            view_height: 0,
+        }
+    }
+}
+
+impl DetailsState {
+    /// Open the view with the first parameter focused
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focused_param = 0;
+        self.edit = None;
+        self.command_edit = None;
+        self.command_override = None;
+        self.scroll = 0;
+        self.filter.clear();
+        self.filter_edit = None;
+        self.visible.clear();
+    }
+
+    /// Close the view, dropping any edit and hand-edited command
+    pub fn close(&mut self) {
+        self.open = false;
+        self.edit = None;
+        self.command_edit = None;
+        self.command_override = None;
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.edit.is_some()
+    }
+
+    /// Narrow the listed parameters to the ones matching `filter`, moving the focus
+    /// to the first of them when the focused one is filtered out
+    pub fn apply_filter(&mut self, params: &[Parameter]) {
+        let filter = self.filter.to_lowercase();
+        self.visible = params
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| {
+                p.name.to_lowercase().contains(&filter)
+                    || p.description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&filter))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        if !self.visible.contains(&self.focused_param) {
+            if let Some(&first) = self.visible.first() {
+                self.focused_param = first;
+            }
+        }
+    }
+
+    /// Whether the parameter at `idx` is listed with the current filter
+    pub fn is_visible(&self, idx: usize) -> bool {
+        self.visible.contains(&idx)
+    }
+
+    /// Number of parameters listed with the current filter
+    pub fn visible_count(&self) -> usize {
+        self.visible.len()
+    }
+
+    // Position of the focused parameter among the visible ones
+    fn focused_position(&self) -> usize {
+        self.visible
+            .iter()
+            .position(|&idx| idx == self.focused_param)
+            .unwrap_or(0)
+    }
+
+    /// Focus the previous listed parameter
+    pub fn focus_prev(&mut self) {
+        let pos = self.focused_position().saturating_sub(1);
+        if let Some(&idx) = self.visible.get(pos) {
+            self.focused_param = idx;
+        }
+    }
+
+    /// Focus the next listed parameter, staying on the last one
+    pub fn focus_next(&mut self) {
+        if let Some(&idx) = self.visible.get(self.focused_position() + 1) {
+            self.focused_param = idx;
+        }
+    }
+
+    /// Record the rendered layout and scroll so the focused parameter is visible
+    pub fn layout(&mut self, param_rows: Vec<usize>, total_rows: usize, view_height: usize) {
+        self.param_rows = param_rows;
+        self.total_rows = total_rows;
+        self.view_height = view_height.max(1);
+
+        let (start, end) = self.focused_rows();
+        if start < self.scroll {
+            self.scroll = start;
+        } else if end > self.scroll + self.view_height {
+            // show the whole parameter when it fits, else its first rows
+            self.scroll = end.saturating_sub(self.view_height).min(start);
+        }
+        // don't leave blank rows at the bottom when scrolled back up
+        let max_scroll = self.total_rows.saturating_sub(self.view_height);
+        self.scroll = self.scroll.min(max_scroll);
+    }
+
+    // Rows spanned by the focused parameter: its lines up to the next parameter
+    fn focused_rows(&self) -> (usize, usize) {
+        let pos = self.focused_position();
+        let start = self.param_rows.get(pos).copied().unwrap_or(0);
+        let end = self
+            .param_rows
+            .get(pos + 1)
+            .copied()
+            .unwrap_or(self.total_rows);
+        (start, end)
+    }
+
+    /// Focus the first parameter at least a screen below the focused one
+    pub fn page_down(&mut self) {
+        let target = self.focused_rows().0 + self.view_height;
+        let last = self.param_rows.len().saturating_sub(1);
+        let pos = self
+            .param_rows
+            .iter()
+            .position(|&row| row >= target)
+            .unwrap_or(last);
+        if let Some(&idx) = self.visible.get(pos) {
+            self.focused_param = idx;
+        }
+    }
+
+    /// Focus the last parameter at least a screen above the focused one
+    pub fn page_up(&mut self) {
+        let target = self.focused_rows().0.saturating_sub(self.view_height);
+        let pos = self
+            .param_rows
+            .iter()
+            .rposition(|&row| row <= target)
+            .unwrap_or(0);
+        if let Some(&idx) = self.visible.get(pos) {
+            self.focused_param = idx;
+        }
+    }
+}
+
+/// Current value of a parameter; `selected` is the option index for selects
+#[derive(Debug, Clone, Default)]
+pub struct ParamValue {
+    pub value: String,
+    pub selected: usize,
+}
+
+impl ParamValue {
+    /// Initial value: the default, or for selects the option matching it (else the first)
+    fn initial(param: &Parameter) -> Self {
+        // a select with a source has no options until they are queried
+        if param.param_type == ParameterType::Select && !param.options.is_empty() {
+            let selected = param
+                .default
+                .as_ref()
+                .and_then(|def| param.options.iter().position(|o| &o.value == def))
+                .unwrap_or(0);
+            Self {
+                value: param
+                    .options
+                    .get(selected)
+                    .map(|o| o.value.clone())
+                    .unwrap_or_default(),
+                selected,
+            }
+        } else {
+            Self {
+                value: param
+                    .default
+                    .clone()
+                    .or_else(|| param.secret.clone())
+                    .unwrap_or_default(),
+                selected: 0,
+            }
+        }
+    }
+}
+
+/// Piece of a substituted command: template text, or the value of parameter `param`
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub text: String,
+    pub param: Option<usize>,
+    // for a value, its placeholder as written in the template, e.g. `{BRANCH|slug}`
+    pub placeholder: String,
+}
+
+/// Parameter values of every action, keyed by action. Missing keys or parameter
+/// indices read as empty and writes to them are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ParamStore {
+    values: HashMap<ActionKey, Vec<ParamValue>>,
+    // name, placeholder and value of the global selector, used by every action
+    selector: Option<(String, String, String)>,
+}
+
+impl ParamStore {
+    pub fn new(columns: &[Column]) -> Self {
+        let mut values = HashMap::new();
+        for (column, col) in columns.iter().enumerate() {
+            for (action, act) in col.actions.iter().enumerate() {
+                values.insert(
+                    ActionKey { column, action },
+                    act.parameters.iter().map(ParamValue::initial).collect(),
+                );
+            }
+        }
+        Self {
+            values,
+            selector: None,
+        }
+    }
+
+    /// Value of the global selector `param`, replacing its placeholder in the
+    /// templates of actions without a parameter of their own for it
+    pub fn set_selector(&mut self, param: &Parameter, value: &str) {
+        self.selector = Some((
+            param.name.clone(),
+            param.placeholder.clone(),
+            value.to_string(),
+        ));
+    }
+
+    fn get(&self, key: ActionKey, pidx: usize) -> Option<&ParamValue> {
+        self.values.get(&key).and_then(|params| params.get(pidx))
+    }
+
+    fn get_mut(&mut self, key: ActionKey, pidx: usize) -> Option<&mut ParamValue> {
+        self.values
+            .get_mut(&key)
+            .and_then(|params| params.get_mut(pidx))
+    }
+
+    /// Put back the value the parameter starts with, its configured default
+    pub fn reset(&mut self, key: ActionKey, pidx: usize, param: &Parameter) {
+        if let Some(p) = self.get_mut(key, pidx) {
+            *p = ParamValue::initial(param);
+        }
+    }
+
+    /// Whether the value is still the one the parameter starts with
+    pub fn is_initial(&self, key: ActionKey, pidx: usize, param: &Parameter) -> bool {
+        self.value(key, pidx) == ParamValue::initial(param).value
+    }
+
+    pub fn value(&self, key: ActionKey, pidx: usize) -> &str {
+        self.get(key, pidx).map(|p| p.value.as_str()).unwrap_or("")
+    }
+
+    pub fn selected(&self, key: ActionKey, pidx: usize) -> usize {
+        self.get(key, pidx).map(|p| p.selected).unwrap_or(0)
+    }
+
+    pub fn set_value(&mut self, key: ActionKey, pidx: usize, value: String) {
+        if let Some(p) = self.get_mut(key, pidx) {
+            p.value = value;
+        }
+    }
+
+    /// Set a saved value; for selects only when it is one of the options, or any
+    /// value while the options of a source are not known yet
+    pub fn restore(&mut self, key: ActionKey, pidx: usize, param: &Parameter, value: &str) {
+        let selected = if param.param_type == ParameterType::Select {
+            match param.options.iter().position(|o| o.value == value) {
+                Some(i) => i,
+                None if param.source.is_some() && param.options.is_empty() => 0,
+                None => return,
+            }
+        } else {
+            0
+        };
+        if let Some(p) = self.get_mut(key, pidx) {
+            p.value = value.to_string();
+            p.selected = selected;
+        }
+    }
+
+    /// Move the selected option of a select by `delta`, keeping the value in sync
+    pub fn step_option(&mut self, key: ActionKey, pidx: usize, param: &Parameter, delta: isize) {
+        if param.options.is_empty() {
+            return;
+        }
+        if let Some(p) = self.get_mut(key, pidx) {
+            let last = param.options.len() as isize - 1;
+            let new = (p.selected as isize + delta).clamp(0, last) as usize;
+            p.selected = new;
+            p.value = param.options[new].value.clone();
+        }
+    }
+
+    /// The action's template with every placeholder replaced by its current value
+    pub fn substitute(&self, key: ActionKey, action: &Action) -> String {
+        self.substitute_with(action, &|pidx| self.value(key, pidx).to_string())
+    }
+
+    /// `substitute` with the value of each parameter, by index, given by `value`: e.g.
+    /// a secret masked where it goes
+    pub fn substitute_with(&self, action: &Action, value: &dyn Fn(usize) -> String) -> String {
+        self.segments_with(action, value)
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect()
+    }
+
+    /// The substituted template split into literal text and parameter values
+    pub fn segments(&self, key: ActionKey, action: &Action) -> Vec<Segment> {
+        self.segments_with(action, &|pidx| self.value(key, pidx).to_string())
+    }
+
+    fn segments_with(&self, action: &Action, value: &dyn Fn(usize) -> String) -> Vec<Segment> {
+        let values: Vec<String> = (0..action.parameters.len()).map(value).collect();
+        let mut segments = Vec::new();
+        let rendered;
+        let mut rest = match action.engine {
+            TemplateEngine::Plain => action.template.as_str(),
+            TemplateEngine::Handlebars => {
+                let values = self
+                    .selector
+                    .iter()
+                    .map(|(name, _, value)| (name.as_str(), value.as_str()))
+                    .chain(
+                        action
+                            .parameters
+                            .iter()
+                            .zip(&values)
+                            .map(|(p, value)| (p.name.as_str(), value.as_str())),
+                    );
+                // templates are checked at load, a failed render leaves it as written
+                rendered = template::render_handlebars(&action.template, values)
+                    .unwrap_or_else(|_| action.template.clone());
+                rendered.as_str()
+            }
+        };
+        loop {
+            // earliest placeholder in what's left; the longest one wins a tie, and the
+            // action's own parameters win over the selector
+            let next = action
+                .parameters
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| !p.placeholder.is_empty())
+                .filter_map(|(pidx, p)| {
+                    template::find(rest, &p.placeholder).map(|o| (o, Some((pidx, p))))
+                })
+                .chain(
+                    self.selector
+                        .iter()
+                        .filter_map(|(_, placeholder, _)| template::find(rest, placeholder))
+                        .map(|o| (o, None)),
+                )
+                .min_by_key(|(o, _)| (o.at, std::cmp::Reverse(o.len)));
+            let Some((found, param)) = next else {
+                break;
+            };
+            if found.at > 0 {
+                segments.push(Segment {
+                    text: rest[..found.at].to_string(),
+                    param: None,
+                    placeholder: String::new(),
+                });
+            }
+            let placeholder = rest[found.at..found.at + found.len].to_string();
+            segments.push(match param {
+                Some((pidx, param)) => {
+                    let value = &values[pidx];
+                    let text = match param.repeat_format {
+                        Some(ref format) => template::repeat(format, value, &found.filters),
+                        None => template::apply(value, &found.filters),
+                    };
+                    Segment {
+                        text,
+                        param: Some(pidx),
+                        placeholder,
+                    }
+                }
+                None => {
+                    let value = self.selector.as_ref().map_or("", |(.., value)| value);
+                    Segment {
+                        text: template::apply(value, &found.filters),
+                        param: None,
+                        placeholder,
+                    }
+                }
+            });
+            rest = &rest[found.at + found.len..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment {
+                text: rest.to_string(),
+                param: None,
+                placeholder: String::new(),
+            });
+        }
+        drop_empty_groups(segments)
+    }
+}
+
+// Optional groups of a template, like `[--tag {TAG}]`: a `[` directly followed by
+// something else than a space (so `[ -f x ]` stays a shell test), up to the next
+// `]`, with a placeholder inside. The group is dropped with a space next to it when
+// one of its values is empty, else only its brackets are.
+fn drop_empty_groups(segments: Vec<Segment>) -> Vec<Segment> {
+    enum Piece {
+        Char(char),
+        Value(Segment),
+    }
+    let mut pieces = Vec::new();
+    for segment in segments {
+        match segment.param {
+            Some(_) => pieces.push(Piece::Value(segment)),
+            None => pieces.extend(segment.text.chars().map(Piece::Char)),
+        }
+    }
+    let is_char = |piece: Option<&Piece>, test: &dyn Fn(char) -> bool| matches!(piece, Some(Piece::Char(c)) if test(*c));
+
+    let mut i = 0;
+    while i < pieces.len() {
+        let opens = is_char(pieces.get(i), &|c| c == '[')
+            && i + 1 < pieces.len()
+            && !is_char(pieces.get(i + 1), &char::is_whitespace);
+        let close = pieces[i + 1..]
+            .iter()
+            .position(|p| matches!(p, Piece::Char('[' | ']')))
+            .map(|n| i + 1 + n)
+            .filter(|&j| is_char(pieces.get(j), &|c| c == ']'));
+        let values: Vec<&Segment> = match close {
+            Some(j) if opens => pieces[i + 1..j]
+                .iter()
+                .filter_map(|p| match p {
+                    Piece::Value(segment) => Some(segment),
+                    Piece::Char(_) => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let Some(j) = close.filter(|_| !values.is_empty()) else {
+            i += 1;
+            continue;
+        };
+        if values.iter().all(|segment| !segment.text.is_empty()) {
+            pieces.remove(j);
+            pieces.remove(i);
+        } else {
+            pieces.drain(i..=j);
+            let space_after = i == pieces.len() || is_char(pieces.get(i), &|c| c == ' ');
+            if i > 0 && space_after && is_char(pieces.get(i - 1), &|c| c == ' ') {
+                pieces.remove(i - 1);
+                i -= 1;
+            } else if i == 0 && space_after && !pieces.is_empty() {
+                pieces.remove(0);
+            }
+        }
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for piece in pieces {
+        match (piece, segments.last_mut()) {
+            (Piece::Char(c), Some(last)) if last.param.is_none() => last.text.push(c),
+            (Piece::Char(c), _) => segments.push(Segment {
+                text: c.to_string(),
+                param: None,
+                placeholder: String::new(),
+            }),
+            (Piece::Value(segment), _) => segments.push(segment),
+        }
+    }
+    segments
+}
@@ -0,0 +1,67 @@
+/// A line of the diff between two outputs
+#[derive(Debug, PartialEq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+// lines compared pairwise past the common start and end; longer outputs show as
+// replaced wholesale
+const MAX_CELLS: usize = 4_000_000;
+
+/// Line diff of `old` and `new`: the longest common subsequence kept, removed lines
+/// before the added ones they are replaced by
+pub fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<Change<'a>> {
+    let head = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let tail = old[head..]
+        .iter()
+        .rev()
+        .zip(new[head..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[head..old.len() - tail], &new[head..new.len() - tail]);
+
+    let mut changes: Vec<Change> = old[..head].iter().map(|l| Change::Same(l)).collect();
+    if old_mid.len() * new_mid.len() > MAX_CELLS {
+        changes.extend(old_mid.iter().map(|l| Change::Removed(l)));
+        changes.extend(new_mid.iter().map(|l| Change::Added(l)));
+    } else {
+        changes.extend(middle(old_mid, new_mid));
+    }
+    changes.extend(old[old.len() - tail..].iter().map(|l| Change::Same(l)));
+    changes
+}
+
+// Diff by the table of common subsequence lengths of every pair of suffixes
+fn middle<'a>(old: &'a [String], new: &'a [String]) -> Vec<Change<'a>> {
+    let width = new.len() + 1;
+    let mut lengths = vec![0usize; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(&old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len()
+            || (i < old.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            changes.push(Change::Removed(&old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(&new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
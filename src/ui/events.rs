@@ -0,0 +1,187 @@
+use crossterm::event::{self, Event, KeyEvent, MouseEvent};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::control::{self, Request, Socket};
+use crate::error::CallbotError;
+
+// how long the reader waits for input before checking whether it was paused
+const READ_POLL: Duration = Duration::from_millis(50);
+// period of `AppEvent::Tick`: cursor blink, toast expiry, follow mode refresh
+const TICK_RATE: Duration = Duration::from_millis(500);
+// how often the config file is checked for changes
+const CONFIG_POLL: Duration = Duration::from_secs(1);
+
+/// Everything the main loop reacts to. Terminal input is translated here so the
+/// rest of the UI doesn't depend on crossterm's event type.
+pub enum AppEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    // the terminal was resized
+    Resize,
+    // text pasted into the terminal, received at once with bracketed paste
+    Paste(String),
+    Tick,
+    // background jobs have new output or finished; `Jobs::poll` collects it
+    JobOutput,
+    // the config file changed on disk and was loaded again, or failed to
+    ConfigReloaded(Result<Box<Config>, CallbotError>),
+    // SIGINT, SIGTERM or SIGHUP: clean up and exit
+    Signal(i32),
+    // a request on the control socket, answered through the sender
+    Control(Request, Sender<serde_json::Value>),
+}
+
+/// Sending side of the event channel, e.g. to inject events in tests
+pub type EventSender = Sender<io::Result<AppEvent>>;
+
+/// Events produced by background threads (terminal reader, ticker, signals, config
+/// watcher, control socket) over one channel, so the main loop sleeps until
+/// something happens
+pub struct Events {
+    tx: EventSender,
+    rx: Receiver<io::Result<AppEvent>>,
+    // set while a foreground command owns the terminal, see `pause`
+    paused: Arc<AtomicBool>,
+    // removes the control socket when the loop ends
+    _socket: Option<Socket>,
+}
+
+impl Events {
+    /// Start the threads; they stop once the receiving side is dropped. With a
+    /// `config_path`, changes to that file are reported as `ConfigReloaded`.
+    pub fn spawn(config_path: Option<PathBuf>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        spawn_reader(tx.clone(), Arc::clone(&paused));
+        spawn_ticker(tx.clone(), Arc::clone(&paused));
+        spawn_signal_watcher(tx.clone());
+        if let Some(path) = config_path {
+            spawn_config_watcher(tx.clone(), path);
+        }
+        let control_tx = tx.clone();
+        let socket = control::listen(move |request, reply| {
+            control_tx
+                .send(Ok(AppEvent::Control(request, reply)))
+                .is_ok()
+        });
+        Self {
+            tx,
+            rx,
+            paused,
+            _socket: socket,
+        }
+    }
+
+    pub fn sender(&self) -> EventSender {
+        self.tx.clone()
+    }
+
+    /// Next event, or None when `timeout` elapsed first
+    pub fn next(&self, timeout: Duration) -> io::Result<Option<AppEvent>> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(event) => event.map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "event threads stopped",
+            )),
+        }
+    }
+
+    /// Stop reading so a command run in the foreground gets the keyboard. Waits for
+    /// a read in progress to time out.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+        thread::sleep(READ_POLL * 2);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+}
+
+// Terminal input; a read error is forwarded and ends the thread
+fn spawn_reader(tx: EventSender, paused: Arc<AtomicBool>) {
+    thread::spawn(move || loop {
+        if paused.load(Ordering::Relaxed) {
+            thread::sleep(READ_POLL);
+            continue;
+        }
+        let event = match event::poll(READ_POLL) {
+            Ok(false) => continue,
+            Ok(true) => event::read(),
+            Err(err) => Err(err),
+        };
+        let event = match event {
+            Ok(Event::Key(key)) => Ok(AppEvent::Key(key)),
+            Ok(Event::Mouse(mouse)) => Ok(AppEvent::Mouse(mouse)),
+            Ok(Event::Resize(..)) => Ok(AppEvent::Resize),
+            Ok(Event::Paste(text)) => Ok(AppEvent::Paste(text)),
+            // focus changes aren't used
+            Ok(_) => continue,
+            Err(err) => Err(err),
+        };
+        let failed = event.is_err();
+        if tx.send(event).is_err() || failed {
+            break;
+        }
+    });
+}
+
+// Periodic tick; none are queued while a foreground command runs
+fn spawn_ticker(tx: EventSender, paused: Arc<AtomicBool>) {
+    thread::spawn(move || loop {
+        thread::sleep(TICK_RATE);
+        if !paused.load(Ordering::Relaxed) && tx.send(Ok(AppEvent::Tick)).is_err() {
+            break;
+        }
+    });
+}
+
+// Termination signals, so the terminal is restored instead of left in raw mode.
+// A signal that cannot be hooked keeps its default action.
+fn spawn_signal_watcher(tx: EventSender) {
+    let Ok(mut signals) = Signals::new([SIGINT, SIGTERM, SIGHUP]) else {
+        tracing::warn!("could not install signal handlers");
+        return;
+    };
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            if tx.send(Ok(AppEvent::Signal(signal))).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// Reload the config whenever its modification time changes
+fn spawn_config_watcher(tx: EventSender, path: PathBuf) {
+    let modified = |path: &PathBuf| -> Option<SystemTime> { path.metadata().ok()?.modified().ok() };
+    thread::spawn(move || {
+        let mut last = modified(&path);
+        loop {
+            thread::sleep(CONFIG_POLL);
+            let current = modified(&path);
+            if current.is_none() || current == last {
+                continue;
+            }
+            last = current;
+            let config = Config::load(&path).map(Box::new);
+            if let Err(ref err) = config {
+                tracing::warn!("config not reloaded: {}", err);
+            }
+            if tx.send(Ok(AppEvent::ConfigReloaded(config))).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+// Characters every FIGfont defines, in file order: printable ASCII then the
+// seven German letters
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+// Horizontal layout bits of a FIGfont header (`full_layout`): the controlled
+// smushing rules, then fitting and smushing themselves
+const SM_EQUAL: u32 = 1;
+const SM_LOWLINE: u32 = 2;
+const SM_HIERARCHY: u32 = 4;
+const SM_PAIR: u32 = 8;
+const SM_BIGX: u32 = 16;
+const SM_HARDBLANK: u32 = 32;
+const SM_KERN: u32 = 64;
+const SM_SMUSH: u32 = 128;
+const SM_RULES: u32 = 63;
+
+/// A FIGlet font (`.flf`), laid out as figlet does left to right: characters drawn
+/// at full width, moved together until they touch (fitting) or overlap by one column
+/// (smushing), following the layout and smushing rules of the font's header.
+pub struct Font {
+    height: usize,
+    hardblank: char,
+    layout: u32,
+    // rows of each glyph, hardblanks kept until the banner is laid out
+    glyphs: HashMap<char, Vec<Vec<char>>>,
+}
+
+impl Font {
+    /// Parse the content of a `.flf` file
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut lines = source.lines();
+        let header = lines.next().ok_or("empty font file")?;
+        let signature = header
+            .strip_prefix("flf2a")
+            .ok_or("not a FIGlet font (missing flf2a signature)")?;
+        let mut chars = signature.chars();
+        let hardblank = chars.next().ok_or("missing hardblank")?;
+        let numbers: Vec<i64> = chars
+            .as_str()
+            .split_whitespace()
+            .map(|n| n.parse().unwrap_or(0))
+            .collect();
+        let height = numbers
+            .first()
+            .copied()
+            .filter(|&h| h > 0)
+            .ok_or("missing height")? as usize;
+        let old_layout = numbers.get(3).copied().unwrap_or(0);
+        let comment_lines = numbers.get(4).copied().unwrap_or(0).max(0) as usize;
+        // the full layout supersedes the old one when given
+        let layout = match numbers.get(6) {
+            Some(&full) => full as u32 & (SM_RULES | SM_KERN | SM_SMUSH),
+            None if old_layout == 0 => SM_KERN,
+            None if old_layout < 0 => 0,
+            None => (old_layout as u32 & 31) | SM_SMUSH,
+        };
+
+        let mut lines = lines.skip(comment_lines);
+        let mut font = Self {
+            height,
+            hardblank,
+            layout,
+            glyphs: HashMap::new(),
+        };
+        for ch in (' '..='~').chain(DEUTSCH) {
+            let mut rows = Vec::with_capacity(height);
+            for row in 0..height {
+                let Some(line) = lines.next() else {
+                    // fonts may stop after ASCII; what was read so far is usable
+                    return Ok(font);
+                };
+                rows.push(glyph_row(line, row + 1 == height));
+            }
+            font.glyphs.insert(ch, rows);
+        }
+        Ok(font)
+    }
+
+    /// Whether the font has a glyph for every character of `text`
+    pub fn can_render(&self, text: &str) -> bool {
+        text.chars().all(|ch| self.glyphs.contains_key(&ch))
+    }
+
+    /// Render `text` as banner rows; characters the font lacks are skipped
+    pub fn render(&self, text: &str) -> Vec<String> {
+        let mut rows: Vec<Vec<char>> = vec![Vec::new(); self.height];
+        let mut previous_width = 0;
+        for ch in text.chars() {
+            let Some(glyph) = self.glyphs.get(&ch) else {
+                continue;
+            };
+            let width = glyph.first().map_or(0, Vec::len);
+            let amount = self.overlap(&rows, glyph, previous_width, width);
+            for (row, part) in rows.iter_mut().zip(glyph) {
+                let len = row.len();
+                for (k, &right) in part.iter().enumerate().take(amount) {
+                    if len + k >= amount {
+                        let column = len + k - amount;
+                        row[column] = self
+                            .smush(row[column], right, previous_width, width)
+                            .unwrap_or(right);
+                    }
+                }
+                row.extend(part.iter().skip(amount));
+            }
+            previous_width = width;
+        }
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|c| if c == self.hardblank { ' ' } else { c })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Columns a glyph can move left onto the banner so far: the fewest over its rows
+    // between the last ink of the banner and the first of the glyph, one more where
+    // the two characters meeting smush
+    fn overlap(
+        &self,
+        rows: &[Vec<char>],
+        glyph: &[Vec<char>],
+        previous: usize,
+        width: usize,
+    ) -> usize {
+        if self.layout & (SM_SMUSH | SM_KERN) == 0 {
+            return 0;
+        }
+        let mut amount = width;
+        for (row, part) in rows.iter().zip(glyph) {
+            let mut end = row.len();
+            while end > 0 && row.get(end).is_none_or(|&c| c == ' ') {
+                end -= 1;
+            }
+            let left = row.get(end).copied().filter(|&c| c != ' ');
+            let start = part.iter().take_while(|&&c| c == ' ').count();
+            let mut fits = (start + row.len()) as isize - 1 - end as isize;
+            match (left, part.get(start)) {
+                (None, _) => fits += 1,
+                (Some(left), Some(&right)) => {
+                    if self.smush(left, right, previous, width).is_some() {
+                        fits += 1;
+                    }
+                }
+                (Some(_), None) => {}
+            }
+            amount = amount.min(fits.max(0) as usize);
+        }
+        amount
+    }
+
+    // The character two overlapping ones become, None when they can't overlap
+    fn smush(&self, left: char, right: char, previous: usize, width: usize) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        if previous < 2 || width < 2 || self.layout & SM_SMUSH == 0 {
+            return None;
+        }
+        let hardblank = self.hardblank;
+        // universal smushing: the right character wins, hardblanks lose
+        if self.layout & SM_RULES == 0 {
+            return Some(if right == hardblank { left } else { right });
+        }
+        if self.layout & SM_HARDBLANK != 0 && left == hardblank && right == hardblank {
+            return Some(left);
+        }
+        if left == hardblank || right == hardblank {
+            return None;
+        }
+        if self.layout & SM_EQUAL != 0 && left == right {
+            return Some(left);
+        }
+        if self.layout & SM_LOWLINE != 0 {
+            const BORDERS: &str = "|/\\[]{}()<>";
+            if left == '_' && BORDERS.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && BORDERS.contains(left) {
+                return Some(left);
+            }
+        }
+        if self.layout & SM_HIERARCHY != 0 {
+            // classes in rank order: the character of the higher class stays
+            const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |c: char| CLASSES.iter().position(|class| class.contains(c));
+            if let (Some(l), Some(r)) = (class(left), class(right)) {
+                if l != r {
+                    return Some(if l > r { left } else { right });
+                }
+            }
+        }
+        if self.layout & SM_PAIR != 0 {
+            const PAIRS: [(char, char); 3] = [('[', ']'), ('{', '}'), ('(', ')')];
+            if PAIRS.iter().any(|&(open, close)| {
+                (left, right) == (open, close) || (left, right) == (close, open)
+            }) {
+                return Some('|');
+            }
+        }
+        if self.layout & SM_BIGX != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+// A row of a glyph without its end mark: the last character, doubled on the
+// glyph's last row
+fn glyph_row(line: &str, last: bool) -> Vec<char> {
+    let mut row: Vec<char> = line.trim_end_matches(['\r', '\n']).chars().collect();
+    if let Some(endmark) = row.pop() {
+        if last && row.last() == Some(&endmark) {
+            row.pop();
+        }
+    }
+    row
+}
@@ -0,0 +1,436 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::text::{Line, Span};
+
+use super::modal::{ModalOutcome, ModalView};
+use super::theme::Theme;
+
+/// Context in which a key binding is active
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyContext {
+    Main,
+    Details,
+    Edit,
+    Output,
+    Global,
+}
+
+impl KeyContext {
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeyContext::Main => "Main",
+            KeyContext::Details => "Details",
+            KeyContext::Edit => "Edit mode",
+            KeyContext::Output => "Output pane",
+            KeyContext::Global => "Anywhere",
+        }
+    }
+}
+
+/// Order in which contexts are listed in the help overlay
+pub const CONTEXTS: [KeyContext; 5] = [
+    KeyContext::Main,
+    KeyContext::Details,
+    KeyContext::Edit,
+    KeyContext::Output,
+    KeyContext::Global,
+];
+
+/// A documented key binding
+pub struct Binding {
+    pub context: KeyContext,
+    pub keys: &'static str,
+    pub description: &'static str,
+}
+
+/// Every key binding handled by `handle_key`. The help overlay is generated from
+/// it, and a test presses each binding in its context.
+pub const KEYMAP: &[Binding] = &[
+    Binding {
+        context: KeyContext::Main,
+        keys: "Tab",
+        description: "Focus next column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Shift+Tab",
+        description: "Focus previous column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Left/Right",
+        description: "Focus previous/next column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Up/Down",
+        description: "Move selection",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "PgUp/PgDn",
+        description: "Move selection by one page",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Home/End",
+        description: "Jump to first/last action",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Ctrl+Left/Right",
+        description: "Shrink/grow the focused column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "z",
+        description: "Collapse/expand the focused column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "s",
+        description: "Cycle the order of the focused column: config, a-z, last run, most used",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Alt+Up/Down",
+        description: "Move the selected action up/down its column (kept across sessions)",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Space",
+        description: "Mark/unmark the action for a batch run",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "R",
+        description: "Run the marked actions in the background",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "</>",
+        description: "Change the first select parameter (e.g. environment)",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Enter",
+        description: "Open action details",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "r/b",
+        description: "Run the action (in the background with b); asks for missing values first",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "1..9",
+        description: "Focus the nth column",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "Alt+1..9",
+        description: "Open a recently run action",
+    },
+    Binding {
+        context: KeyContext::Main,
+        keys: "hotkey",
+        description: "Run the action configured with that `hotkey`",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "Up/Down",
+        description: "Focus previous/next parameter",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "PgUp/PgDn",
+        description: "Focus a parameter one screen up/down",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "Left/Right",
+        description: "Change select value",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "Enter",
+        description: "Edit text parameter",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "/",
+        description: "Filter parameters by name or description",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "r",
+        description: "Run the command",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "e",
+        description: "Edit the final command (until the view is closed)",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "b",
+        description: "Run in the background, output in the split view",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "D",
+        description: "Dry run: the dry_template of the action, in the background",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "d",
+        description: "Reset the focused parameter to its default",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "p/P",
+        description: "Apply a preset/save the values as a preset",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "x",
+        description: "Export the command as a shell script or an alias",
+    },
+    Binding {
+        context: KeyContext::Details,
+        keys: "Esc",
+        description: "Back to the columns",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Enter",
+        description: "Accept value",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Esc",
+        description: "Cancel and restore value",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Backspace/Delete",
+        description: "Delete the character before/under the cursor",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Left/Right",
+        description: "Move the cursor",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Ctrl+Left/Right",
+        description: "Move the cursor by one word",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Home/End",
+        description: "Move the cursor to the start/end",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Ctrl+U",
+        description: "Clear the value",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Up/Down",
+        description: "Pick a value the parameter was run with; Enter takes it",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Tab/Shift+Tab",
+        description: "Complete a file or directory name, again to cycle",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Ctrl+Z/Ctrl+Y",
+        description: "Undo/redo a change",
+    },
+    Binding {
+        context: KeyContext::Edit,
+        keys: "Ctrl+V",
+        description: "Paste the clipboard at the cursor (also Shift+Insert)",
+    },
+    Binding {
+        context: KeyContext::Output,
+        keys: "y",
+        description: "Copy the output of the job in the output pane",
+    },
+    Binding {
+        context: KeyContext::Output,
+        keys: "S",
+        description: "Save the whole output of the job in the output pane to a file",
+    },
+    Binding {
+        context: KeyContext::Output,
+        keys: "c",
+        description: "Diff the output against the previous run of the same action",
+    },
+    Binding {
+        context: KeyContext::Output,
+        keys: "Z",
+        description: "Zoom a job to the whole output pane while several run, again the next",
+    },
+    Binding {
+        context: KeyContext::Output,
+        keys: "f/F",
+        description: "Filter the output by a pattern (regex, any case) / show the other lines",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "?",
+        description: "Show/hide this help",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "t",
+        description: "Cycle color theme",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "g/G",
+        description: "Next/previous value of the selector in the header",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "o",
+        description: "Toggle the output pane",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "w",
+        description: "Watch: run the action again every watch_secs (2s), w again stops",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "K",
+        description: "Abort every running job, the batch and the watches",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "Ctrl+R",
+        description: "Search the commands run before, to run or fill in again",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "Mouse wheel",
+        description: "Move the selection or the focused parameter",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "F5",
+        description: "Reload the config and run its providers again",
+    },
+    Binding {
+        context: KeyContext::Global,
+        keys: "q",
+        description: "Quit",
+    },
+];
+
+/// Keymap overlay: every binding from `KEYMAP`, grouped by context
+pub struct HelpModal;
+
+impl ModalView for HelpModal {
+    fn title(&self) -> String {
+        "Keys".to_string()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let key_width = KEYMAP
+            .iter()
+            .map(|b| b.keys.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines: Vec<Line> = Vec::new();
+        for context in CONTEXTS {
+            lines.push(Line::from(Span::styled(context.title(), theme.bold())));
+            for binding in KEYMAP.iter().filter(|b| b.context == context) {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:width$}  ", binding.keys, width = key_width),
+                        theme.accent(),
+                    ),
+                    Span::raw(binding.description),
+                ]));
+            }
+            lines.push(Line::from(Span::raw("")));
+        }
+        lines.pop();
+        lines
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => ModalOutcome::Close,
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (60, 80)
+    }
+
+    fn footer(&self) -> &'static str {
+        "Esc or ? to close"
+    }
+}
+
+/// Parse a key written in the config, e.g. "F5", "x", "Ctrl+r" or "Alt+Enter"
+pub fn parse_key(s: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = s.trim();
+    // a lone "+" is a key, not a separator
+    while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+        modifiers |= match prefix.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        rest = key;
+    }
+
+    let mut chars = rest.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some((KeyCode::Char(c), modifiers));
+    }
+    let lower = rest.to_ascii_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return (1..=12).contains(&n).then_some((KeyCode::F(n), modifiers));
+    }
+    let code = match lower.as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pgup" | "pageup" => KeyCode::PageUp,
+        "pgdn" | "pagedown" => KeyCode::PageDown,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ => return None,
+    };
+    Some((code, modifiers))
+}
+
+/// Whether a key event is the key parsed by `parse_key`. Shift is part of the
+/// character itself ("R" vs "r"), so it is ignored for character keys.
+pub fn hotkey_matches((code, modifiers): (KeyCode, KeyModifiers), key: &KeyEvent) -> bool {
+    let mask = match code {
+        KeyCode::Char(_) => !KeyModifiers::SHIFT,
+        _ => KeyModifiers::all(),
+    };
+    key.code == code && key.modifiers & mask == modifiers & mask
+}
@@ -0,0 +1,191 @@
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
+use ratatui::Frame;
+use std::collections::HashMap;
+
+use super::app_state::ActionKey;
+use super::theme::Theme;
+use super::{
+    column_title, draw_scrollbar, ellipsize, mark_prefix, wrap_words, App, MIN_SUMMARY_WIDTH,
+};
+use crate::config::{Action, LabelOverflow};
+
+/// Items of a list column built by earlier frames, by action index. Only the
+/// visible ones are kept, and an item is built again only when what it shows
+/// changed, so long generated columns cost their visible window per frame.
+#[derive(Default)]
+pub struct ItemCache {
+    items: HashMap<usize, CachedItem>,
+}
+
+struct CachedItem {
+    // the item is stale once its action moved or was replaced
+    label: String,
+    inputs: ItemInputs,
+    item: ListItem<'static>,
+    // the label was cut with an ellipsis
+    truncated: bool,
+}
+
+// What an item shows besides its action's label
+#[derive(Clone, PartialEq)]
+struct ItemInputs {
+    width: usize,
+    overflow: LabelOverflow,
+    marked: bool,
+    // running job or outcome of the latest run
+    status: Option<Span<'static>>,
+    summary: Option<String>,
+    summary_style: Style,
+}
+
+/// Column drawn with the default `layout = "list"`. The caller handles empty and
+/// collapsed columns.
+pub fn draw_list(f: &mut Frame, area: Rect, app: &mut App, col_idx: usize, theme: &Theme) {
+    let focused = app.nav.focused_column() == col_idx;
+    // room for the label: borders, highlight symbol and the two leading spaces
+    let width = (area.width as usize).saturating_sub(6).max(1);
+    let viewport = area.height.saturating_sub(2) as usize;
+    let column = &app.nav.columns[col_idx];
+    let count = column.actions.len();
+    let selected = column
+        .list_state
+        .selected()
+        .unwrap_or(0)
+        .min(count.saturating_sub(1));
+    let offset = column.list_state.offset().min(selected);
+
+    // Same scrolling as `List`: keep the previous offset while the selection fits
+    // below it, built backwards from the selection so a jump to the end of a long
+    // column doesn't build everything in between
+    let mut used = item_height(app, col_idx, selected, width, theme);
+    let mut start = selected;
+    while start > offset {
+        let height = item_height(app, col_idx, start - 1, width, theme);
+        if used + height > viewport {
+            break;
+        }
+        used += height;
+        start -= 1;
+    }
+    let mut end = selected + 1;
+    while end < count && used < viewport {
+        used += item_height(app, col_idx, end, width, theme);
+        end += 1;
+    }
+
+    let column = &mut app.nav.columns[col_idx];
+    column.items.items.retain(|i, _| (start..end).contains(i));
+    let items: Vec<ListItem> = (start..end)
+        .filter_map(|i| column.items.items.get(&i))
+        .map(|cached| cached.item.clone())
+        .collect();
+    let truncated = column
+        .items
+        .items
+        .get(&selected)
+        .is_some_and(|cached| cached.truncated);
+    if focused && truncated {
+        app.truncated_label = Some(column.actions[selected].label.clone());
+    }
+
+    let col_title = column_title(&column.title, count, area.width as usize);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(col_title, theme.bold()))
+                .title_alignment(Alignment::Center),
+        )
+        // highlight the selected item; visually stronger when focused
+        .highlight_style(if focused {
+            theme.selected()
+        } else {
+            theme.dim()
+        })
+        .highlight_symbol(if focused { "► " } else { "  " });
+
+    // the list only gets the window; the column's state keeps the real offset
+    let mut window = ListState::default().with_selected(Some(selected - start));
+    f.render_stateful_widget(list, area, &mut window);
+    *column.list_state.offset_mut() = start;
+    draw_scrollbar(f, area, count, viewport, start, theme);
+}
+
+// Rows taken by an item, building it first unless the cached one is current
+fn item_height(app: &mut App, col_idx: usize, i: usize, width: usize, theme: &Theme) -> usize {
+    let key = ActionKey {
+        column: col_idx,
+        action: i,
+    };
+    let status = match app.running_badge(key) {
+        Some(badge) => Some(Span::styled(badge, theme.accent())),
+        None => app.last_run_badge(key, theme),
+    };
+    let inputs = ItemInputs {
+        width,
+        overflow: app.config.app.label_overflow,
+        marked: app.marked.contains(&key),
+        status,
+        summary: app.value_summary(key),
+        summary_style: theme.dim(),
+    };
+    let column = &mut app.nav.columns[col_idx];
+    let cached = match column.items.items.get(&i) {
+        Some(cached) if cached.inputs == inputs && cached.label == column.actions[i].label => {
+            cached
+        }
+        _ => {
+            let built = build_item(&column.actions[i], inputs);
+            column.items.items.insert(i, built);
+            &column.items.items[&i]
+        }
+    };
+    cached.item.height()
+}
+
+fn build_item(action: &Action, inputs: ItemInputs) -> CachedItem {
+    let mark = mark_prefix(inputs.marked);
+    let label_width = match inputs.status {
+        Some(ref s) => inputs.width.saturating_sub(s.width() + 1).max(1),
+        None => inputs.width,
+    };
+    let mut truncated = false;
+    let mut content = match inputs.overflow {
+        LabelOverflow::Ellipsis => {
+            let (label, cut) = ellipsize(&action.label, label_width);
+            truncated = cut;
+            vec![Line::from(Span::raw(format!("{}{}  ", mark, label)))]
+        }
+        LabelOverflow::Wrap => wrap_words(&action.label, label_width)
+            .into_iter()
+            .enumerate()
+            .map(|(line_idx, line)| {
+                // continuation lines are indented under the first one
+                let indent = if line_idx == 0 { mark } else { "    " };
+                Line::from(Span::raw(format!("{}{}  ", indent, line)))
+            })
+            .collect(),
+    };
+    // parameter values in the room left after the label
+    if let (Some(summary), Some(last)) = (&inputs.summary, content.last_mut()) {
+        // the line holds the mark, the label and two spaces
+        let room = (label_width + 2).saturating_sub(last.width());
+        if room >= MIN_SUMMARY_WIDTH {
+            let (summary, _) = ellipsize(summary, room);
+            last.spans
+                .push(Span::styled(format!("{}  ", summary), inputs.summary_style));
+        }
+    }
+    if let (Some(status), Some(first)) = (inputs.status.clone(), content.first_mut()) {
+        first.spans.push(status);
+    }
+    CachedItem {
+        label: action.label.clone(),
+        inputs,
+        item: ListItem::new(content),
+        truncated,
+    }
+}
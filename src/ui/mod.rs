@@ -1,780 +1,3928 @@
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
+use ratatui::backend::Backend;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::{Span, Spans};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
-use ratatui::Terminal;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
+};
+use ratatui::{Frame, Terminal};
 use std::time::Instant;
+mod app_state;
+mod diff;
+mod events;
+mod figlet;
+mod keymap;
+mod list;
+mod modal;
+mod output;
+mod path_completion;
+mod table;
+mod theme;
 mod title;
+mod toast;
+
+#[cfg(test)]
+mod tests;
+use app_state::{ActionKey, ColumnState, DetailsState, EditState, NavigationState, ParamStore};
+use events::{AppEvent, Events};
+pub use keymap::parse_key;
+use keymap::{hotkey_matches, HelpModal};
+use list::draw_list;
+use modal::{
+    ConfirmModal, CountdownModal, HistoryModal, LogModal, MessageModal, Modal, ModalOutcome, Pick,
+    PickItem, PickModal, Prompt, PromptModal, Question, TypedConfirmModal,
+};
+use output::{draw_output, OutputView};
+use serde_json::json;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use title::title_spans;
-
-use crate::config::{Action, Config};
-use crate::runner::run_command;
-
-/// Column state: tracks selection within a column
-pub struct ColumnState {
-    pub title: String,
-    pub actions: Vec<Action>,
-    pub list_state: ListState,
-}
-
-// Helper to build substituted command for action (column index, action index)
-fn build_substituted_command(app: &App, c: usize, a: usize) -> String {
-    let template = app.columns[c].actions[a].template.clone();
-    let mut out = template.clone();
-    for (pidx, param) in app.columns[c].actions[a].parameters.iter().enumerate() {
-        let val = if param.param_type == crate::config::ParameterType::Select {
-            let sel = app.param_selected[c][a][pidx];
-            param
-                .options
-                .get(sel)
-                .map(|o| o.value.clone())
-                .unwrap_or_default()
-        } else {
-            app.param_values[c][a][pidx].clone()
-        };
-        out = out.replace(&param.placeholder, &val);
+use table::draw_table;
+pub use theme::{is_color, parse_color};
+use theme::{theme_index, Theme, THEMES};
+use title::{title_line, title_spans};
+use toast::{ToastLevel, Toasts};
+
+use crate::clipboard;
+use crate::config::{
+    expand_home, Action, BannerMode, BatchMode, Column, ColumnLayout, ColumnSort, Config,
+    Parameter, ParameterOption, ParameterType, Preset, TemplateEngine,
+};
+use crate::control::{self, Request};
+use crate::error::CallbotError;
+use crate::export;
+use crate::jobs::{Job, JobStatus, Jobs};
+use crate::recovery::{Recovery, SavedEdit};
+use crate::runner::{enter_tui, exit_code, run_command, simulate_command, Handover};
+use crate::secrets;
+use crate::session::{SavedAction, SavedColumn, SavedParam, Session};
+use crate::shell_history;
+use crate::sources::{self, options::OptionCache};
+use crate::state::{now_secs, HistoryEntry, State, UiState};
+use crate::status::{self, RunnerStatus};
+use crate::template;
+use crate::transcript::{Entry, Transcript};
+use crate::webhook;
+
+/// What the keyboard currently drives, shown in the status bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Browse,
+    Details,
+    Edit,
+}
+
+impl Mode {
+    fn label(&self) -> &'static str {
+        match self {
+            Mode::Browse => "BROWSE",
+            Mode::Details => "DETAILS",
+            Mode::Edit => "EDIT",
+        }
     }
-    out
 }
 
 pub struct App {
     pub config: Config,
-    pub columns: Vec<ColumnState>,
-    pub focused_column: usize,
-    // when true, the middle area shows the details view for the focused action
-    pub show_details: bool,
-    // Index of focused parameter within the details view when open
-    pub details_focused_param: usize,
-    // text edit mode state when editing a text parameter in the details view
-    pub details_in_edit: bool,
-    pub details_edit_buffer: String,
-    pub details_edit_original: String,
-    // blinking cursor state (toggle on ticks)
-    pub details_cursor_on: bool,
-    // For each column -> action -> parameter (when select), the selected option index
-    // Layout: [column_idx][action_idx][param_idx] => usize (option index or 0)
-    pub param_selected: Vec<Vec<Vec<usize>>>,
-    // Current parameter values (strings) for substitution: [col][action][param]
-    pub param_values: Vec<Vec<Vec<String>>>,
+    // columns and the focused column/action
+    pub nav: NavigationState,
+    // details view of the focused action and the edit in progress
+    pub details: DetailsState,
+    // current parameter values of every action
+    pub params: ParamStore,
+    // popup drawn on top of everything else; captures all keys while open
+    pub modal: Option<Modal>,
+    // index into theme::THEMES, cycled at runtime with `t`
+    pub theme: usize,
+    // full label of the focused action when it was ellipsized in its column
+    pub truncated_label: Option<String>,
+    // transient notifications shown in the corner of the middle area
+    pub toasts: Toasts,
+    // --simulate: show fake output instead of running anything
+    pub simulate: bool,
+    // --follow: read-only viewer of the jobs mirrored in `follow_dir`
+    pub follow: bool,
+    // checkpoint left by a crashed instance, until its restore prompt is answered
+    pub recovery: Option<Recovery>,
+    // this instance's last checkpoint, and when it was taken
+    checkpoint: Option<Recovery>,
+    checkpoint_at: Instant,
+    // commands run in the background and their output
+    pub jobs: Jobs,
+    // action each background job was started from, by job id
+    pub job_actions: HashMap<usize, ActionKey>,
+    // actions marked with Space for a batch run
+    pub marked: HashSet<ActionKey>,
+    // actions of a sequential batch still waiting for the previous one to finish
+    pub batch_queue: VecDeque<ActionKey>,
+    // job of the sequential batch currently running
+    pub batch_job: Option<usize>,
+    // when true, the middle area is split with the output pane on the right
+    pub split_view: bool,
+    // diff and filter of the output pane
+    output: OutputView,
+    // option of the global selector (`[selector]`) chosen with g/G
+    pub selector: usize,
+    // where runs are recorded with `--record`
+    pub transcript: Option<Transcript>,
+    // run waiting for the `delay_secs` of its action to pass
+    countdown: Option<Countdown>,
+    // run waiting for its confirmation phrase to be typed, in the background or not
+    typed_run: Option<(ActionKey, bool)>,
+    // kubectl answers reused by the option sources
+    option_cache: OptionCache,
+    // orders chosen with s, by column id
+    sort_overrides: HashMap<String, ColumnSort>,
+    // actions re-run on an interval
+    watches: Vec<Watch>,
+    // secrets read from their manager this session, by reference
+    secret_cache: HashMap<String, String>,
+    // persisted cross-session state (recently run actions)
+    pub state: State,
+    // name of the workspace given with --session, saved on quit
+    pub session: Option<String>,
+    // config file, reloaded when it changes on disk
+    pub config_path: Option<PathBuf>,
+    // the last frame was too small for the layout and only showed a placeholder
+    pub too_small: bool,
+    // size of the last frame, for paging
+    pub size: Rect,
 }
 
 impl App {
-    pub fn new(config: Config) -> Self {
-        let columns: Vec<ColumnState> = config
-            .columns
-            .iter()
-            .map(|col| {
-                let mut ls = ListState::default();
-                if col.actions.is_empty() {
-                    ls.select(None);
-                } else {
-                    ls.select(Some(0));
-                }
-                ColumnState {
-                    title: col.title.clone(),
-                    actions: col.actions.clone(),
-                    list_state: ls,
-                }
+    pub fn new(mut config: Config, state: State) -> Self {
+        sort_actions(&mut config.columns, &state);
+        let mut app = Self {
+            nav: NavigationState::new(&config.columns),
+            details: DetailsState::default(),
+            params: ParamStore::new(&config.columns),
+            modal: None,
+            theme: theme_index(&config.app.theme),
+            truncated_label: None,
+            toasts: Toasts::default(),
+            simulate: false,
+            follow: false,
+            recovery: None,
+            checkpoint: None,
+            checkpoint_at: Instant::now(),
+            jobs: Jobs::with_spool(config.app.follow_dir.as_deref().map(expand_home)),
+            job_actions: HashMap::new(),
+            marked: HashSet::new(),
+            batch_queue: VecDeque::new(),
+            batch_job: None,
+            split_view: false,
+            output: OutputView::default(),
+            state,
+            session: None,
+            config_path: None,
+            too_small: false,
+            size: Rect::default(),
+            selector: 0,
+            transcript: None,
+            countdown: None,
+            typed_run: None,
+            option_cache: OptionCache::default(),
+            sort_overrides: HashMap::new(),
+            watches: Vec::new(),
+            secret_cache: HashMap::new(),
+            config,
+        };
+        app.selector = app
+            .config
+            .selector
+            .as_ref()
+            .and_then(|param| {
+                let default = param.default.as_ref()?;
+                param.options.iter().position(|o| &o.value == default)
             })
-            .collect();
+            .unwrap_or(0);
+        app.sync_selector();
+        app
+    }
 
-        Self {
-            config: config.clone(),
-            columns,
-            focused_column: 0,
-            show_details: false,
-            details_focused_param: 0,
-            details_in_edit: false,
-            details_edit_buffer: String::new(),
-            details_edit_original: String::new(),
-            details_cursor_on: true,
-            // initialize param_selected to match config structure
-            // for select parameters, prefer the parameter.default value when present
-            param_selected: config
-                .columns
-                .iter()
-                .map(|col| {
-                    col.actions
-                        .iter()
-                        .map(|act| {
-                            act.parameters
-                                .iter()
-                                .map(|p| {
-                                    if p.param_type == crate::config::ParameterType::Select {
-                                        if let Some(ref def) = p.default {
-                                            // find index of option whose value matches default
-                                            p.options
-                                                .iter()
-                                                .position(|o| &o.value == def)
-                                                .unwrap_or(0)
-                                        } else {
-                                            0usize
-                                        }
-                                    } else {
-                                        0usize
-                                    }
-                                })
-                                .collect()
-                        })
-                        .collect()
-                })
-                .collect(),
-            // initialize parameter values: for selects prefer parameter.default -> matching option value; else first option.
-            param_values: config
-                .columns
-                .iter()
-                .map(|col| {
-                    col.actions
-                        .iter()
-                        .map(|act| {
-                            act.parameters
-                                .iter()
-                                .enumerate()
-                                .map(|(_pidx, p)| {
-                                    if p.param_type == crate::config::ParameterType::Select {
-                                        if let Some(ref def) = p.default {
-                                            p.options
-                                                .iter()
-                                                .find(|o| &o.value == def)
-                                                .map(|o| o.value.clone())
-                                                .or_else(|| {
-                                                    p.options.get(0).map(|o| o.value.clone())
-                                                })
-                                                .unwrap_or_default()
-                                        } else {
-                                            p.options
-                                                .get(0)
-                                                .map(|o| o.value.clone())
-                                                .unwrap_or_default()
-                                        }
-                                    } else {
-                                        p.default.clone().unwrap_or_default()
-                                    }
-                                })
-                                .collect()
-                        })
-                        .collect()
-                })
-                .collect(),
-        }
+    /// Command line of an action with its current parameter values substituted
+    fn command_for(&self, key: ActionKey) -> String {
+        self.nav
+            .action(key)
+            .map(|action| self.params.substitute(key, action))
+            .unwrap_or_default()
+    }
+
+    /// `command_for` with the secret values masked where they are substituted, for
+    /// what is shown, logged or saved
+    fn masked_command_for(&self, key: ActionKey) -> String {
+        self.nav
+            .action(key)
+            .map(|action| self.masked_command(key, action))
+            .unwrap_or_default()
+    }
+
+    // The command of `action` (or a variant of it, e.g. its dry run) with the values
+    // of its secret parameters masked
+    fn masked_command(&self, key: ActionKey, action: &Action) -> String {
+        self.stand_in_secrets(key, action, &secrets::hides, &|_, _| {
+            secrets::MASK.to_string()
+        })
     }
 
-    fn move_up(&mut self) {
-        if let Some(col) = self.columns.get_mut(self.focused_column) {
-            if let Some(curr) = col.list_state.selected() {
-                if curr > 0 {
-                    let new = curr - 1;
-                    col.list_state.select(Some(new));
+    // The command of `action` with the values of the secret parameters selected by
+    // `secret` (parameter, value) given by `stand_in` (parameter, placeholder as
+    // written). Values are substituted and filtered as they are, so the optional
+    // groups kept are those of the real command; the stand-in takes the filtered
+    // value's place. Handlebars templates have the values in their text, where they
+    // are replaced as written.
+    fn stand_in_secrets(
+        &self,
+        key: ActionKey,
+        action: &Action,
+        secret: &dyn Fn(&Parameter, &str) -> bool,
+        stand_in: &dyn Fn(&Parameter, &str) -> String,
+    ) -> String {
+        let secrets: Vec<(usize, &Parameter)> = action
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(pidx, p)| secret(p, self.params.value(key, *pidx)))
+            .collect();
+        let mut command = String::new();
+        for segment in self.params.segments(key, action) {
+            match segment
+                .param
+                .and_then(|i| secrets.iter().find(|(pidx, _)| *pidx == i))
+            {
+                Some(_) if segment.text.is_empty() => {}
+                Some((_, param)) => command.push_str(&stand_in(param, &segment.placeholder)),
+                None if action.engine == TemplateEngine::Handlebars => {
+                    let text = secrets.iter().fold(segment.text, |text, (pidx, param)| {
+                        text.replace(
+                            self.params.value(key, *pidx),
+                            &stand_in(param, &param.placeholder),
+                        )
+                    });
+                    command.push_str(&text);
                 }
+                None => command.push_str(&segment.text),
             }
         }
+        command
+    }
+
+    // Hand-edited command from the details view, when set for `key`. Secret values
+    // are never in it: their placeholders stand in for them, see `editable_command`.
+    fn command_override(&self, key: ActionKey) -> Option<&String> {
+        self.details
+            .command_override
+            .as_ref()
+            .filter(|_| self.details.open && self.nav.focused_key() == Some(key))
+    }
+
+    /// Command that runs for an action: the hand-edited one from the details view when
+    /// set for the focused action, else the substituted template
+    fn final_command(&self, key: ActionKey) -> String {
+        let values = self.values(key);
+        self.final_command_with(key, &values)
+    }
+
+    // `final_command` with the parameter values given by index, e.g. the secrets
+    // their references stand for
+    fn final_command_with(&self, key: ActionKey, values: &[String]) -> String {
+        let Some(action) = self.nav.action(key) else {
+            return String::new();
+        };
+        match self.command_override(key) {
+            Some(command) => self.fill_secrets(key, command, values, false),
+            None => self
+                .params
+                .substitute_with(action, &|pidx| values[pidx].clone()),
+        }
+    }
+
+    // Current values of the parameters of an action, by index
+    fn values(&self, key: ActionKey) -> Vec<String> {
+        let count = self.nav.action(key).map_or(0, |a| a.parameters.len());
+        (0..count)
+            .map(|pidx| self.params.value(key, pidx).to_string())
+            .collect()
+    }
+
+    /// `final_command` as shown, logged or saved: with its secret values masked
+    fn shown_command(&self, key: ActionKey) -> String {
+        match self.command_override(key) {
+            Some(command) => self.fill_secrets(key, command, &self.values(key), true),
+            None => self.masked_command_for(key),
+        }
+    }
+
+    /// Starting point of a hand edit of the command: the current one, with the
+    /// placeholders of the secret parameters (filters included) left in place of
+    /// their values
+    fn editable_command(&self, key: ActionKey) -> String {
+        if let Some(command) = self.command_override(key) {
+            return command.clone();
+        }
+        let Some(action) = self.nav.action(key) else {
+            return String::new();
+        };
+        self.stand_in_secrets(
+            key,
+            action,
+            &|param, value| param.is_secret() && !value.is_empty(),
+            &|_, placeholder| placeholder.to_string(),
+        )
     }
 
-    fn move_down(&mut self) {
-        if let Some(col) = self.columns.get_mut(self.focused_column) {
-            if let Some(curr) = col.list_state.selected() {
-                if curr + 1 < col.actions.len() {
-                    let new = curr + 1;
-                    col.list_state.select(Some(new));
+    // Put the secret `values` of an action in a hand-edited command, or their mask:
+    // each placeholder left by `editable_command` becomes the value with its filters
+    // applied, as the template would have it
+    fn fill_secrets(
+        &self,
+        key: ActionKey,
+        command: &str,
+        values: &[String],
+        masked: bool,
+    ) -> String {
+        let Some(action) = self.nav.action(key) else {
+            return command.to_string();
+        };
+        action
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.is_secret() && !p.placeholder.is_empty())
+            .fold(command.to_string(), |command, (pidx, p)| {
+                let mut filled = String::new();
+                let mut rest = command.as_str();
+                while let Some(found) = template::find(rest, &p.placeholder) {
+                    filled.push_str(&rest[..found.at]);
+                    if masked && secrets::hides(p, self.params.value(key, pidx)) {
+                        filled.push_str(secrets::MASK);
+                    } else if let Some(ref format) = p.repeat_format {
+                        filled.push_str(&template::repeat(format, &values[pidx], &found.filters));
+                    } else {
+                        filled.push_str(&template::apply(&values[pidx], &found.filters));
+                    }
+                    rest = &rest[found.at + found.len..];
                 }
-            }
+                filled.push_str(rest);
+                filled
+            })
+    }
+
+    /// Whether an action has secret values that would be written in clear
+    fn has_hidden_values(&self, key: ActionKey) -> bool {
+        self.nav.action(key).is_some_and(|action| {
+            action
+                .parameters
+                .iter()
+                .enumerate()
+                .any(|(pidx, p)| secrets::hides(p, self.params.value(key, pidx)))
+        })
+    }
+
+    /// Values of the parameters of an action to run it with: the secret references
+    /// replaced by the secrets, read once per session. They go through the template
+    /// like any value, filters included. None (with an error toast) when a secret
+    /// can't be read.
+    fn run_values(&mut self, key: ActionKey) -> Option<Vec<String>> {
+        let references: Vec<(usize, String)> = self
+            .nav
+            .action(key)
+            .map(|action| {
+                action
+                    .parameters
+                    .iter()
+                    .enumerate()
+                    .filter(|(pidx, p)| {
+                        p.is_secret() && secrets::is_reference(self.params.value(key, *pidx))
+                    })
+                    .map(|(pidx, p)| (pidx, p.name.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut values = self.values(key);
+        for (pidx, name) in references {
+            let reference = values[pidx].clone();
+            values[pidx] = match self.secret_cache.get(&reference) {
+                Some(secret) => secret.clone(),
+                None => match secrets::resolve(&reference) {
+                    Ok(secret) => {
+                        self.secret_cache.insert(reference, secret.clone());
+                        secret
+                    }
+                    Err(err) => {
+                        self.toasts
+                            .error(format!("Could not read {}: {}", name, err));
+                        return None;
+                    }
+                },
+            };
         }
+        Some(values)
+    }
+
+    /// `final_command` with the secrets of its references, to run it
+    fn run_command(&mut self, key: ActionKey) -> Option<String> {
+        let values = self.run_values(key)?;
+        Some(self.final_command_with(key, &values))
     }
 
-    // removed unused focused_selection
+    /// Preview rows of the focused action's command in a terminal `width` columns wide.
+    /// Parameter values are colored per parameter and unset placeholders flagged; a
+    /// hand-edited command (or the edit buffer with its cursor) is shown as is.
+    fn preview(&self, width: u16, theme: &Theme) -> Vec<Line<'static>> {
+        let segments: Vec<(String, Style)> = if let Some(ref edit) = self.details.command_edit {
+            edit_spans(edit, self.details.cursor_on, theme.text())
+                .into_iter()
+                .map(|span| (span.content.into_owned(), span.style))
+                .collect()
+        } else if let Some(key) = self
+            .nav
+            .focused_key()
+            .filter(|&key| self.command_override(key).is_some())
+        {
+            vec![(self.shown_command(key), theme.text())]
+        } else if let (Some(key), Some(action)) =
+            (self.nav.focused_key(), self.nav.focused_action())
+        {
+            self.params
+                .segments(key, action)
+                .into_iter()
+                .map(|segment| {
+                    match segment
+                        .param
+                        .and_then(|i| Some((i, action.parameters.get(i)?)))
+                    {
+                        None => (segment.text, theme.text()),
+                        Some((pidx, param)) if !segment.text.is_empty() => {
+                            let text = if secrets::hides(param, self.params.value(key, pidx)) {
+                                secrets::MASK.to_string()
+                            } else {
+                                segment.text
+                            };
+                            (text, theme.param(pidx))
+                        }
+                        // unset: show the placeholder, loudly when the parameter is required
+                        Some((_, param)) if param.required => (
+                            param.placeholder.clone(),
+                            theme.failure().add_modifier(Modifier::UNDERLINED),
+                        ),
+                        Some((_, param)) => (param.placeholder.clone(), theme.faint()),
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        preview_lines(&segments, preview_width(width))
+    }
 
-    fn focused_action(&self) -> Option<&Action> {
-        self.columns
-            .get(self.focused_column)
-            .and_then(|col| col.list_state.selected().and_then(|i| col.actions.get(i)))
+    /// The parameter focused in the details view, with the key of its action
+    fn focused_param(&self) -> Option<(ActionKey, &Parameter)> {
+        let key = self.nav.focused_key()?;
+        let param = self
+            .nav
+            .action(key)?
+            .parameters
+            .get(self.details.focused_param)?;
+        Some((key, param))
     }
 
-    fn focused_action_index(&self) -> Option<(usize, usize)> {
-        if let Some(col) = self.columns.get(self.focused_column) {
-            if let Some(act_idx) = col.list_state.selected() {
-                return Some((self.focused_column, act_idx));
-            }
+    fn mode(&self) -> Mode {
+        if self.details.is_editing()
+            || self.details.command_edit.is_some()
+            || self.details.filter_edit.is_some()
+            || self.output.filter_edit.is_some()
+        {
+            Mode::Edit
+        } else if self.details.open {
+            Mode::Details
+        } else {
+            Mode::Browse
         }
-        None
     }
 
-    fn column_count(&self) -> usize {
-        self.columns.len()
+    fn theme(&self) -> &'static Theme {
+        &THEMES[self.theme]
     }
-}
 
-pub fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-    mut app: App,
-) -> io::Result<()> {
-    let tick_rate = Duration::from_millis(500);
-    let mut last_tick = Instant::now();
+    // Banner color and optional gradient end; configured colors override the theme's
+    // and reduced motion keeps the banner a single color
+    fn banner_colors(&self) -> (Color, Option<Color>) {
+        let app = &self.config.app;
+        let color = app
+            .banner_color
+            .as_deref()
+            .and_then(parse_color)
+            .unwrap_or(self.theme().banner);
+        let gradient_to = app
+            .banner_gradient
+            .as_deref()
+            .filter(|_| !app.reduced_motion)
+            .and_then(parse_color);
+        (color, gradient_to)
+    }
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-
-            // Obtain the title lines (figlet or fallback) so we can size the top (header) chunk
-            let title_lines = title_spans(&app.config.app.title);
-            // reserve one extra row for the subtitle we append below
-            let title_height = (title_lines.len() as u16).saturating_add(1).max(3);
-
-            // Layout: header (title + subtitle), middle (columns or details), footer (preview + help)
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints(
-                    [
-                        Constraint::Length(title_height),
-                        Constraint::Min(10),
-                        Constraint::Length(6),
-                    ]
-                    .as_ref(),
-                )
-                .split(size);
-
-            // Build header content: figlet lines, subtitle and a blank line below
-            let mut title_body: Vec<Spans> = Vec::new();
-            title_body.extend(title_lines.clone());
-            // subtitle from config
-            title_body.push(Spans::from(Span::styled(
-                app.config.app.subtitle.clone(),
-                Style::default().fg(Color::Rgb(150, 150, 150)),
-            )));
-            // one empty row below subtitle
-            title_body.push(Spans::from(Span::raw("")));
-
-            let header = Paragraph::new(title_body).alignment(Alignment::Center);
-            f.render_widget(header, chunks[0]);
-
-            // Middle area: either the columns or a details view depending on state
-            if !app.show_details {
-                // Columns layout - dynamic based on config
-                let num_columns = app.column_count();
-                let column_constraints: Vec<Constraint> = (0..num_columns)
-                    .map(|_| Constraint::Ratio(1, num_columns as u32))
-                    .collect();
+    fn cycle_theme(&mut self) {
+        self.theme = (self.theme + 1) % THEMES.len();
+        self.toasts.info(format!("Theme: {}", self.theme().name));
+    }
 
-                let middle_chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints(column_constraints)
-                    .split(chunks[1]);
+    /// Chosen option of the global selector, if the config has one
+    fn selector_option(&self) -> Option<(&Parameter, &ParameterOption)> {
+        let param = self.config.selector.as_ref()?;
+        Some((param, param.options.get(self.selector)?))
+    }
 
-                // Render each column dynamically
-                for col_idx in 0..app.columns.len() {
-                    // snapshot small bits so we don't keep immutable borrows while taking a
-                    // mutable borrow for the ListState below
-                    let actions = app.columns[col_idx].actions.clone();
-                    let title_text = app.columns[col_idx].title.clone();
-                    let focused = app.focused_column == col_idx;
+    // Hand the chosen selector value to the templates
+    fn sync_selector(&mut self) {
+        if let Some((param, option)) = self.selector_option() {
+            let (param, value) = (param.clone(), option.value.clone());
+            self.params.set_selector(&param, &value);
+        }
+    }
 
-                    let items: Vec<ListItem> = actions
-                        .iter()
-                        .enumerate()
-                        .map(|(_i, action)| {
-                            let content = vec![Spans::from(Span::raw(format!("  {}  ", action.label)))];
-                            ListItem::new(content)
-                        })
-                        .collect();
-
-                    let col_title = {
-                        let inner = middle_chunks[col_idx].width as usize;
-                        let core = &title_text;
-                        if inner > core.len() + 2 {
-                            format!(" {} ", core)
-                        } else {
-                            core.clone()
-                        }
-                    };
-
-                    let mut list = List::new(items)
-                        .block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .title(Span::styled(
-                                    col_title,
-                                    Style::default().add_modifier(Modifier::BOLD),
-                                ))
-                                .title_alignment(Alignment::Center),
-                        )
-                        // highlight the selected item; visually stronger when focused
-                        .highlight_style(if focused {
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default().fg(Color::Rgb(150, 150, 150))
-                        });
+    /// Choose the next (`delta` 1) or previous (-1) option of the global selector,
+    /// wrapping around
+    fn cycle_selector(&mut self, delta: isize) {
+        let Some(count) = self.config.selector.as_ref().map(|p| p.options.len()) else {
+            return;
+        };
+        self.selector = (self.selector as isize + delta).rem_euclid(count as isize) as usize;
+        self.sync_selector();
+        if let Some((param, option)) = self.selector_option() {
+            let message = format!("{}: {}", param.name, option.label);
+            self.toasts.info(message);
+        }
+    }
 
-                    if focused {
-                        list = list.highlight_symbol("► ");
-                    } else {
-                        list = list.highlight_symbol("  ");
-                    }
+    /// Recently run actions that still exist in the config
+    fn recent_actions(&self) -> Vec<ActionKey> {
+        self.state
+            .recent
+            .iter()
+            .filter_map(|r| {
+                let column = self
+                    .config
+                    .columns
+                    .iter()
+                    .position(|col| col.id == r.column)?;
+                let action = self
+                    .nav
+                    .column(column)?
+                    .actions
+                    .iter()
+                    .position(|act| act.label == r.label)?;
+                Some(ActionKey { column, action })
+            })
+            .take(self.config.app.recent_limit)
+            .collect()
+    }
+
+    /// Focus the nth recent action and open its details view
+    fn open_recent(&mut self, n: usize) {
+        if let Some(&key) = self.recent_actions().get(n) {
+            self.nav.select(key);
+            self.open_details();
+        }
+    }
+
+    /// Open the details view of the focused action, with the options of its
+    /// selects that have a source queried again
+    fn open_details(&mut self) {
+        self.details.open();
+        if let Some(key) = self.nav.focused_key() {
+            self.refresh_options(key, None);
+        }
+    }
 
-                    // render statefully so the List will scroll to keep the selected item visible
-                    f.render_stateful_widget(
-                        list,
-                        middle_chunks[col_idx],
-                        &mut app.columns[col_idx].list_state,
-                    );
+    /// Query the options of the selects of an action that have a source, or with
+    /// `changed` only those whose kube context or namespace refer to that parameter.
+    /// A value still offered stays selected, others go back to the default.
+    fn refresh_options(&mut self, key: ActionKey, changed: Option<usize>) {
+        let Some(action) = self.nav.action(key) else {
+            return;
+        };
+        let changed = changed.and_then(|pidx| action.parameters.get(pidx));
+        let refers = |text: &Option<String>, param: &Parameter| {
+            text.as_ref()
+                .is_some_and(|t| t.contains(&param.placeholder))
+        };
+        let mut sourced = Vec::new();
+        for (pidx, param) in action.parameters.iter().enumerate() {
+            let Some(source) = param.source else {
+                continue;
+            };
+            if changed
+                .is_some_and(|c| !refers(&param.kube_context, c) && !refers(&param.namespace, c))
+            {
+                continue;
+            }
+            // the values of the parameters the query depends on
+            let mut query = param.clone();
+            for (other, p) in action.parameters.iter().enumerate() {
+                let value = self.params.value(key, other);
+                for text in [&mut query.kube_context, &mut query.namespace]
+                    .into_iter()
+                    .flatten()
+                {
+                    *text = text.replace(&p.placeholder, value);
                 }
+            }
+            sourced.push((pidx, source, query));
+        }
+        for (pidx, source, param) in sourced {
+            let options = match sources::options::load(source, &param, &mut self.option_cache) {
+                Ok(options) => options,
+                Err(err) => {
+                    tracing::warn!(param = %param.name, error = %err, "option source failed");
+                    self.toasts.error(format!(
+                        "Could not list the options of {}: {}",
+                        param.name, err
+                    ));
+                    continue;
+                }
+            };
+            let Some(param) = self
+                .nav
+                .action_mut(key)
+                .and_then(|a| a.parameters.get_mut(pidx))
+            else {
+                continue;
+            };
+            param.options = options;
+            let value = self.params.value(key, pidx).to_string();
+            if param.options.iter().any(|o| o.value == value) {
+                self.params.restore(key, pidx, param, &value);
             } else {
-                // Details view replaces the columns in the middle area while keeping header/footer
-                let area = chunks[1];
-
-                // Use the action label as the window title when available. Add a leading
-                // and trailing space for visual padding.
-                let title_text = app
-                    .focused_action()
-                    .map(|a| format!(" {} ", a.label))
-                    .unwrap_or_else(|| " Details ".to_string());
-
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .title(Span::styled(title_text.as_str(), Style::default().add_modifier(Modifier::BOLD)));
-                f.render_widget(block, area);
-
-                let inner = Rect {
-                    x: area.x + 1,
-                    y: area.y + 1,
-                    width: area.width.saturating_sub(2),
-                    height: area.height.saturating_sub(2),
-                };
+                self.params.reset(key, pidx, param);
+            }
+        }
+    }
 
-                // Build detailed content from the focused action (parameters only)
-                let mut lines: Vec<Spans> = Vec::new();
+    // Write the state file, a failure is only reported
+    fn save_state(&mut self) {
+        if let Err(err) = self.state.save() {
+            self.toasts.error(format!("Could not save state: {}", err));
+        }
+    }
 
-                if let Some(action) = app.focused_action() {
-                    if !action.parameters.is_empty() {
-                        lines.push(Spans::from(Span::styled(
-                            "Parameters:",
-                            Style::default().add_modifier(Modifier::BOLD),
-                        )));
+    /// Copy the output of the job shown in the output pane to the system clipboard
+    fn copy_output(&mut self) {
+        let Some(job) = self.output.job(&self.jobs) else {
+            self.toasts.info("No output to copy");
+            return;
+        };
+        let lines = job.output.len();
+        match clipboard::set_contents(job.output.join("\n")) {
+            Ok(()) => self.toasts.info(format!(
+                "Copied {} line{} of {}",
+                lines,
+                if lines == 1 { "" } else { "s" },
+                job.label
+            )),
+            Err(err) => self
+                .toasts
+                .error(format!("Could not write the clipboard: {}", err)),
+        }
+    }
 
-                for (idx, param) in action.parameters.iter().enumerate() {
-                            let required_marker = if param.required { " *" } else { "" };
+    /// Run an action, once its phrase is typed if it has `confirm = "typed"`
+    fn launch(&mut self, key: ActionKey, background: bool) -> Step {
+        let Some(action) = self.nav.action(key) else {
+            return Step::Redraw;
+        };
+        let Some(phrase) = action.typed_confirmation() else {
+            return self.launch_confirmed(key, background);
+        };
+        let (label, phrase) = (action.label.clone(), phrase.to_string());
+        self.modal = Some(Modal::new(TypedConfirmModal {
+            label,
+            command: self.shown_command(key),
+            phrase,
+            input: EditState::new(String::new()),
+        }));
+        self.typed_run = Some((key, background));
+        Step::Redraw
+    }
 
-                            // Parameter header line; omit type suffix for selects
-                            let mut spans = vec![Span::raw("  "), Span::styled(&param.name, Style::default().fg(Color::Yellow))];
-                            if param.param_type == crate::config::ParameterType::Select {
-                                spans.push(Span::raw(format!("{}  ", required_marker)));
-                            } else {
-                                spans.push(Span::raw(format!(" {}  ", required_marker)));
-                            }
+    /// Run an action in the foreground or the background (always for actions set to
+    /// `background`), after the countdown of its `delay_secs` if it has one
+    fn launch_confirmed(&mut self, key: ActionKey, background: bool) -> Step {
+        let watch_secs = self.nav.action(key).map_or(0, |a| a.watch_secs);
+        if watch_secs > 0 {
+            if !self.is_watched(key) {
+                self.start_watch(key, Duration::from_secs(watch_secs));
+            }
+            return Step::Redraw;
+        }
+        let background = background || self.nav.action(key).is_some_and(|a| a.background);
+        let delay = self.nav.action(key).map_or(0, |a| a.delay_secs);
+        if delay > 0 && !self.simulate {
+            let fires_at = Instant::now() + Duration::from_secs(delay);
+            self.countdown = Some(Countdown {
+                key,
+                background,
+                fires_at,
+            });
+            let label = self.nav.action(key).map(|a| a.label.clone());
+            self.modal = Some(Modal::new(CountdownModal {
+                label: label.unwrap_or_default(),
+                command: self.shown_command(key),
+                fires_at,
+            }));
+            return Step::Redraw;
+        }
+        if background {
+            self.start_job(key);
+            return Step::Redraw;
+        }
+        Step::Run(key)
+    }
 
-                            // If select, render options inline with highlight for selected
-                            if param.param_type == crate::config::ParameterType::Select {
-                                if let Some((c, a)) = app.focused_action_index() {
-                                    let sel = app.param_selected[c][a][idx];
-                                    // Render options on a separate line under the parameter
-                                    lines.push(Spans::from(vec![Span::raw("    ")]));
-                                    let mut opt_spans: Vec<Span> = Vec::new();
-                                    for (oi, opt) in param.options.iter().enumerate() {
-                                        // color mapping for environment-like options
-                                        let styled = match opt.value.as_str() {
-                                            "qlf" => Style::default().fg(Color::Green),
-                                            "pprod" | "pprod_legacy" => Style::default().fg(Color::Rgb(255, 165, 0)),
-                                            v if v.starts_with("prod") => Style::default().fg(Color::Red),
-                                            _ => Style::default(),
-                                        };
-
-                                        if oi == sel {
-                                            // selected: bold + distinct fg
-                                            opt_spans.push(Span::styled(format!("[{}] ", opt.label), styled.add_modifier(Modifier::BOLD)));
-                                        } else {
-                                            opt_spans.push(Span::styled(format!(" {}  ", opt.label), styled));
-                                        }
-                                    }
-                                    lines.push(Spans::from(opt_spans));
-                                }
-                            } else {
-                                // for text params, show current value; when editing show the edit buffer
-                                if let Some((c, a)) = app.focused_action_index() {
-                                    let val = app.param_values[c][a][idx].clone();
-                                    if app.details_in_edit && idx == app.details_focused_param {
-                                        // show the live edit buffer with a blinking cursor
-                                        let buf = app.details_edit_buffer.clone();
-                                        spans.push(Span::raw(": "));
-                                        let cursor = if app.details_cursor_on { "_" } else { " " };
-                                        spans.push(Span::styled(
-                                            format!("{}{}", buf, cursor),
-                                            Style::default().add_modifier(Modifier::BOLD),
-                                        ));
-                                        spans.push(Span::styled(
-                                            " (editing)",
-                                            Style::default().fg(Color::Rgb(150, 150, 150)).add_modifier(Modifier::ITALIC),
-                                        ));
-                                    } else {
-                                        spans.push(Span::raw(format!(": {}", val)));
-                                    }
-                                }
-                            }
+    // Run the action of a countdown that ran out
+    fn fire_countdown(&mut self) -> Option<Step> {
+        let countdown = self.countdown.take_if(|c| c.fires_at <= Instant::now())?;
+        self.modal = None;
+        if countdown.background {
+            self.start_job(countdown.key);
+            return Some(Step::Redraw);
+        }
+        Some(Step::Run(countdown.key))
+    }
 
-                            // indicate focus with a pointer glyph on the start of the line
-                            if idx == app.details_focused_param {
-                                let pointer_style = if app.details_in_edit { Style::default().fg(Color::Yellow).bg(Color::Rgb(40,40,40)) } else { Style::default().fg(Color::Yellow) };
-                                let mut row = vec![Span::styled("➜ ", pointer_style)];
-                                row.extend(spans);
-                                lines.push(Spans::from(row));
-                            } else {
-                                lines.push(Spans::from(spans));
-                            }
+    /// Count a finished run in the action's statistics; saved with its exit code
+    fn record_stats(&mut self, key: ActionKey, code: i32, duration: Duration) {
+        let (column, label) = self.action_id(key);
+        self.state.record_stats(&column, &label, code, duration);
+    }
 
-                            if let Some(ref desc) = param.description {
-                                lines.push(Spans::from(vec![
-                                    Span::raw("    "),
-                                    Span::styled(desc, Style::default().fg(Color::Rgb(150, 150, 150))),
-                                ]));
-                            }
-                        }
-                    } else {
-                        lines.push(Spans::from(Span::raw("No parameters")));
-                    }
-                } else {
-                    lines.push(Spans::from(Span::raw("No action selected")));
-                }
+    /// Persist the exit code of the last run
+    fn record_exit(&mut self, code: i32) {
+        self.state.record_exit(code);
+        self.save_state();
+    }
+
+    /// Persist the exit code of an action run in the background
+    fn record_exit_of(&mut self, key: ActionKey, code: i32) {
+        let (Some(column), Some(action)) =
+            (self.config.columns.get(key.column), self.nav.action(key))
+        else {
+            return;
+        };
+        let (column_id, label) = (column.id.clone(), action.label.clone());
+        self.state.record_exit_of(&column_id, &label, code);
+        self.save_state();
+    }
+
+    /// Remember the action as recently run and persist it
+    fn record_run(&mut self, key: ActionKey) {
+        let (Some(column), Some(action)) =
+            (self.config.columns.get(key.column), self.nav.action(key))
+        else {
+            return;
+        };
+        let (column_id, label) = (column.id.clone(), action.label.clone());
+        // text values offered back while editing a parameter of the same name
+        let values: Vec<(String, String)> = action
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.param_type == ParameterType::Text && !p.is_secret())
+            .map(|(pidx, p)| (p.name.clone(), self.params.value(key, pidx).to_string()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        let history = HistoryEntry {
+            column: column_id.clone(),
+            label: label.clone(),
+            command: self.shown_command(key),
+            values: action
+                .parameters
+                .iter()
+                .enumerate()
+                .map(|(pidx, p)| (p, self.params.value(key, pidx)))
+                .filter(|(p, value)| !secrets::hides(p, value))
+                .map(|(p, value)| (p.name.clone(), value.to_string()))
+                .collect(),
+            timestamp: now_secs(),
+            edited: self.command_override(key).cloned(),
+        };
+        self.state
+            .record_run(&column_id, &label, self.config.app.recent_limit);
+        self.state.record_command(history);
+        for (name, value) in values {
+            self.state.record_value(&name, &value);
+        }
+        self.save_state();
+    }
 
-                lines.push(Spans::from(Span::raw("")));
-                lines.push(Spans::from(Span::styled(
-                    " Press r to run or Esc to return to the main page ",
-                    Style::default().fg(Color::Rgb(100, 100, 100)),
-                )));
+    /// Run an action in the background and show its output in the split view
+    fn start_job(&mut self, key: ActionKey) -> Option<usize> {
+        let action = self.nav.action(key)?;
+        let label = action.label.clone();
+        let simulation = action.simulate.clone();
+        self.split_view = true;
+
+        if self.simulate {
+            let cmd = self.final_command(key);
+            let (output, code) = simulate_command(&cmd, simulation.as_ref());
+            let shown = self.shown_command(key);
+            let id = self.jobs.push_finished(&label, &shown, &output, code);
+            self.job_actions.insert(id, key);
+            return Some(id);
+        }
 
-                let text = Paragraph::new(lines)
-                    .alignment(Alignment::Left)
-                    .wrap(Wrap { trim: true });
-                f.render_widget(text, inner);
+        let shown = self.shown_command(key);
+        let cmd = self.run_command(key)?;
+        self.record_run(key);
+        if self.config.app.shell_history {
+            if let Err(err) = shell_history::append(&shown, self.config.app.history_file.as_deref())
+            {
+                self.toasts
+                    .error(format!("Could not write shell history: {}", err));
             }
+        }
+        let id = self.spawn_job(&label, &cmd, &shown)?;
+        self.job_actions.insert(id, key);
+        Some(id)
+    }
 
-            // Footer area: preview + help. Always present even when details are shown
-            let bottom_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Length(3), Constraint::Length(3)].as_ref())
-                .split(chunks[2]);
+    // Start a background job, publishing its status; `shown` is the command with its
+    // secrets masked
+    fn spawn_job(&mut self, label: &str, cmd: &str, shown: &str) -> Option<usize> {
+        match self.jobs.spawn(label, cmd, shown) {
+            Ok(id) => {
+                status::publish(&self.config.app, RunnerStatus::Running, Some(label));
+                Some(id)
+            }
+            Err(err) => {
+                status::publish(&self.config.app, RunnerStatus::Failed, Some(label));
+                self.toasts.error(err.to_string());
+                None
+            }
+        }
+    }
 
-            // show the action template in the preview
-            // Build preview_line by substituting parameter placeholders with current values
-            let mut preview_line = String::new();
-                    if let Some((c, a)) = app.focused_action_index() {
-                        preview_line = build_substituted_command(&app, c, a);
-                    }
+    fn is_watched(&self, key: ActionKey) -> bool {
+        let (column, label) = self.action_id(key);
+        self.watches
+            .iter()
+            .any(|w| w.column == column && w.label == label)
+    }
 
-            // Draw bordered preview and render a single-line paragraph inside
-            let preview_area = bottom_chunks[0];
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .title(Span::styled(
-                    " Preview ",
-                    Style::default().add_modifier(Modifier::BOLD),
-                ))
-                .title_alignment(Alignment::Left);
-            f.render_widget(block, preview_area);
-
-            let inner = Rect {
-                x: preview_area.x + 1,
-                y: preview_area.y + 1,
-                width: preview_area.width.saturating_sub(2),
-                // force a single-line inner area so only one row is displayed
-                height: 1,
+    /// Start or stop re-running an action every `watch_secs` (two seconds without)
+    fn toggle_watch(&mut self, key: ActionKey) {
+        let (column, label) = self.action_id(key);
+        if self.is_watched(key) {
+            self.watches
+                .retain(|w| !(w.column == column && w.label == label));
+            self.toasts.info(format!("Stopped watching {}", label));
+            return;
+        }
+        let secs = self.nav.action(key).map_or(0, |a| a.watch_secs);
+        let every = if secs > 0 {
+            Duration::from_secs(secs)
+        } else {
+            DEFAULT_WATCH
+        };
+        self.start_watch(key, every);
+    }
+
+    // Run an action now and then every `every`; only the first run is recorded
+    fn start_watch(&mut self, key: ActionKey, every: Duration) {
+        let (column, label) = self.action_id(key);
+        self.toasts.info(format!(
+            "Watching {} every {}s (w stops)",
+            label,
+            every.as_secs()
+        ));
+        if !self.simulate {
+            self.record_run(key);
+        }
+        self.watches.push(Watch {
+            column,
+            label,
+            every,
+            next: Instant::now(),
+            runs: Vec::new(),
+        });
+        self.run_watches();
+    }
+
+    /// Start the watched actions that are due and not still running; whether any was
+    fn run_watches(&mut self) -> bool {
+        let now = Instant::now();
+        let mut started = false;
+        for i in 0..self.watches.len() {
+            let watch = &self.watches[i];
+            let running = watch
+                .runs
+                .last()
+                .and_then(|&id| self.jobs.get(id))
+                .is_some_and(Job::is_running);
+            if watch.next > now || running {
+                continue;
+            }
+            let Some(key) = self.find_action(&watch.column, &watch.label) else {
+                // gone from the config
+                continue;
             };
-            let inner_para = Paragraph::new(vec![Spans::from(vec![
-                Span::raw("  "),
-                Span::raw(preview_line.clone()),
-                Span::raw("  "),
-            ])])
-            .alignment(Alignment::Left)
-            .wrap(Wrap { trim: false });
-            f.render_widget(inner_para, inner);
-
-            // Help bar content
-            let help_text =
-                "Tab: switch column   Up/Down: navigate   Enter: details   r:Run   q: quit | *: Optional";
-
-            // If the help area is tall enough, render a bordered block and draw the
-            // help text inside the block inner rect. Otherwise render the help line
-            // directly (no border) so it remains visible on small terminals.
-            let help_area = bottom_chunks[1];
-            if help_area.height >= 3 {
-                let block = Block::default().borders(Borders::ALL).title(Span::styled(
-                    " Help ",
-                    Style::default().add_modifier(Modifier::BOLD),
-                ));
-                f.render_widget(block, help_area);
-
-                let inner = Rect {
-                    x: help_area.x + 1,
-                    y: help_area.y + 1,
-                    width: help_area.width.saturating_sub(2),
-                    height: help_area.height.saturating_sub(2),
-                };
-                let inner_para = Paragraph::new(vec![Spans::from(vec![
-                    Span::raw("  "),
-                    Span::styled(help_text, Style::default().fg(Color::Rgb(150, 150, 150))),
-                    Span::raw("  "),
-                ])])
-                .alignment(Alignment::Left)
-                .wrap(Wrap { trim: false });
-                f.render_widget(inner_para, inner);
+            let label = format!("{} ↻ {}s", watch.label, watch.every.as_secs());
+            let shown = self.masked_command_for(key);
+            let id = if self.simulate {
+                let cmd = self.command_for(key);
+                let simulation = self.nav.action(key).and_then(|a| a.simulate.clone());
+                let (output, code) = simulate_command(&cmd, simulation.as_ref());
+                Some(self.jobs.push_finished(&label, &shown, &output, code))
             } else {
-                // cramped: render help text plainly so it's visible
-                let compact = Paragraph::new(vec![Spans::from(vec![
-                    Span::raw("  "),
-                    Span::styled(help_text, Style::default().fg(Color::Rgb(150, 150, 150))),
-                    Span::raw("  "),
-                ])])
-                .alignment(Alignment::Left);
-                f.render_widget(compact, help_area);
-            }
-        })?;
-
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
-
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                // If we're in text edit mode, handle editing keys separately
-                if app.details_in_edit {
-                    if let Some((c, a)) = app.focused_action_index() {
-                        let pidx = app.details_focused_param;
-                        match key.code {
-                            KeyCode::Char(ch) => {
-                                // append character to buffer and update param_values
-                                app.details_edit_buffer.push(ch);
-                                app.param_values[c][a][pidx] = app.details_edit_buffer.clone();
-                            }
-                            KeyCode::Backspace => {
-                                app.details_edit_buffer.pop();
-                                app.param_values[c][a][pidx] = app.details_edit_buffer.clone();
-                            }
-                            KeyCode::Enter => {
-                                // accept edit
-                                app.details_in_edit = false;
-                                app.details_edit_original.clear();
-                                app.details_edit_buffer.clear();
-                            }
-                            KeyCode::Esc => {
-                                // cancel edit, revert original value
-                                app.param_values[c][a][pidx] = app.details_edit_original.clone();
-                                app.details_in_edit = false;
-                                app.details_edit_buffer.clear();
-                                app.details_edit_original.clear();
-                            }
-                            _ => {}
-                        }
-                    }
-                    continue;
-                }
-
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Tab => {
-                        // Only switch columns when details view is not open
-                        if !app.show_details {
-                            let num_cols = app.column_count();
-                            if num_cols > 0 {
-                                app.focused_column = (app.focused_column + 1) % num_cols;
-                            }
-                        }
-                    }
-                    KeyCode::Up => {
-                        if app.show_details {
-                            if app.details_focused_param > 0 {
-                                app.details_focused_param -= 1;
-                            }
-                        } else {
-                            app.move_up()
-                        }
-                    }
-                    KeyCode::Down => {
-                        if app.show_details {
-                            // move to next parameter if available
-                            if let Some((_c, a)) = app.focused_action_index() {
-                                let params_len =
-                                    app.columns[app.focused_column].actions[a].parameters.len();
-                                if app.details_focused_param + 1 < params_len {
-                                    app.details_focused_param += 1;
-                                }
-                            }
-                        } else {
-                            app.move_down()
-                        }
-                    }
-                    KeyCode::Left => {
-                        if app.show_details {
-                            if let Some((c, a)) = app.focused_action_index() {
-                                if let Some(param) = app.columns[c].actions[a]
-                                    .parameters
-                                    .get(app.details_focused_param)
-                                {
-                                    if param.param_type == crate::config::ParameterType::Select {
-                                        let opts_len = param.options.len();
-                                        if opts_len > 0 {
-                                            let cur = &mut app.param_selected[c][a]
-                                                [app.details_focused_param];
-                                            if *cur > 0 {
-                                                *cur -= 1;
-                                                // sync param_values with new selection
-                                                app.param_values[c][a][app.details_focused_param] =
-                                                    param.options[*cur].value.clone();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Right => {
-                        if app.show_details {
-                            if let Some((c, a)) = app.focused_action_index() {
-                                if let Some(param) = app.columns[c].actions[a]
-                                    .parameters
-                                    .get(app.details_focused_param)
-                                {
-                                    if param.param_type == crate::config::ParameterType::Select {
-                                        let opts_len = param.options.len();
-                                        if opts_len > 0 {
-                                            let cur = &mut app.param_selected[c][a]
-                                                [app.details_focused_param];
-                                            if *cur + 1 < opts_len {
-                                                *cur += 1;
-                                                // sync param_values with new selection
-                                                app.param_values[c][a][app.details_focused_param] =
-                                                    param.options[*cur].value.clone();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::PageUp => {
-                        // When details view is open, PageUp is reserved for details navigation;
-                        // ignore it here so the columns don't change.
-                        if !app.show_details {
-                            // move up by one page in the focused column
-                            let size = terminal.size()?;
-                            let title_lines = title_spans(&app.config.app.title);
-                            let title_height = (title_lines.len() as u16).saturating_add(1).max(3);
-                            // account for outer margin (1 top + 1 bottom)
-                            let middle_height = size
-                                .height
-                                .saturating_sub(2)
-                                .saturating_sub(title_height)
-                                .saturating_sub(6);
-                            let page = middle_height.saturating_sub(2).max(1) as usize; // inner height minus block borders
-
-                            if let Some(col) = app.columns.get_mut(app.focused_column) {
-                                if !col.actions.is_empty() {
-                                    if let Some(curr) = col.list_state.selected() {
-                                        let new = curr.saturating_sub(page);
-                                        col.list_state.select(Some(new));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::PageDown => {
-                        // When details view is open, PageDown is reserved; ignore here
-                        if !app.show_details {
-                            // move down by one page in the focused column
-                            let size = terminal.size()?;
-                            let title_lines = title_spans(&app.config.app.title);
-                            let title_height = (title_lines.len() as u16).saturating_add(1).max(3);
-                            let middle_height = size
-                                .height
-                                .saturating_sub(2)
-                                .saturating_sub(title_height)
-                                .saturating_sub(6);
-                            let page = middle_height.saturating_sub(2).max(1) as usize;
-
-                            if let Some(col) = app.columns.get_mut(app.focused_column) {
-                                if !col.actions.is_empty() {
-                                    if let Some(curr) = col.list_state.selected() {
-                                        let new =
-                                            (curr + page).min(col.actions.len().saturating_sub(1));
-                                        col.list_state.select(Some(new));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Home => {
-                        // jump to top (only when not showing details)
-                        if !app.show_details {
-                            if let Some(col) = app.columns.get_mut(app.focused_column) {
-                                if !col.actions.is_empty() {
-                                    col.list_state.select(Some(0));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::End => {
-                        // jump to bottom (only when not showing details)
-                        if !app.show_details {
-                            if let Some(col) = app.columns.get_mut(app.focused_column) {
-                                if !col.actions.is_empty() {
-                                    col.list_state
-                                        .select(Some(col.actions.len().saturating_sub(1)));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Enter => {
-                        // If details view is not shown, open it. If it is shown and the
-                        // focused parameter is text, enter edit mode. Otherwise toggle details.
-                        if !app.show_details {
-                            app.show_details = true;
-                        } else if let Some((c, a)) = app.focused_action_index() {
-                            if let Some(param) = app.columns[c].actions[a]
-                                .parameters
-                                .get(app.details_focused_param)
-                            {
-                                if param.param_type == crate::config::ParameterType::Text {
-                                    // enter edit mode
-                                    app.details_in_edit = true;
-                                    app.details_edit_original =
-                                        app.param_values[c][a][app.details_focused_param].clone();
-                                    app.details_edit_buffer = app.details_edit_original.clone();
-                                } else {
-                                    // non-text: no-op for Enter while in details
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Esc => {
-                        // close details view if open
-                        if app.show_details {
-                            app.show_details = false;
-                        }
-                    }
-                    KeyCode::Char('r') => {
-                        // when details are shown, run the substituted command
-                        if app.show_details {
-                            if let Some((c, a)) = app.focused_action_index() {
-                                let cmd = build_substituted_command(&app, c, a);
-                                let _ = run_command(terminal, &cmd);
-                            }
-                        }
-                    }
-                    _ => {}
+                self.run_values(key)
+                    .and_then(|values| {
+                        let action = self.nav.action(key)?;
+                        Some(
+                            self.params
+                                .substitute_with(action, &|pidx| values[pidx].clone()),
+                        )
+                    })
+                    .and_then(|cmd| self.spawn_job(&label, &cmd, &shown))
+            };
+            let watch = &mut self.watches[i];
+            watch.next = now + watch.every;
+            if let Some(id) = id {
+                watch.runs.push(id);
+                if watch.runs.len() > 3 {
+                    let old = watch.runs.remove(0);
+                    self.jobs.forget(old);
                 }
+                self.split_view = true;
+                started = true;
             }
         }
+        started
+    }
 
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
-            // toggle blinking cursor when in edit mode
-            app.details_cursor_on = !app.details_cursor_on;
-        }
+    /// The `dry_template` of an action as an action of its own, to substitute its
+    /// parameter values in
+    fn dry_action(&self, key: ActionKey) -> Option<Action> {
+        let action = self.nav.action(key)?;
+        Some(Action {
+            template: action.dry_template.clone()?,
+            ..action.clone()
+        })
     }
-}
 
-// removed old modal preview helper
+    /// Run the dry-run variant of an action in the background. It is not a run of the
+    /// action: no history, statistics or last status.
+    fn start_dry_run(&mut self, key: ActionKey) {
+        let Some(dry) = self.dry_action(key) else {
+            self.toasts.info("This action has no dry_template");
+            return;
+        };
+        let shown = self.masked_command(key, &dry);
+        let label = format!("{} (dry run)", dry.label);
+        self.split_view = true;
+        if self.simulate {
+            let cmd = self.params.substitute(key, &dry);
+            let (output, code) = simulate_command(&cmd, None);
+            self.jobs.push_finished(&label, &shown, &output, code);
+            return;
+        }
+        if let Some(values) = self.run_values(key) {
+            let cmd = self
+                .params
+                .substitute_with(&dry, &|pidx| values[pidx].clone());
+            self.spawn_job(&label, &cmd, &shown);
+        }
+    }
 
-// removed centered_rect helper
+    /// Step the first select parameter of the focused action to its previous or next option
+    fn step_first_select(&mut self, delta: isize) {
+        let (Some(key), Some(action)) = (self.nav.focused_key(), self.nav.focused_action()) else {
+            return;
+        };
+        let Some((pidx, param)) = action
+            .parameters
+            .iter()
+            .enumerate()
+            .find(|(_, p)| p.param_type == ParameterType::Select)
+        else {
+            self.toasts.info("This action has no select parameter");
+            return;
+        };
+        let param = param.clone();
+        self.params.step_option(key, pidx, &param, delta);
+    }
+
+    /// Mark or unmark the focused action for a batch run
+    fn toggle_marked(&mut self) {
+        if let Some(key) = self.nav.focused_key() {
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+        }
+    }
+
+    /// Run the marked actions in the background, in list order. Nothing starts when
+    /// one of them still misses a required value or must be confirmed by typing.
+    fn run_batch(&mut self) {
+        let mut keys: Vec<ActionKey> = self.marked.iter().copied().collect();
+        if keys.is_empty() {
+            self.toasts.info("Mark actions with Space first");
+            return;
+        }
+        keys.sort_by_key(|k| (k.column, k.action));
+        for &key in &keys {
+            if let Some(action) = self.nav.action(key) {
+                if action.typed_confirmation().is_some() {
+                    self.toasts.error(format!(
+                        "{} must be confirmed, run it on its own",
+                        action.label
+                    ));
+                    return;
+                }
+            }
+            if let Some((pidx, reason)) = self.first_invalid(key) {
+                self.prompt_invalid(key, pidx, &reason);
+                return;
+            }
+        }
+        self.marked.clear();
+        match self.config.app.batch {
+            BatchMode::Parallel => {
+                for key in keys {
+                    self.start_job(key);
+                }
+            }
+            BatchMode::Sequential => {
+                self.batch_queue.extend(keys);
+                self.advance_batch();
+            }
+        }
+    }
+
+    /// Start the next action of a sequential batch once the previous one succeeded
+    fn advance_batch(&mut self) {
+        if let Some(id) = self.batch_job {
+            match self.jobs.get(id) {
+                Some(job) if job.is_running() => return,
+                Some(job) if !job.succeeded() => {
+                    let skipped = self.batch_queue.len();
+                    self.batch_queue.clear();
+                    if skipped > 0 {
+                        self.toasts
+                            .error(format!("Batch stopped, {} action(s) not run", skipped));
+                    }
+                }
+                _ => {}
+            }
+            self.batch_job = None;
+        }
+        // an action that could not start doesn't hold up the rest
+        while self.batch_job.is_none() {
+            let Some(key) = self.batch_queue.pop_front() else {
+                break;
+            };
+            self.batch_job = self.start_job(key);
+        }
+    }
+
+    /// Apply a saved workspace: parameter values, column layout, focus and open panes
+    pub fn restore_session(&mut self, session: &Session) {
+        self.restore_params(&session.params);
+
+        for saved in &session.marked {
+            if let Some(key) = self.find_action(&saved.column, &saved.action) {
+                self.marked.insert(key);
+            }
+        }
+
+        for saved in &session.columns {
+            if let Some(c) = self.config.columns.iter().position(|c| c.id == saved.id) {
+                let col = &mut self.nav.columns[c];
+                col.collapsed = saved.collapsed;
+                col.width = saved.width.or(col.width);
+                if let Some(weight) = saved.weight {
+                    col.weight = weight;
+                }
+            }
+        }
+
+        let focused = session
+            .focused_column
+            .as_ref()
+            .and_then(|id| self.config.columns.iter().position(|c| &c.id == id));
+        if let Some(column) = focused {
+            let action = session
+                .focused_action
+                .as_ref()
+                .and_then(|label| {
+                    self.nav
+                        .column(column)?
+                        .actions
+                        .iter()
+                        .position(|a| &a.label == label)
+                })
+                .unwrap_or(0);
+            self.nav.select(ActionKey { column, action });
+        }
+        if session.details_open && self.nav.focused_action().is_some() {
+            self.open_details();
+        }
+        self.split_view = session.split_view;
+    }
+
+    // Set parameter values saved by column id, action label and parameter name
+    fn restore_params(&mut self, params: &[SavedParam]) {
+        for saved in params {
+            let Some(key) = self.find_action(&saved.column, &saved.action) else {
+                continue;
+            };
+            let Some(act) = self.nav.action(key) else {
+                continue;
+            };
+            if let Some((pidx, param)) = act
+                .parameters
+                .iter()
+                .enumerate()
+                .find(|(_, p)| p.name == saved.param)
+            {
+                let param = param.clone();
+                self.params.restore(key, pidx, &param, &saved.value);
+            }
+        }
+    }
+
+    /// Ask whether to restore what a crashed instance was editing, see `crash_recovery`
+    pub fn offer_recovery(&mut self) {
+        if !self.config.app.crash_recovery || self.follow {
+            return;
+        }
+        let Some(recovery) = Recovery::orphaned() else {
+            return;
+        };
+        if recovery.is_empty() {
+            recovery.discard();
+            return;
+        }
+        let text = format!(
+            "callbot did not exit cleanly last time. Restore these values?\n\n{}",
+            recovery.describe().join("\n")
+        );
+        self.modal = Some(Modal::new(ConfirmModal {
+            question: Question::RestoreEdits,
+            title: "Restore unsaved values".to_string(),
+            text,
+        }));
+        self.recovery = Some(recovery);
+    }
+
+    // Act on the answer to a `ConfirmModal`
+    fn answer(&mut self, question: Question, yes: bool) -> Step {
+        match question {
+            Question::DelayedRun => {
+                if self.countdown.take().is_some() {
+                    self.toasts.info("Run cancelled");
+                }
+            }
+            Question::TypedRun => {
+                if let Some((key, background)) = self.typed_run.take() {
+                    if yes {
+                        return self.launch_confirmed(key, background);
+                    }
+                    self.toasts.info("Run cancelled");
+                }
+            }
+            Question::RestoreEdits => {
+                if let Some(recovery) = self.recovery.take() {
+                    if yes {
+                        self.restore_recovery(&recovery);
+                    }
+                    recovery.discard();
+                }
+            }
+        }
+        Step::Redraw
+    }
+
+    // Use the text typed in a `PromptModal`
+    fn entered(&mut self, prompt: Prompt, text: &str) {
+        match prompt {
+            Prompt::PresetName => self.save_preset(text),
+            Prompt::ScriptPath => self.export_script(Path::new(text)),
+            Prompt::OutputPath(id) => self.export_output(id, Path::new(text)),
+        }
+    }
+
+    // Act on the entry chosen in a `PickModal` or `HistoryModal`
+    fn picked(&mut self, pick: Pick, name: &str) -> Step {
+        if matches!(pick, Pick::RerunHistory | Pick::FillHistory) {
+            return self.recall(name, pick == Pick::RerunHistory);
+        }
+        let Some(key) = self.nav.focused_key() else {
+            return Step::Redraw;
+        };
+        match pick {
+            Pick::RerunHistory | Pick::FillHistory => {}
+            Pick::ApplyPreset => {
+                let preset = self.presets(key).into_iter().find(|(p, _)| p.name == name);
+                if let Some((preset, _)) = preset {
+                    self.apply_preset(key, &preset);
+                    self.toasts.info(format!("Preset '{}' applied", name));
+                }
+            }
+            Pick::DeletePreset => {
+                let (column, label) = self.action_id(key);
+                self.state.delete_preset(&column, &label, name);
+                self.save_state();
+                self.toasts.info(format!("Preset '{}' deleted", name));
+                self.open_presets();
+            }
+            Pick::Export => {
+                let label = self.nav.action(key).map(|a| a.label.clone());
+                let label = label.unwrap_or_default();
+                if name == "alias" {
+                    let alias = export::alias(&label, &self.final_command(key));
+                    self.modal = Some(Modal::new(MessageModal::info("Alias", alias)));
+                } else {
+                    self.modal = Some(Modal::new(
+                        PromptModal::new(
+                            Prompt::ScriptPath,
+                            "Export script",
+                            "File to write the script to:",
+                        )
+                        .with_input(export::script_name(&label)),
+                    ));
+                }
+            }
+        }
+        Step::Redraw
+    }
+
+    /// Search the commands run before (Ctrl+R)
+    fn open_history(&mut self) {
+        if self.state.history.is_empty() {
+            self.toasts.info("No command in the history yet");
+            return;
+        }
+        let entries = self
+            .state
+            .history
+            .iter()
+            .map(|e| (e.command.clone(), e.label.clone()))
+            .collect();
+        self.modal = Some(Modal::new(HistoryModal::new(entries)));
+    }
+
+    /// Open the details of the action of a history entry with the values it ran
+    /// with, and run it again when `run`. A command edited by hand before running
+    /// comes back as the command override.
+    fn recall(&mut self, command: &str, run: bool) -> Step {
+        let Some(entry) = self.state.history.iter().find(|e| e.command == command) else {
+            return Step::Redraw;
+        };
+        let entry = entry.clone();
+        let Some(key) = self.find_action(&entry.column, &entry.label) else {
+            self.toasts
+                .error(format!("'{}' is no longer in the config", entry.label));
+            return Step::Redraw;
+        };
+        self.nav.select(key);
+        self.open_details();
+        let parameters = self.nav.action(key).map(|a| a.parameters.clone());
+        for (pidx, param) in parameters.unwrap_or_default().iter().enumerate() {
+            if let Some(value) = entry.values.get(&param.name) {
+                self.params.restore(key, pidx, param, value);
+            }
+        }
+        self.details.command_override = entry.edited;
+        if !run {
+            return Step::Redraw;
+        }
+        if self.follow {
+            self.toasts.info("Follow mode is read-only");
+            return Step::Redraw;
+        }
+        self.launch(key, false)
+    }
+
+    /// Offer to export the focused action's command as a script or an alias
+    fn open_export(&mut self) {
+        if self
+            .nav
+            .focused_key()
+            .is_some_and(|key| self.has_hidden_values(key))
+        {
+            self.toasts
+                .error("Not exported: its secret values would be written in clear");
+            return;
+        }
+        let items = [
+            (
+                "script",
+                "standalone shell script, for someone without callbot",
+            ),
+            ("alias", "alias line to add to a shell's rc file"),
+        ];
+        self.modal = Some(Modal::new(PickModal {
+            title: "Export".to_string(),
+            items: items
+                .into_iter()
+                .map(|(name, detail)| PickItem {
+                    name: name.to_string(),
+                    detail: detail.to_string(),
+                    deletable: false,
+                })
+                .collect(),
+            selected: 0,
+            pick: Pick::Export,
+            delete: None,
+        }));
+    }
+
+    /// Ask where to save the output of the job in the output pane
+    fn open_output_export(&mut self) {
+        let Some(job) = self.output.job(&self.jobs) else {
+            self.toasts.info("No output to save");
+            return;
+        };
+        self.modal = Some(Modal::new(
+            PromptModal::new(
+                Prompt::OutputPath(job.id),
+                "Save output",
+                format!("File to write the output of {} to:", job.label),
+            )
+            .with_input(export::log_name(&job.label)),
+        ));
+    }
+
+    // Write all the captured output of a job to a file
+    fn export_output(&mut self, id: usize, path: &Path) {
+        let Some(job) = self.jobs.get(id) else {
+            self.toasts.error("The job is gone");
+            return;
+        };
+        let lines = job.output.len();
+        match export::write_output(&expand_home(&path.to_string_lossy()), &job.output) {
+            Ok(()) => self.toasts.info(format!(
+                "Saved {} line{} to {}",
+                lines,
+                if lines == 1 { "" } else { "s" },
+                path.display()
+            )),
+            Err(err) => self
+                .toasts
+                .error(format!("Could not write {}: {}", path.display(), err)),
+        }
+    }
+
+    // Write the focused action's command to a script file
+    fn export_script(&mut self, path: &Path) {
+        let Some(key) = self.nav.focused_key() else {
+            return;
+        };
+        let Some(action) = self.nav.action(key) else {
+            return;
+        };
+        // commands run from callbot's directory, so does the script
+        let dir = std::env::current_dir().unwrap_or_default();
+        let script = export::script(
+            &action.label,
+            action.description.as_deref(),
+            &self.final_command(key),
+            &dir,
+        );
+        match export::write_script(&expand_home(&path.to_string_lossy()), &script) {
+            Ok(()) => self.toasts.info(format!("Exported to {}", path.display())),
+            Err(err) => self
+                .toasts
+                .error(format!("Could not write {}: {}", path.display(), err)),
+        }
+    }
+
+    // Column id and label identifying an action in the state file
+    fn action_id(&self, key: ActionKey) -> (String, String) {
+        let column = self.config.columns.get(key.column).map(|c| c.id.clone());
+        let label = self.nav.action(key).map(|a| a.label.clone());
+        (column.unwrap_or_default(), label.unwrap_or_default())
+    }
+
+    /// Presets of an action: those of the config, then the saved ones (true)
+    fn presets(&self, key: ActionKey) -> Vec<(Preset, bool)> {
+        let Some(action) = self.nav.action(key) else {
+            return Vec::new();
+        };
+        let (column, label) = self.action_id(key);
+        let saved: Vec<Preset> = self.state.presets_of(&column, &label).cloned().collect();
+        action
+            .presets
+            .iter()
+            .cloned()
+            .map(|p| (p, false))
+            .chain(saved.into_iter().map(|p| (p, true)))
+            .collect()
+    }
+
+    /// List the presets of the focused action to apply or delete one
+    fn open_presets(&mut self) {
+        let Some(key) = self.nav.focused_key() else {
+            return;
+        };
+        let presets = self.presets(key);
+        if presets.is_empty() {
+            self.toasts
+                .info("No presets for this action (P saves the current values)");
+            return;
+        }
+        let items = presets
+            .into_iter()
+            .map(|(preset, saved)| {
+                let values: Vec<String> = preset
+                    .values
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect();
+                let origin = if saved { "" } else { " (config)" };
+                PickItem {
+                    name: preset.name,
+                    detail: format!("{}{}", values.join(" "), origin),
+                    deletable: saved,
+                }
+            })
+            .collect();
+        self.modal = Some(Modal::new(PickModal {
+            title: "Presets".to_string(),
+            items,
+            selected: 0,
+            pick: Pick::ApplyPreset,
+            delete: Some(Pick::DeletePreset),
+        }));
+    }
+
+    // Set the values of a preset; names the action doesn't have are skipped
+    fn apply_preset(&mut self, key: ActionKey, preset: &Preset) {
+        let Some(action) = self.nav.action(key) else {
+            return;
+        };
+        let params = action.parameters.clone();
+        for (pidx, param) in params.iter().enumerate() {
+            if let Some(value) = preset.values.get(&param.name) {
+                self.params.restore(key, pidx, param, value);
+            }
+        }
+    }
+
+    // Save the current values of the focused action as a preset
+    fn save_preset(&mut self, name: &str) {
+        let Some(key) = self.nav.focused_key() else {
+            return;
+        };
+        let Some(action) = self.nav.action(key) else {
+            return;
+        };
+        let values = action
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(pidx, p)| (p, self.params.value(key, pidx)))
+            .filter(|(p, value)| !secrets::hides(p, value))
+            .map(|(p, value)| (p.name.clone(), value.to_string()))
+            .collect();
+        let preset = Preset {
+            name: name.to_string(),
+            values,
+        };
+        let (column, label) = self.action_id(key);
+        self.state.save_preset(&column, &label, preset);
+        self.save_state();
+        self.toasts.info(format!("Preset '{}' saved", name));
+    }
+
+    // Set the recovered values and resume the edit that was in progress
+    fn restore_recovery(&mut self, recovery: &Recovery) {
+        self.restore_params(&recovery.params);
+        let Some(ref edit) = recovery.edit else {
+            return;
+        };
+        let Some(key) = self.find_action(&edit.param.column, &edit.param.action) else {
+            return;
+        };
+        let pidx = self
+            .nav
+            .action(key)
+            .and_then(|a| a.parameters.iter().position(|p| p.name == edit.param.param));
+        let Some(pidx) = pidx else {
+            return;
+        };
+        self.nav.select(key);
+        self.open_details();
+        self.details.focused_param = pidx;
+        self.params.set_value(key, pidx, edit.param.value.clone());
+        self.details.edit = Some(EditState::resume(
+            edit.param.value.clone(),
+            edit.original.clone(),
+        ));
+    }
+
+    // Values changed from their defaults, and the parameter being edited
+    fn snapshot_recovery(&self) -> Recovery {
+        let mut params = Vec::new();
+        for (column, col) in self.nav.columns.iter().enumerate() {
+            let column_id = &self.config.columns[column].id;
+            for (action, act) in col.actions.iter().enumerate() {
+                let key = ActionKey { column, action };
+                for (pidx, param) in act.parameters.iter().enumerate() {
+                    // secrets are typed again rather than left on the disk
+                    if secrets::hides(param, self.params.value(key, pidx)) {
+                        continue;
+                    }
+                    if !self.params.is_initial(key, pidx, param) {
+                        params.push(SavedParam {
+                            column: column_id.clone(),
+                            action: act.label.clone(),
+                            param: param.name.clone(),
+                            value: self.params.value(key, pidx).to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        let edit = match (&self.details.edit, self.focused_param()) {
+            (Some(edit), Some((key, param))) if !param.is_secret() => Some(SavedEdit {
+                param: SavedParam {
+                    column: self.config.columns[key.column].id.clone(),
+                    action: self
+                        .nav
+                        .action(key)
+                        .map(|a| a.label.clone())
+                        .unwrap_or_default(),
+                    param: param.name.clone(),
+                    value: edit.buffer.clone(),
+                },
+                original: edit.original.clone(),
+            }),
+            _ => None,
+        };
+        Recovery::new(params, edit)
+    }
+
+    // Write the crash recovery checkpoint when the edits changed since the last one
+    fn checkpoint(&mut self) {
+        if !self.config.app.crash_recovery
+            || self.follow
+            || self.checkpoint_at.elapsed() < CHECKPOINT_INTERVAL
+        {
+            return;
+        }
+        self.checkpoint_at = Instant::now();
+        let recovery = self.snapshot_recovery();
+        if self.checkpoint.as_ref() == Some(&recovery) {
+            return;
+        }
+        let saved = if recovery.is_empty() {
+            recovery.discard();
+            Ok(())
+        } else {
+            recovery.save()
+        };
+        if let Err(err) = saved {
+            tracing::warn!("could not write the recovery checkpoint: {}", err);
+        }
+        self.checkpoint = Some(recovery);
+    }
+
+    // Remove this instance's checkpoint on a clean exit, by q or through a run that
+    // keeps the terminal: nothing is left to recover
+    fn discard_checkpoint(&mut self) {
+        if let Some(checkpoint) = self.checkpoint.take() {
+            checkpoint.discard();
+        }
+    }
+
+    /// Put the UI back where the last exit left it, see `restore_ui`
+    pub fn restore_ui_state(&mut self) {
+        if !self.config.app.restore_ui {
+            return;
+        }
+        let ui = self.state.ui.clone();
+        for saved in &ui.selected {
+            if let Some(key) = self.find_action(&saved.column, &saved.action) {
+                self.nav.columns[key.column]
+                    .list_state
+                    .select(Some(key.action));
+            }
+        }
+        for (col, conf) in self.nav.columns.iter_mut().zip(&self.config.columns) {
+            col.collapsed = ui.collapsed.contains(&conf.id);
+        }
+        if let Some(column) = ui
+            .focused_column
+            .as_ref()
+            .and_then(|id| self.config.columns.iter().position(|c| &c.id == id))
+        {
+            self.nav.focus_column(column);
+        }
+        if let Some(ref name) = ui.theme {
+            self.theme = theme_index(name);
+        }
+        if ui.details_open {
+            if let Some(action) = self.nav.focused_action() {
+                let params = action.parameters.clone();
+                self.open_details();
+                self.details.filter = ui.filter;
+                self.details.apply_filter(&params);
+            }
+        }
+    }
+
+    // Where the UI is now, for the next launch
+    fn snapshot_ui_state(&self) -> UiState {
+        let columns = self.nav.columns.iter().zip(&self.config.columns);
+        UiState {
+            focused_column: self
+                .config
+                .columns
+                .get(self.nav.focused_column())
+                .map(|c| c.id.clone()),
+            selected: columns
+                .clone()
+                .filter_map(|(col, conf)| {
+                    let action = col.actions.get(col.list_state.selected()?)?;
+                    Some(SavedAction {
+                        column: conf.id.clone(),
+                        action: action.label.clone(),
+                    })
+                })
+                .collect(),
+            collapsed: columns
+                .filter(|(col, _)| col.collapsed)
+                .map(|(_, conf)| conf.id.clone())
+                .collect(),
+            details_open: self.details.open,
+            filter: self.details.filter.clone(),
+            theme: (self.theme != theme_index(&self.config.app.theme))
+                .then(|| self.theme().name.to_string()),
+        }
+    }
+
+    /// Remember where the UI is for the next launch, see `restore_ui`
+    fn save_ui_state(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.config.app.restore_ui {
+            return Ok(());
+        }
+        self.state.ui = self.snapshot_ui_state();
+        self.state.save()
+    }
+
+    /// Recent values of the parameter being edited that contain what was typed
+    fn suggestions(&self) -> Vec<String> {
+        let (Some(edit), Some((_, param))) = (&self.details.edit, self.focused_param()) else {
+            return Vec::new();
+        };
+        if param.is_secret() {
+            return Vec::new();
+        }
+        let typed = edit.buffer.to_lowercase();
+        self.state
+            .values_of(&param.name)
+            .iter()
+            .filter(|v| **v != edit.buffer && v.to_lowercase().contains(&typed))
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect()
+    }
+
+    /// Insert pasted text at the cursor of the edit in progress, if any
+    fn paste(&mut self, text: &str) -> bool {
+        if let Some(ref mut edit) = self.details.command_edit {
+            edit.insert_str(text);
+        } else if let Some(ref mut edit) = self.output.filter_edit {
+            edit.insert_str(text);
+            let filter = edit.buffer.clone();
+            self.output.set_filter(&filter);
+        } else if let Some(ref mut edit) = self.details.filter_edit {
+            edit.insert_str(text);
+            self.details.filter = edit.buffer.clone();
+            if let Some(action) = self.nav.focused_action() {
+                let params = action.parameters.clone();
+                self.details.apply_filter(&params);
+            }
+        } else if let Some(ref mut edit) = self.details.edit {
+            edit.insert_str(text);
+            if let Some(key) = self.nav.focused_key() {
+                let pidx = self.details.focused_param;
+                self.params.set_value(key, pidx, edit.buffer.clone());
+            }
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Reload the config file now, running its providers again
+    pub fn refresh_config(&mut self) {
+        if let Some(path) = self.config_path.clone() {
+            self.apply_reload(Config::load(&path).map(Box::new));
+        }
+    }
+
+    /// Answer a request received on the control socket
+    fn handle_control(&mut self, request: Request) -> serde_json::Value {
+        match request {
+            Request::Run {
+                column,
+                action,
+                params,
+            } => self.run_from_control(&column, &action, &params),
+            Request::Reload => {
+                let Some(path) = self.config_path.clone() else {
+                    return control::error("no config file to reload");
+                };
+                let config = Config::load(&path).map(Box::new);
+                let reply = match config {
+                    Ok(_) => control::ok(json!({})),
+                    Err(ref err) => control::error(err.to_string()),
+                };
+                self.apply_reload(config);
+                reply
+            }
+            Request::Jobs => {
+                let jobs: Vec<_> = self
+                    .jobs
+                    .recent(usize::MAX)
+                    .iter()
+                    .map(|job| control::job_info(job, false))
+                    .collect();
+                control::ok(json!({ "jobs": jobs }))
+            }
+            Request::Job { id } => match self.jobs.get(id) {
+                Some(job) => control::ok(json!({ "job": control::job_info(job, true) })),
+                None => control::error(format!("no job {}", id)),
+            },
+        }
+    }
+
+    // Set the given values and start the action in the background
+    fn run_from_control(
+        &mut self,
+        column: &str,
+        action: &str,
+        values: &HashMap<String, String>,
+    ) -> serde_json::Value {
+        if self.follow {
+            return control::error("follow mode is read-only");
+        }
+        let Some(key) = self.find_action(column, action) else {
+            return control::error(format!("no action '{}' in column '{}'", action, column));
+        };
+        if self
+            .nav
+            .action(key)
+            .is_some_and(|a| a.typed_confirmation().is_some())
+        {
+            return control::error(format!("'{}' must be confirmed in the app", action));
+        }
+        self.refresh_options(key, None);
+        let parameters = self.nav.action(key).map(|a| a.parameters.clone());
+        let parameters = parameters.unwrap_or_default();
+        let mut requested = Vec::new();
+        for (name, value) in values {
+            let Some(pidx) = parameters.iter().position(|p| &p.name == name) else {
+                return control::error(format!("'{}' has no parameter '{}'", action, name));
+            };
+            let param = &parameters[pidx];
+            if param.param_type == ParameterType::Select
+                && !param.options.iter().any(|o| &o.value == value)
+            {
+                return control::error(format!("'{}' is not an option of {}", value, name));
+            }
+            requested.push((pidx, value));
+        }
+        // the requested values only serve this run: the UI keeps the ones shown
+        let shown = self.params.clone();
+        for (pidx, value) in requested {
+            self.params.restore(key, pidx, &parameters[pidx], value);
+        }
+        let reply = match self.first_invalid(key) {
+            Some((pidx, reason)) => {
+                control::error(format!("{}: {}", parameters[pidx].name, reason))
+            }
+            None => match self.start_job(key) {
+                Some(id) => control::ok(json!({ "job": id })),
+                None => control::error("the command could not be started"),
+            },
+        };
+        self.params = shown;
+        reply
+    }
+
+    // Switch to the reloaded config, or report why it could not be loaded
+    fn apply_reload(&mut self, config: Result<Box<Config>, CallbotError>) {
+        match config {
+            Ok(config) => self.reload_config(*config),
+            Err(err) => {
+                self.modal = Some(Modal::new(MessageModal::error(
+                    format!("Config not reloaded: {}", err.title()),
+                    err.to_string(),
+                )))
+            }
+        }
+    }
+
+    /// Switch to a config reloaded from disk, keeping parameter values, layout, focus
+    /// and marks of the actions that are still there
+    fn reload_config(&mut self, mut config: Config) {
+        for column in &mut config.columns {
+            if let Some(&sort) = self.sort_overrides.get(&column.id) {
+                column.sort = sort;
+            }
+        }
+        self.rebuild(config);
+        self.toasts.info("Config reloaded");
+    }
+
+    /// Cycle the order of the actions of the focused column; the order chosen
+    /// outlives reloads until quitting
+    fn cycle_sort(&mut self) {
+        let Some(column) = self.config.columns.get(self.nav.focused_column()) else {
+            return;
+        };
+        let sort = column.sort.next();
+        self.sort_overrides.insert(column.id.clone(), sort);
+        self.toasts
+            .info(format!("{} sorted {}", column.title, sort.describe()));
+        let mut config = self.config.clone();
+        config.columns[self.nav.focused_column()].sort = sort;
+        self.rebuild(config);
+    }
+
+    /// Move the focused action up or down its column, saving the new order
+    fn move_action(&mut self, delta: isize) {
+        let column = self.nav.focused_column();
+        let Some(config) = self.config.columns.get(column) else {
+            return;
+        };
+        if config.sort != ColumnSort::Config {
+            self.toasts
+                .info("Actions only move in config order: press s to get back to it");
+            return;
+        }
+        let (Some(col), Some(key)) = (self.nav.column(column), self.nav.focused_key()) else {
+            return;
+        };
+        let to = key.action as isize + delta;
+        if to < 0 || to as usize >= col.actions.len() {
+            return;
+        }
+        let mut labels: Vec<String> = col.actions.iter().map(|a| a.label.clone()).collect();
+        labels.swap(key.action, to as usize);
+        self.state.set_order(&config.id.clone(), labels);
+        self.save_state();
+        self.rebuild(self.config.clone());
+    }
+
+    // Switch to another config (or the same one ordered differently), keeping what
+    // `reload_config` keeps
+    fn rebuild(&mut self, mut config: Config) {
+        sort_actions(&mut config.columns, &self.state);
+        let snapshot = self.snapshot_session();
+        // jobs and the batch queue refer to actions by position, which may have moved
+        let job_actions: Vec<(usize, String, String)> = self
+            .job_actions
+            .iter()
+            .filter_map(|(&id, &key)| {
+                let column = self.config.columns.get(key.column)?.id.clone();
+                Some((id, column, self.nav.action(key)?.label.clone()))
+            })
+            .collect();
+
+        // keep the selector on the same value when it is still offered
+        let selected = self.selector_option().map(|(_, o)| o.value.clone());
+        self.nav = NavigationState::new(&config.columns);
+        self.params = ParamStore::new(&config.columns);
+        self.config = config;
+        self.selector = selected
+            .and_then(|value| {
+                let param = self.config.selector.as_ref()?;
+                param.options.iter().position(|o| o.value == value)
+            })
+            .unwrap_or(0);
+        self.sync_selector();
+        self.details = DetailsState::default();
+        self.marked.clear();
+        self.batch_queue.clear();
+        self.restore_session(&snapshot);
+        self.job_actions = job_actions
+            .into_iter()
+            .filter_map(|(id, column, label)| Some((id, self.find_action(&column, &label)?)))
+            .collect();
+    }
+
+    /// Current workspace, to be saved under the --session name
+    fn snapshot_session(&self) -> Session {
+        let mut params = Vec::new();
+        for (column, col) in self.nav.columns.iter().enumerate() {
+            let column_id = &self.config.columns[column].id;
+            for (action, act) in col.actions.iter().enumerate() {
+                let key = ActionKey { column, action };
+                for (pidx, param) in act.parameters.iter().enumerate() {
+                    params.push(SavedParam {
+                        column: column_id.clone(),
+                        action: act.label.clone(),
+                        param: param.name.clone(),
+                        value: self.params.value(key, pidx).to_string(),
+                    });
+                }
+            }
+        }
+        Session {
+            params,
+            focused_column: self
+                .config
+                .columns
+                .get(self.nav.focused_column())
+                .map(|c| c.id.clone()),
+            focused_action: self.nav.focused_action().map(|a| a.label.clone()),
+            details_open: self.details.open,
+            split_view: self.split_view,
+            columns: self
+                .nav
+                .columns
+                .iter()
+                .zip(&self.config.columns)
+                .map(|(col, conf)| SavedColumn {
+                    id: conf.id.clone(),
+                    collapsed: col.collapsed,
+                    width: col.width,
+                    weight: Some(col.weight),
+                })
+                .collect(),
+            marked: self
+                .marked
+                .iter()
+                .filter_map(|&key| {
+                    Some(SavedAction {
+                        column: self.config.columns.get(key.column)?.id.clone(),
+                        action: self.nav.action(key)?.label.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Action with the given label in the column with the given id
+    fn find_action(&self, column_id: &str, label: &str) -> Option<ActionKey> {
+        let column = self.config.columns.iter().position(|c| c.id == column_id)?;
+        let action = self
+            .nav
+            .column(column)?
+            .actions
+            .iter()
+            .position(|a| a.label == label)?;
+        Some(ActionKey { column, action })
+    }
+
+    /// Save the workspace when running under --session
+    fn save_session(&self) -> Result<(), Box<dyn Error>> {
+        let Some(ref name) = self.session else {
+            return Ok(());
+        };
+        let mut session = self.snapshot_session();
+        let store = &self.config.app.secret_store;
+        session.params.retain_mut(|saved| {
+            if !self.is_hidden(saved) {
+                return true;
+            }
+            match secrets::seal(store, &secret_id(saved), &saved.value) {
+                Some(sealed) => {
+                    saved.value = sealed;
+                    true
+                }
+                None => false,
+            }
+        });
+        session.save(name)
+    }
+
+    /// Open the password values sealed in a saved session, dropping those that can't be
+    pub fn open_secrets(&self, session: &mut Session) {
+        let store = &self.config.app.secret_store;
+        session.params.retain_mut(|saved| {
+            if !self.is_hidden(saved) {
+                return true;
+            }
+            match secrets::open(store, &secret_id(saved), &saved.value) {
+                Some(value) => {
+                    saved.value = value;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    // Whether a saved value is a secret, see `secrets::hides`
+    fn is_hidden(&self, saved: &SavedParam) -> bool {
+        self.find_action(&saved.column, &saved.action)
+            .and_then(|key| self.nav.action(key))
+            .and_then(|a| a.parameters.iter().find(|p| p.name == saved.param))
+            .is_some_and(|p| secrets::hides(p, &saved.value))
+    }
+
+    /// Spinner and running time of the newest running job started from an action,
+    /// or whether it waits its turn in a batch
+    fn running_badge(&self, key: ActionKey) -> Option<String> {
+        if self.batch_queue.contains(&key) {
+            return Some("queued".to_string());
+        }
+        self.job_actions
+            .iter()
+            .filter(|(_, k)| **k == key)
+            .filter_map(|(id, _)| self.jobs.get(*id))
+            .filter(|job| job.is_running())
+            .max_by_key(|job| job.id)
+            .map(|job| {
+                format!(
+                    "{} {}",
+                    spinner(job, self.config.app.reduced_motion),
+                    format_elapsed(job.run_time())
+                )
+            })
+    }
+
+    /// Style of a select option: its own `color`, else the `option_colors` entry for
+    /// its value (exact, then the longest matching "prefix*")
+    fn option_style(&self, option: &ParameterOption, theme: &Theme) -> Style {
+        let colors = &self.config.app.option_colors;
+        let spec = option.color.as_ref().or_else(|| {
+            colors.get(&option.value).or_else(|| {
+                colors
+                    .iter()
+                    .filter_map(|(pattern, color)| {
+                        let prefix = pattern.strip_suffix('*')?;
+                        option
+                            .value
+                            .starts_with(prefix)
+                            .then_some((prefix.len(), color))
+                    })
+                    .max_by_key(|(len, _)| *len)
+                    .map(|(_, color)| color)
+            })
+        });
+        match spec.and_then(|spec| theme.color(spec)) {
+            Some(color) => Style::default().fg(color),
+            None => theme.text(),
+        }
+    }
+
+    /// Outcome and age of the action's latest run: "✓ 5m ago", "✗ 2 1h ago", or
+    /// "· 3d ago" when it never reported an exit code
+    fn last_run_badge(&self, key: ActionKey, theme: &Theme) -> Option<Span<'static>> {
+        let column = self.config.columns.get(key.column)?;
+        let action = self.nav.action(key)?;
+        let run = self.state.last_run_of(&column.id, &action.label)?;
+        let ago = format_ago(run.timestamp);
+        Some(match run.exit_code {
+            Some(0) => Span::styled(format!("✓ {}", ago), theme.success()),
+            Some(code) => Span::styled(format!("✗ {} {}", code, ago), theme.failure()),
+            None => Span::styled(format!("· {}", ago), theme.dim()),
+        })
+    }
+
+    /// Action bound to `key` with a `hotkey` in the config
+    fn hotkey_action(&self, key: &KeyEvent) -> Option<ActionKey> {
+        self.nav
+            .columns
+            .iter()
+            .enumerate()
+            .find_map(|(column, col)| {
+                col.actions
+                    .iter()
+                    .position(|a| {
+                        a.hotkey
+                            .as_deref()
+                            .and_then(parse_key)
+                            .is_some_and(|hotkey| hotkey_matches(hotkey, key))
+                    })
+                    .map(|action| ActionKey { column, action })
+            })
+    }
+
+    /// Current parameter values of an action, e.g. "[env=prod branch=main]", for the
+    /// list; None when it has no value set or `list_values` is off
+    fn value_summary(&self, key: ActionKey) -> Option<String> {
+        if !self.config.app.list_values {
+            return None;
+        }
+        let action = self.nav.action(key)?;
+        let values: Vec<String> = action
+            .parameters
+            .iter()
+            .enumerate()
+            .filter_map(|(pidx, p)| {
+                let value = self.params.value(key, pidx);
+                if value.is_empty() {
+                    return None;
+                }
+                if secrets::hides(p, value) {
+                    return Some(format!("{}={}", p.name, secrets::MASK));
+                }
+                let (value, _) = ellipsize(value, SUMMARY_VALUE_WIDTH);
+                Some(format!("{}={}", p.name, value))
+            })
+            .collect();
+        if values.is_empty() {
+            None
+        } else {
+            Some(format!("[{}]", values.join(" ")))
+        }
+    }
+
+    /// First parameter of an action whose value fails validation, with the reason
+    fn first_invalid(&self, key: ActionKey) -> Option<(usize, String)> {
+        let action = self.nav.action(key)?;
+        action.parameters.iter().enumerate().find_map(|(pidx, p)| {
+            p.check(self.params.value(key, pidx))
+                .err()
+                .map(|e| (pidx, e))
+        })
+    }
+
+    /// Open the details of an action on a parameter that needs a (valid) value
+    fn prompt_invalid(&mut self, key: ActionKey, pidx: usize, reason: &str) {
+        if !(self.details.open && self.nav.focused_key() == Some(key)) {
+            self.nav.select(key);
+            self.open_details();
+        }
+        self.details.focused_param = pidx;
+        if let Some(param) = self.nav.action(key).and_then(|a| a.parameters.get(pidx)) {
+            self.toasts.error(format!("{}: {}", param.name, reason));
+        }
+    }
+
+    /// Reload the jobs mirrored by other instances (`--follow`)
+    fn follow_jobs(&mut self) {
+        if let Some(ref dir) = self.config.app.follow_dir {
+            self.jobs.follow(&expand_home(dir));
+        }
+    }
+
+    /// Panic button: stop every running job at once, and the batch and watches that
+    /// would start more
+    fn abort_jobs(&mut self) {
+        if self.follow {
+            self.toasts.info("Follow mode is read-only");
+            return;
+        }
+        let (queued, watched) = (self.batch_queue.len(), self.watches.len());
+        self.batch_queue.clear();
+        self.watches.clear();
+        let aborted = self.jobs.abort_running();
+        if aborted + queued + watched == 0 {
+            self.toasts.info("No job running");
+            return;
+        }
+        let mut message = format!(
+            "Aborted {} job{}",
+            aborted,
+            if aborted == 1 { "" } else { "s" }
+        );
+        if queued > 0 {
+            message.push_str(&format!(", {} queued not run", queued));
+        }
+        if watched > 0 {
+            message.push_str(&format!(", stopped watching {}", watched));
+        }
+        self.toasts.error(message);
+    }
+
+    /// Collect job output and report finished jobs; returns whether anything changed
+    fn poll_jobs(&mut self) -> bool {
+        let updates = self.jobs.poll();
+        let changed = !updates.is_empty();
+        for id in updates.finished {
+            let Some(job) = self.jobs.get(id) else {
+                continue;
+            };
+            let (label, status, succeeded) =
+                (job.label.clone(), job.status.clone(), job.succeeded());
+            let column = self.job_actions.get(&id).map(|key| self.action_id(*key).0);
+            let mut entry = Entry::new(
+                &column.unwrap_or_default(),
+                &label,
+                &job.command,
+                job.run_time(),
+            );
+            entry.exit_code = match status {
+                JobStatus::Exited(code) => Some(code),
+                _ => None,
+            };
+            entry.background = true;
+            entry.output = Some(job.output.clone());
+            if let Some(ref transcript) = self.transcript {
+                if let Err(err) = transcript.append(&entry) {
+                    self.toasts
+                        .error(format!("Could not record the run: {}", err));
+                }
+            }
+            // a watch reports through the output pane, not on every run
+            if self.watches.iter().any(|w| w.runs.contains(&id)) {
+                continue;
+            }
+            // aborted by hand, there is nothing to tell
+            if status != JobStatus::Aborted {
+                webhook::notify(&self.config.webhooks, &entry);
+            }
+            match status {
+                JobStatus::Exited(code) => {
+                    if succeeded {
+                        self.toasts.info(format!("{} finished", label));
+                    } else {
+                        self.toasts
+                            .error(format!("{} failed (exit {})", label, code));
+                    }
+                    if let Some(&key) = self.job_actions.get(&id) {
+                        let duration = job.run_time();
+                        self.record_stats(key, code, duration);
+                        self.record_exit_of(key, code);
+                    } else if self.state.last_run.as_ref().map(|r| &r.label) == Some(&label) {
+                        self.record_exit(code);
+                    }
+                }
+                JobStatus::Failed(ref err) => {
+                    self.toasts.error(format!("{} failed: {}", label, err));
+                }
+                JobStatus::Running | JobStatus::Aborted => {}
+            }
+            if self.jobs.running_count() == 0 {
+                if succeeded {
+                    status::publish(&self.config.app, RunnerStatus::Idle, None);
+                } else {
+                    status::publish(&self.config.app, RunnerStatus::Failed, Some(&label));
+                }
+            }
+        }
+        if self.batch_job.is_some() {
+            self.advance_batch();
+        }
+        changed
+    }
+}
+
+// recent values listed under a parameter being edited
+const MAX_SUGGESTIONS: usize = 5;
+// how often edited values are checkpointed for crash recovery, at most
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+// longest time the screen goes without a redraw, for relative times
+const IDLE_REFRESH: Duration = Duration::from_secs(5);
+// spinner frames of running jobs, advanced every `SPINNER_INTERVAL`
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+// Footer: preview block (borders plus its rows), status bar (1 row) and help block (3 rows)
+fn footer_height(preview_rows: usize) -> u16 {
+    preview_rows as u16 + 2 + 1 + 3
+}
+
+// the preview grows up to this many rows for long commands
+const MAX_PREVIEW_ROWS: usize = 4;
+
+// values longer than this are cut in the list's parameter summary
+const SUMMARY_VALUE_WIDTH: usize = 20;
+// the summary is left out when less room than this is left after the label
+const MIN_SUMMARY_WIDTH: usize = 8;
+
+// Smallest usable terminal: narrower than this, labels are cut to nothing
+const MIN_WIDTH: u16 = 40;
+// middle area (columns or details) with its borders and a few rows of content
+const MIN_BODY_HEIGHT: u16 = 5;
+// with `banner = "auto"`, rows the middle area needs beyond its minimum for the full
+// banner to be kept; on shorter terminals the header collapses to one title line
+const BANNER_ROOM: u16 = 10;
+
+// Smallest terminal the layout fits in: margins, header, middle area and a footer
+// with a one-row preview
+fn min_size(app: &App, title_lines: usize) -> (u16, u16) {
+    let height = 2 + header_height(app, title_lines) + MIN_BODY_HEIGHT + footer_height(1);
+    (MIN_WIDTH, height)
+}
+
+// Column title with its number of actions, e.g. " Projects (12) ", dropping the
+// count and then the padding when the column is too narrow
+fn column_title(title: &str, count: usize, width: usize) -> String {
+    let counted = format!("{} ({})", title, count);
+    if width > counted.chars().count() + 2 {
+        format!(" {} ", counted)
+    } else if width > title.chars().count() + 2 {
+        format!(" {} ", title)
+    } else {
+        title.to_string()
+    }
+}
+
+// Column without actions: a message saying why instead of an empty block
+fn draw_empty_column(
+    f: &mut Frame,
+    area: Rect,
+    column: &ColumnState,
+    focused: bool,
+    theme: &Theme,
+) {
+    let reason = match column.hidden_actions {
+        0 => "Its source returned no actions".to_string(),
+        1 => "1 action is hidden for this user/profile".to_string(),
+        n => format!("{} actions are hidden for this user/profile", n),
+    };
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("No actions", theme.bold())),
+        Line::from(Span::styled(reason, theme.faint())),
+    ];
+    let border = if focused {
+        theme.accent()
+    } else {
+        theme.text()
+    };
+    let message = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border)
+                .title(Span::styled(
+                    column_title(&column.title, 0, area.width as usize),
+                    theme.bold(),
+                ))
+                .title_alignment(Alignment::Center),
+        );
+    f.render_widget(message, area);
+}
+
+// Title lines of the header for a `size` terminal: the figlet banner, or a single
+// title line when the banner is compacted
+fn header_title(app: &App, size: Rect) -> Vec<Line<'static>> {
+    let (color, gradient_to) = app.banner_colors();
+    let title = &app.config.app.title;
+    let width = size.width.saturating_sub(2) as usize;
+    let compact = match app.config.app.banner {
+        BannerMode::Full => false,
+        BannerMode::Compact => true,
+        BannerMode::Auto => {
+            let lines = title_spans(title, color, gradient_to, width);
+            if size.height >= min_size(app, lines.len()).1 + BANNER_ROOM {
+                return lines;
+            }
+            true
+        }
+    };
+    if compact {
+        vec![title_line(title, color, width)]
+    } else {
+        title_spans(title, color, gradient_to, width)
+    }
+}
+
+// Help line of the current mode: the configured one, or hints for the keys that
+// apply in it
+fn help_text(app: &App) -> String {
+    let help = &app.config.app.help;
+    let (configured, builtin) = if app.details.edit.is_some()
+        || app.details.filter_edit.is_some()
+        || app.output.filter_edit.is_some()
+    {
+        (
+            &help.edit,
+            "Type to edit   Left/Right/Home/End: move   Ctrl+Z/Ctrl+Y: undo/redo   Enter: accept   Esc: cancel",
+        )
+    } else if app.details.open {
+        (
+            &help.details,
+            "Up/Down: parameter   Left/Right: change value   Enter: edit   /: filter   r: run   b: background   e: edit command   Esc: back   ?: keys | *: Optional",
+        )
+    } else if app.split_view {
+        (
+            &help.output,
+            "o: hide output   f: filter   c: diff   Up/Down: navigate   Enter: details   r: run   b: background   Space: mark   R: run marked   ?: keys   q: quit",
+        )
+    } else {
+        (
+            &help.browse,
+            "Tab/Left/Right: switch column   Up/Down: navigate   Enter: details   r: run   b: background   Space: mark   R: run marked   o: output   ?: keys   q: quit",
+        )
+    };
+    configured.as_deref().unwrap_or(builtin).to_string()
+}
+
+// Placeholder drawn instead of the UI when the terminal is below `min_size`
+fn draw_too_small(f: &mut Frame, area: Rect, (width, height): (u16, u16), theme: &Theme) {
+    let lines = vec![
+        Line::from(Span::styled("Terminal too small", theme.bold())),
+        Line::from(Span::styled(
+            format!(
+                "need {}x{}, have {}x{}",
+                width, height, area.width, area.height
+            ),
+            theme.dim(),
+        )),
+    ];
+    // vertically centered when there is room for it
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let rect = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        rect,
+    );
+}
+
+// Hard-wrap the styled pieces of the previewed command to `width` characters per
+// row, ending with "…" when it needs more than MAX_PREVIEW_ROWS rows
+fn preview_lines(segments: &[(String, Style)], width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let chars: Vec<(char, Style)> = segments
+        .iter()
+        .flat_map(|(text, style)| text.chars().map(move |ch| (ch, *style)))
+        .collect();
+    let rows = chars.len().div_ceil(width).clamp(1, MAX_PREVIEW_ROWS);
+    let truncated = chars.len() > rows * width;
+    (0..rows)
+        .map(|row| {
+            let mut line =
+                &chars[(row * width).min(chars.len())..((row + 1) * width).min(chars.len())];
+            let last = row + 1 == rows;
+            if truncated && last {
+                line = &line[..line.len().saturating_sub(1)];
+            }
+            // merge consecutive characters of the same style into one span
+            let mut spans = vec![Span::raw("  ")];
+            let mut current = String::new();
+            let mut current_style = None;
+            for &(ch, style) in line {
+                if current_style.is_some() && current_style != Some(style) {
+                    spans.push(Span::styled(
+                        std::mem::take(&mut current),
+                        current_style.unwrap_or_default(),
+                    ));
+                }
+                current_style = Some(style);
+                current.push(ch);
+            }
+            if !current.is_empty() {
+                spans.push(Span::styled(current, current_style.unwrap_or_default()));
+            }
+            if truncated && last {
+                spans.push(Span::raw("…"));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// Width available to the preview text in a terminal `width` columns wide: outer
+// margin, block borders and two spaces of padding on each side
+fn preview_width(width: u16) -> usize {
+    (width as usize).saturating_sub(2 + 2 + 4)
+}
+
+// Human friendly age of a unix timestamp, e.g. "5m ago"
+fn format_ago(timestamp: u64) -> String {
+    let secs = crate::state::now_secs().saturating_sub(timestamp);
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h ago", secs / 3600)
+    } else {
+        format!("{}d ago", secs / 86400)
+    }
+}
+
+/// Scrollbar on the right border of `area` when `total` rows don't fit in the
+/// `viewport` rows inside it; `position` is the first row shown
+fn draw_scrollbar(
+    f: &mut Frame,
+    area: Rect,
+    total: usize,
+    viewport: usize,
+    position: usize,
+    theme: &Theme,
+) {
+    if total <= viewport {
+        return;
+    }
+    // one position per first row that can be scrolled to
+    let mut state = ScrollbarState::new(total - viewport + 1)
+        .position(position)
+        .viewport_content_length(viewport);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_symbol(None)
+        .thumb_style(theme.dim());
+    let track = area.inner(Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    f.render_stateful_widget(scrollbar, track, &mut state);
+}
+
+// Status bar: current mode, focused column and the outcome of the last run
+fn status_line<'a>(app: &App, theme: &Theme) -> Line<'a> {
+    let sep = || Span::styled(" │ ", theme.faint());
+    let mut spans = vec![Span::styled(
+        format!(" {} ", app.mode().label()),
+        theme.editing(),
+    )];
+    if app.simulate {
+        spans.push(Span::styled(
+            " SIMULATE ",
+            theme.failure().add_modifier(Modifier::BOLD),
+        ));
+    }
+    if app.follow {
+        spans.push(Span::styled(
+            " FOLLOW ",
+            theme.accent().add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.extend([
+        sep(),
+        Span::styled("column: ", theme.dim()),
+        Span::raw(
+            app.nav
+                .focused()
+                .map(|c| c.title.clone())
+                .unwrap_or_default(),
+        ),
+        sep(),
+    ]);
+    if let Some(ref name) = app.session {
+        spans.push(Span::styled("session: ", theme.dim()));
+        spans.push(Span::raw(name.clone()));
+        spans.push(sep());
+    }
+    let running = app.jobs.running_count();
+    if let Some(job) = app.jobs.oldest_running() {
+        spans.push(Span::styled("jobs: ", theme.dim()));
+        spans.push(Span::styled(
+            format!(
+                "{} {} running ({})",
+                spinner(job, app.config.app.reduced_motion),
+                running,
+                format_elapsed(job.run_time())
+            ),
+            theme.accent(),
+        ));
+        spans.push(sep());
+    }
+    spans.push(Span::styled("last run: ", theme.dim()));
+    match app.state.last_run {
+        Some(ref run) => {
+            spans.push(Span::raw(format!("{} ", run.label)));
+            match run.exit_code {
+                Some(0) => spans.push(Span::styled("✓ exit 0", theme.success())),
+                Some(code) => spans.push(Span::styled(format!("✗ exit {}", code), theme.failure())),
+                None => spans.push(Span::styled("no exit status", theme.dim())),
+            }
+            spans.push(Span::styled(
+                format!(" ({})", format_ago(run.timestamp)),
+                theme.dim(),
+            ));
+        }
+        None => spans.push(Span::styled("none", theme.dim())),
+    }
+    // tooltip for a label that was cut off in its column
+    if let Some(ref label) = app.truncated_label {
+        spans.push(sep());
+        spans.push(Span::styled(label.clone(), theme.accent()));
+    }
+    Line::from(spans)
+}
+
+// Leading cells of an action label: a check when it is marked for a batch run
+fn mark_prefix(marked: bool) -> &'static str {
+    if marked {
+        "✓ "
+    } else {
+        "  "
+    }
+}
+
+// Frame of the spinner shown next to a running job; a still ⟳ with reduced motion
+fn spinner(job: &Job, reduced_motion: bool) -> &'static str {
+    if reduced_motion {
+        return "⟳";
+    }
+    let frame = job.run_time().as_millis() / SPINNER_INTERVAL.as_millis();
+    SPINNER[frame as usize % SPINNER.len()]
+}
+
+// Compact running time of a job, e.g. "42s", "3m07s" or "1h05m"
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+// Cut `text` to `width` characters, ending with an ellipsis when it doesn't fit.
+// Returns the text to render and whether it was truncated.
+fn ellipsize(text: &str, width: usize) -> (String, bool) {
+    if text.chars().count() <= width {
+        return (text.to_string(), false);
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    (format!("{}…", kept), true)
+}
+
+// Split `text` into lines of at most `width` characters, breaking on spaces when
+// possible and hard-splitting words that are longer than a line
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word: String = word.to_string();
+        loop {
+            let current_len = current.chars().count();
+            let word_len = word.chars().count();
+            let needed = if current.is_empty() {
+                word_len
+            } else {
+                current_len + 1 + word_len
+            };
+            if needed <= width {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(&word);
+                break;
+            }
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+                continue;
+            }
+            // a single word wider than the line
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(head);
+            if word.is_empty() {
+                break;
+            }
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// interval of a watch started with w on an action without `watch_secs`, as watch(1)
+const DEFAULT_WATCH: Duration = Duration::from_secs(2);
+
+// An action run again and again in the background, by column id and label so it
+// survives reloads
+struct Watch {
+    column: String,
+    label: String,
+    every: Duration,
+    next: Instant,
+    // the last three runs, oldest first, so there are two finished ones to diff while
+    // the next runs; older ones are forgotten
+    runs: Vec<usize>,
+}
+
+// A run started from the keyboard, waiting for the `delay_secs` of its action
+struct Countdown {
+    key: ActionKey,
+    background: bool,
+    fires_at: Instant,
+}
+
+// Name of a saved password value in the secret store, e.g. "servers/Connect/PASSWORD"
+fn secret_id(saved: &SavedParam) -> String {
+    format!("{}/{}/{}", saved.column, saved.action, saved.param)
+}
+
+// Order the actions of each column by its `sort`. The config order is noted the
+// first time, so a column can go back to it after another order; an order set with
+// Alt+Up/Down comes before it, actions added since following in config order.
+fn sort_actions(columns: &mut [Column], state: &State) {
+    for column in columns {
+        if column.config_order.is_empty() {
+            column.config_order = column.actions.iter().map(|a| a.label.clone()).collect();
+        }
+        let id = &column.id;
+        let order = &column.config_order;
+        let position = |action: &Action| order.iter().position(|l| *l == action.label);
+        let custom = state.order_of(id).unwrap_or_default();
+        match column.sort {
+            ColumnSort::Config => column.actions.sort_by_key(|action| {
+                let moved = custom.iter().position(|l| *l == action.label);
+                (moved.unwrap_or(usize::MAX), position(action))
+            }),
+            ColumnSort::Alpha => column
+                .actions
+                .sort_by_cached_key(|action| action.label.to_lowercase()),
+            ColumnSort::Recent => column.actions.sort_by_key(|action| {
+                let run = state.last_run_of(id, &action.label);
+                (
+                    std::cmp::Reverse(run.map(|r| r.timestamp)),
+                    position(action),
+                )
+            }),
+            ColumnSort::MostUsed => column.actions.sort_by_key(|action| {
+                let runs = state.stats_of(id, &action.label).map_or(0, |s| s.runs);
+                (std::cmp::Reverse(runs), position(action))
+            }),
+        }
+    }
+}
+
+// Height of the header: figlet lines plus the subtitle row, and the recent strip when shown
+fn header_height(app: &App, title_lines: usize) -> u16 {
+    let mut height = (title_lines as u16).saturating_add(1).max(3);
+    if !app.recent_actions().is_empty() {
+        height = height.saturating_add(1);
+    }
+    height
+}
+
+pub fn run_app<B: Backend + Handover>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+    let mut last_draw = Instant::now();
+    let events = Events::spawn(app.config_path.clone());
+    let job_events = events.sender();
+    app.jobs.on_update(move || {
+        let _ = job_events.send(Ok(AppEvent::JobOutput));
+    });
+    // something visible changed since the last frame
+    let mut dirty = true;
+
+    loop {
+        // running jobs animate their spinner and elapsed time
+        if app.jobs.running_count() > 0 && last_draw.elapsed() >= SPINNER_INTERVAL {
+            dirty = true;
+        }
+        // keep relative times ("5m ago") current on an otherwise idle screen
+        dirty |= last_draw.elapsed() >= IDLE_REFRESH;
+        // otherwise nothing changed: sleep until input or the next tick
+        if dirty {
+            dirty = false;
+            last_draw = Instant::now();
+            terminal.draw(|f| draw(f, &mut app))?;
+        }
+
+        // wake up for the spinner while jobs run, else only when an event comes in
+        let timeout = if app.jobs.running_count() > 0 {
+            SPINNER_INTERVAL
+        } else {
+            IDLE_REFRESH
+        };
+
+        let Some(event) = events.next(timeout)? else {
+            continue;
+        };
+        match handle_event(&mut app, event) {
+            Step::Idle => {}
+            Step::Redraw => dirty = true,
+            Step::Run(k) => {
+                run_action(terminal, &events, &mut app, k)?;
+                dirty = true;
+            }
+            Step::Quit => {
+                app.discard_checkpoint();
+                if let Err(err) = app.save_ui_state() {
+                    tracing::warn!("could not save the UI state: {}", err);
+                }
+                return app
+                    .save_session()
+                    .map_err(|e| io::Error::other(e.to_string()));
+            }
+        }
+    }
+}
+
+/// Draw one frame of the UI
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let size = f.area();
+    app.size = size;
+    let theme = app.theme();
+    // paint the theme background first; widgets only patch styles on top of it
+    f.render_widget(Block::default().style(theme.base()), size);
+
+    // Obtain the title lines (figlet or fallback) so we can size the top (header) chunk
+    let title_lines = header_title(app, size);
+    // reserve one extra row for the subtitle (and recent strip) we append below
+    let title_height = header_height(app, title_lines.len());
+
+    // below the minimum the layout degenerates: say so until the terminal grows
+    let (min_width, min_height) = min_size(app, title_lines.len());
+    app.too_small = size.width < min_width || size.height < min_height;
+    if app.too_small {
+        draw_too_small(f, size, (min_width, min_height), theme);
+        return;
+    }
+
+    // command of the focused action, wrapped for the preview block; on short
+    // terminals it only gets the rows left over by the minimal layout
+    let mut preview = app.preview(size.width, theme);
+    preview.truncate((size.height - min_height + 1) as usize);
+    let preview_rows = preview.len();
+
+    // Layout: header (title + subtitle), middle (columns or details), footer (preview + help)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(
+            [
+                Constraint::Length(title_height),
+                Constraint::Min(MIN_BODY_HEIGHT),
+                Constraint::Length(footer_height(preview_rows)),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    // Build header content: figlet lines, subtitle and a blank line below
+    let mut title_body: Vec<Line> = Vec::new();
+    title_body.extend(title_lines.clone());
+    // subtitle from config, then the global selector
+    let mut subtitle = vec![Span::styled(app.config.app.subtitle.clone(), theme.dim())];
+    if let Some((param, option)) = app.selector_option() {
+        subtitle.push(Span::styled(format!("   {}: ", param.name), theme.dim()));
+        subtitle.push(Span::styled(
+            option.label.clone(),
+            app.option_style(option, theme),
+        ));
+        subtitle.push(Span::styled(" (g)", theme.faint()));
+    }
+    title_body.push(Line::from(subtitle));
+    // recently run actions, reachable with Alt+<n>
+    let recent = app.recent_actions();
+    if !recent.is_empty() {
+        let mut spans = vec![Span::styled("Recent: ", theme.dim())];
+        for (i, key) in recent.iter().enumerate() {
+            spans.push(Span::styled(format!("Alt+{} ", i + 1), theme.accent()));
+            if let Some(action) = app.nav.action(*key) {
+                spans.push(Span::raw(format!("{}   ", action.label)));
+            }
+        }
+        title_body.push(Line::from(spans));
+    }
+    // one empty row below subtitle
+    title_body.push(Line::from(Span::raw("")));
+
+    let header = Paragraph::new(title_body).alignment(Alignment::Center);
+    f.render_widget(header, chunks[0]);
+
+    // set while rendering the columns when the selected label doesn't fit
+    app.truncated_label = None;
+
+    // Split view: the output pane takes the right half of the middle area
+    let (main_area, output_area) = if app.split_view {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[1]);
+        (halves[0], Some(halves[1]))
+    } else {
+        (chunks[1], None)
+    };
+    if let Some(area) = output_area {
+        draw_output(
+            f,
+            area,
+            &app.jobs,
+            &app.output,
+            app.details.cursor_on,
+            app.config.app.reduced_motion,
+            theme,
+        );
+    }
+
+    // Middle area: either the columns or a details view depending on state
+    if !app.details.open {
+        // Columns layout - dynamic based on config
+        let num_columns = app.nav.columns.len();
+        let column_constraints = app.nav.constraints();
+
+        let middle_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(column_constraints)
+            .split(main_area);
+
+        // Render each column dynamically
+        for col_idx in 0..num_columns {
+            let focused = app.nav.focused_column() == col_idx;
+
+            // collapsed: just the title, one character per row
+            if app.nav.columns[col_idx].collapsed {
+                let style = if focused {
+                    theme.selected()
+                } else {
+                    theme.dim()
+                };
+                let title: Vec<Line> = app.nav.columns[col_idx]
+                    .title
+                    .chars()
+                    .map(|ch| Line::from(Span::styled(ch.to_string(), style)))
+                    .collect();
+                let strip = Paragraph::new(title)
+                    .alignment(Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL).border_style(style));
+                f.render_widget(strip, middle_chunks[col_idx]);
+                continue;
+            }
+
+            if app.nav.columns[col_idx].actions.is_empty() {
+                draw_empty_column(
+                    f,
+                    middle_chunks[col_idx],
+                    &app.nav.columns[col_idx],
+                    focused,
+                    theme,
+                );
+                continue;
+            }
+
+            if app.nav.columns[col_idx].layout == ColumnLayout::Table {
+                draw_table(f, middle_chunks[col_idx], app, col_idx, theme);
+                continue;
+            }
+
+            draw_list(f, middle_chunks[col_idx], app, col_idx, theme);
+        }
+    } else {
+        // Details view replaces the columns in the middle area while keeping header/footer
+        let area = main_area;
+
+        // Use the action label as the window title when available, with its
+        // hotkey if it has one. Add a leading and trailing space for visual padding.
+        let title_text = app
+            .nav
+            .focused_action()
+            .map(|a| match a.hotkey {
+                Some(ref hotkey) => format!(" {} ({}) ", a.label, hotkey),
+                None => format!(" {} ", a.label),
+            })
+            .unwrap_or_else(|| " Details ".to_string());
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title_text.as_str(), theme.bold()));
+        f.render_widget(block, area);
+
+        let inner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
+        };
+
+        // Build detailed content from the focused action (parameters only)
+        let mut lines: Vec<Line> = Vec::new();
+        // index in `lines` of the first line of each parameter
+        let mut param_starts: Vec<usize> = Vec::new();
+
+        if let (Some(key), Some(action)) = (app.nav.focused_key(), app.nav.focused_action()) {
+            app.details.apply_filter(&action.parameters);
+            if !action.parameters.is_empty() {
+                // "(3/12)" while filtered, so hidden parameters aren't a surprise
+                let total = action.parameters.len();
+                let count = if app.details.filter.is_empty() {
+                    format!("({})", total)
+                } else {
+                    format!("({}/{})", app.details.visible_count(), total)
+                };
+                lines.push(Line::from(vec![
+                    Span::styled("Parameters ", theme.bold()),
+                    Span::styled(count, theme.dim()),
+                    Span::styled(":", theme.bold()),
+                ]));
+
+                // filter line while typing it or once applied
+                if let Some(ref edit) = app.details.filter_edit {
+                    let mut spans = vec![Span::styled("  / ", theme.editing())];
+                    spans.extend(edit_spans(edit, app.details.cursor_on, theme.bold()));
+                    lines.push(Line::from(spans));
+                } else if !app.details.filter.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::styled("  / ", theme.accent()),
+                        Span::raw(app.details.filter.clone()),
+                        Span::styled("  Esc clear", theme.faint()),
+                    ]));
+                }
+                if !action
+                    .parameters
+                    .iter()
+                    .enumerate()
+                    .any(|(idx, _)| app.details.is_visible(idx))
+                {
+                    lines.push(Line::from(Span::styled(
+                        "  No parameter matches — press Esc to clear the filter",
+                        theme.faint(),
+                    )));
+                }
+
+                for (idx, param) in action.parameters.iter().enumerate() {
+                    if !app.details.is_visible(idx) {
+                        continue;
+                    }
+                    param_starts.push(lines.len());
+                    let required_marker = if param.required { " *" } else { "" };
+
+                    // Parameter header line; omit type suffix for selects
+                    let mut spans =
+                        vec![Span::raw("  "), Span::styled(&param.name, theme.accent())];
+                    if param.param_type == ParameterType::Select {
+                        spans.push(Span::raw(format!("{}  ", required_marker)));
+                    } else {
+                        spans.push(Span::raw(format!(" {}  ", required_marker)));
+                    }
+
+                    // If select, render options inline with highlight for selected
+                    if param.param_type == ParameterType::Select {
+                        {
+                            let sel = app.params.selected(key, idx);
+                            // Render options on a separate line under the parameter
+                            lines.push(Line::from(vec![Span::raw("    ")]));
+                            let mut opt_spans: Vec<Span> = Vec::new();
+                            for (oi, opt) in param.options.iter().enumerate() {
+                                let styled = app.option_style(opt, theme);
+
+                                if oi == sel {
+                                    // selected: bold + distinct fg
+                                    opt_spans.push(Span::styled(
+                                        format!("[{}] ", opt.label),
+                                        styled.add_modifier(Modifier::BOLD),
+                                    ));
+                                } else {
+                                    opt_spans
+                                        .push(Span::styled(format!(" {}  ", opt.label), styled));
+                                }
+                            }
+                            lines.push(Line::from(opt_spans));
+                        }
+                    } else {
+                        // for text params, show current value; when editing show the edit buffer
+                        {
+                            let val = app.params.value(key, idx);
+                            let edit = app
+                                .details
+                                .edit
+                                .as_ref()
+                                .filter(|_| idx == app.details.focused_param);
+                            let password = param.is_secret() && !secrets::is_reference(val);
+                            if let Some(edit) = edit {
+                                // show the live edit buffer with a blinking cursor
+                                spans.push(Span::raw(": "));
+                                let masked;
+                                let edit = if password {
+                                    masked = edit.masked();
+                                    &masked
+                                } else {
+                                    edit
+                                };
+                                spans.extend(edit_spans(edit, app.details.cursor_on, theme.bold()));
+                                spans.push(Span::styled(
+                                    " (editing)",
+                                    theme.dim().add_modifier(Modifier::ITALIC),
+                                ));
+                            } else if password && !val.is_empty() {
+                                spans.push(Span::raw(format!(": {}", secrets::MASK)));
+                            } else {
+                                spans.push(Span::raw(format!(": {}", val)));
+                            }
+                        }
+                    }
+
+                    // key hint next to the focused parameter
+                    if app.config.app.inline_hints && idx == app.details.focused_param {
+                        let hint = match param.param_type {
+                            ParameterType::Select => "←/→ change",
+                            _ if app.details.is_editing() => "Enter accept · Esc cancel",
+                            ParameterType::Text | ParameterType::Password => "Enter edit",
+                        };
+                        spans.push(Span::styled(format!("  {}", hint), theme.faint()));
+                    }
+
+                    // indicate focus with a pointer glyph on the start of the line
+                    if idx == app.details.focused_param {
+                        let pointer_style = if app.details.is_editing() {
+                            theme.editing()
+                        } else {
+                            theme.accent()
+                        };
+                        let mut row = vec![Span::styled("➜ ", pointer_style)];
+                        row.extend(spans);
+                        lines.push(Line::from(row));
+                    } else {
+                        lines.push(Line::from(spans));
+                    }
+
+                    // recent values of the parameter being edited
+                    if idx == app.details.focused_param {
+                        let highlighted = app.details.edit.as_ref().and_then(|e| e.suggestion);
+                        for (i, value) in app.suggestions().into_iter().enumerate() {
+                            let (marker, style) = if highlighted == Some(i) {
+                                ("    ▸ ", theme.selected())
+                            } else {
+                                ("      ", theme.dim())
+                            };
+                            lines.push(Line::from(vec![
+                                Span::styled(marker, theme.accent()),
+                                Span::styled(value, style),
+                            ]));
+                        }
+                    }
+
+                    if let Some(ref desc) = param.description {
+                        lines.push(Line::from(vec![
+                            Span::raw("    "),
+                            Span::styled(desc, theme.dim()),
+                        ]));
+                    }
+                    // validation problem, live while the value is typed
+                    if let Err(reason) = param.check(app.params.value(key, idx)) {
+                        lines.push(Line::from(vec![
+                            Span::raw("    "),
+                            Span::styled(format!("✗ {}", reason), theme.failure()),
+                        ]));
+                    }
+                }
+            } else {
+                lines.push(Line::from(Span::raw("No parameters")));
+            }
+            let (column, label) = app.action_id(key);
+            if let Some(stats) = app.state.stats_of(&column, &label) {
+                let failures = match stats.failures {
+                    0 => Span::styled("never failed", theme.dim()),
+                    n => Span::styled(format!("{} failed", n), theme.failure()),
+                };
+                lines.push(Line::from(""));
+                lines.push(Line::from(vec![
+                    Span::styled("Usage: ", theme.bold()),
+                    Span::styled(
+                        format!(
+                            "{} run{}, ",
+                            stats.runs,
+                            if stats.runs == 1 { "" } else { "s" }
+                        ),
+                        theme.dim(),
+                    ),
+                    failures,
+                    Span::styled(
+                        format!(", {} on average", format_elapsed(stats.average())),
+                        theme.dim(),
+                    ),
+                ]));
+            }
+        } else {
+            lines.push(Line::from(Span::raw("No action selected")));
+        }
+
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            " Press r to run or Esc to return to the main page ",
+            theme.faint(),
+        )));
+
+        // rows each line takes once wrapped, to scroll the focused parameter into view
+        let width = (inner.width as usize).max(1);
+        let mut row_starts = Vec::with_capacity(lines.len() + 1);
+        let mut rows = 0;
+        for line in &lines {
+            row_starts.push(rows);
+            rows += line.width().max(1).div_ceil(width);
+        }
+        row_starts.push(rows);
+        let param_rows: Vec<usize> = param_starts.iter().map(|&i| row_starts[i]).collect();
+        app.details.layout(param_rows, rows, inner.height as usize);
+
+        let text = Paragraph::new(lines)
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: true })
+            .scroll((app.details.scroll as u16, 0));
+        f.render_widget(text, inner);
+        draw_scrollbar(
+            f,
+            area,
+            rows,
+            inner.height as usize,
+            app.details.scroll,
+            theme,
+        );
+    }
+
+    // Footer area: preview + help. Always present even when details are shown
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(preview_rows as u16 + 2),
+                Constraint::Length(1),
+                Constraint::Length(3),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[2]);
+
+    // Draw bordered preview and render the wrapped command inside
+    let preview_area = bottom_chunks[0];
+    let preview_title = if app.details.command_edit.is_some() {
+        " Preview (editing: Enter accept · Esc cancel) "
+    } else if app.details.command_override.is_some() {
+        " Preview (edited) "
+    } else {
+        " Preview "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(preview_title, theme.bold()))
+        .title_alignment(Alignment::Left);
+    f.render_widget(block, preview_area);
+
+    let inner = Rect {
+        x: preview_area.x + 1,
+        y: preview_area.y + 1,
+        width: preview_area.width.saturating_sub(2),
+        height: preview_area.height.saturating_sub(2),
+    };
+    f.render_widget(Paragraph::new(preview).alignment(Alignment::Left), inner);
+
+    // Help bar content, for the current mode
+    let help_text = help_text(app);
+
+    // If the help area is tall enough, render a bordered block and draw the
+    // help text inside the block inner rect. Otherwise render the help line
+    // directly (no border) so it remains visible on small terminals.
+    // One-line status bar between the preview and the help
+    f.render_widget(Paragraph::new(status_line(app, theme)), bottom_chunks[1]);
+
+    let help_area = bottom_chunks[2];
+    if help_area.height >= 3 {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(" Help ", theme.bold()));
+        f.render_widget(block, help_area);
+
+        let inner = Rect {
+            x: help_area.x + 1,
+            y: help_area.y + 1,
+            width: help_area.width.saturating_sub(2),
+            height: help_area.height.saturating_sub(2),
+        };
+        let inner_para = Paragraph::new(vec![Line::from(vec![
+            Span::raw("  "),
+            Span::styled(help_text, theme.dim()),
+            Span::raw("  "),
+        ])])
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+        f.render_widget(inner_para, inner);
+    } else {
+        // cramped: render help text plainly so it's visible
+        let compact = Paragraph::new(vec![Line::from(vec![
+            Span::raw("  "),
+            Span::styled(help_text, theme.dim()),
+            Span::raw("  "),
+        ])])
+        .alignment(Alignment::Left);
+        f.render_widget(compact, help_area);
+    }
+
+    if !app.toasts.is_empty() {
+        draw_toasts(f, chunks[1], &app.toasts, theme);
+    }
+
+    if let Some(ref modal) = app.modal {
+        modal.render(f, size, theme);
+    }
+}
+
+/// What the main loop does after an event
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    // nothing visible changed
+    Idle,
+    Redraw,
+    // run an action in the foreground, which needs the terminal
+    Run(ActionKey),
+    Quit,
+}
+
+/// Apply an event to the app; everything but running commands in the foreground
+/// happens here, so tests can drive the app without a terminal
+pub fn handle_event(app: &mut App, event: AppEvent) -> Step {
+    let changed = match event {
+        AppEvent::Key(key) => return handle_key(app, key),
+        AppEvent::Tick => {
+            if let Some(step) = app.fire_countdown() {
+                return step;
+            }
+            app.checkpoint();
+            let mut changed = app.toasts.prune();
+            changed |= app.run_watches();
+            changed |= app.modal.as_ref().is_some_and(Modal::is_live) || app.countdown.is_some();
+            if app.follow {
+                app.follow_jobs();
+                changed = true;
+            }
+            // toggle blinking cursor when in edit mode; reduced motion keeps it steady
+            if app.config.app.reduced_motion {
+                app.details.cursor_on = true;
+            } else {
+                app.details.cursor_on = !app.details.cursor_on;
+                changed |= app.mode() == Mode::Edit;
+            }
+            changed
+        }
+        AppEvent::JobOutput => app.poll_jobs(),
+        AppEvent::ConfigReloaded(config) => {
+            app.apply_reload(config);
+            true
+        }
+        // the wheel moves the selection like Up/Down
+        AppEvent::Mouse(mouse) => {
+            let delta = match mouse.kind {
+                MouseEventKind::ScrollUp => -1,
+                MouseEventKind::ScrollDown => 1,
+                _ => return Step::Idle,
+            };
+            if app.modal.is_some() || app.mode() == Mode::Edit {
+                return Step::Idle;
+            }
+            match (app.details.open, delta) {
+                (true, -1) => app.details.focus_prev(),
+                (true, _) => app.details.focus_next(),
+                (false, _) => app.nav.move_selection(delta),
+            }
+            true
+        }
+        // the next frame picks up the new size
+        AppEvent::Resize => true,
+        AppEvent::Paste(text) => app.paste(&text),
+        AppEvent::Control(request, reply) => {
+            // the client may have hung up; nothing to report then
+            let _ = reply.send(app.handle_control(request));
+            true
+        }
+        // jobs would outlive the UI reporting them; the session is saved on quit
+        AppEvent::Signal(signal) => {
+            tracing::info!(signal, "exiting on signal");
+            app.jobs.kill_running();
+            return Step::Quit;
+        }
+    };
+    if changed {
+        Step::Redraw
+    } else {
+        Step::Idle
+    }
+}
+
+// Edit buffer with its blinking cursor: an underscore after the last character,
+// else the character under it in reverse video
+fn edit_spans(edit: &EditState, cursor_on: bool, style: Style) -> Vec<Span<'static>> {
+    let (before, under, after) = edit.split();
+    let cursor = match (under, cursor_on) {
+        (None, true) => Span::styled("_", style),
+        (None, false) => Span::styled(" ", style),
+        (Some(ch), true) => Span::styled(ch.to_string(), style.add_modifier(Modifier::REVERSED)),
+        (Some(ch), false) => Span::styled(ch.to_string(), style),
+    };
+    vec![
+        Span::styled(before.to_string(), style),
+        cursor,
+        Span::styled(after.to_string(), style),
+    ]
+}
+
+fn is_paste_key(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('v') => key.modifiers.contains(KeyModifiers::CONTROL),
+        KeyCode::Insert => key.modifiers.contains(KeyModifiers::SHIFT),
+        _ => false,
+    }
+}
+
+// A key pressed with Ctrl or Alt, which doesn't type its character
+fn is_chord(key: &KeyEvent) -> bool {
+    key.modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// Apply a key press to the app
+fn handle_key(app: &mut App, key: KeyEvent) -> Step {
+    // what is typed into a secret stays out of the log
+    let secret =
+        app.details.edit.is_some() && app.focused_param().is_some_and(|(_, p)| p.is_secret());
+    if !secret {
+        tracing::debug!(code = ?key.code, modifiers = ?key.modifiers, "key");
+    }
+    // nothing is visible to act on while the terminal is too small
+    if app.too_small && key.code != KeyCode::Char('q') {
+        return Step::Redraw;
+    }
+    // An open modal captures all keys until dismissed
+    if let Some(ref mut modal) = app.modal {
+        match modal.handle_key(key) {
+            ModalOutcome::Stay => {}
+            ModalOutcome::Close => app.modal = None,
+            ModalOutcome::Answered(question, yes) => {
+                app.modal = None;
+                return app.answer(question, yes);
+            }
+            ModalOutcome::Entered(prompt, text) => {
+                app.modal = None;
+                app.entered(prompt, &text);
+            }
+            ModalOutcome::Picked(pick, name) => {
+                app.modal = None;
+                return app.picked(pick, &name);
+            }
+        }
+        return Step::Redraw;
+    }
+
+    // hidden debug overlay, reachable from any mode
+    if key.code == KeyCode::F(12) {
+        app.modal = Some(Modal::new(LogModal));
+        return Step::Redraw;
+    }
+
+    // reload the config and what its providers print, from any mode unless an
+    // action is bound to F5
+    let bound = !app.details.open && app.hotkey_action(&key).is_some();
+    if key.code == KeyCode::F(5) && !bound {
+        app.refresh_config();
+        return Step::Redraw;
+    }
+
+    // Ctrl+V and Shift+Insert paste the system clipboard, for terminals that
+    // don't paste by themselves
+    let editing = app.details.edit.is_some()
+        || app.details.filter_edit.is_some()
+        || app.output.filter_edit.is_some()
+        || app.details.command_edit.is_some();
+    if editing && is_paste_key(&key) {
+        match clipboard::contents() {
+            Ok(text) => {
+                app.paste(&text);
+            }
+            Err(err) => app
+                .toasts
+                .error(format!("Could not read the clipboard: {}", err)),
+        }
+        return Step::Redraw;
+    }
+
+    // Editing the final command: the buffer replaces the substituted command
+    if let Some(ref mut edit) = app.details.command_edit {
+        if edit.apply_key(key) {
+            return Step::Redraw;
+        }
+        match key.code {
+            KeyCode::Char(ch) if !is_chord(&key) => edit.insert(ch),
+            KeyCode::Enter => {
+                app.details.command_override = Some(edit.buffer.clone());
+                app.details.command_edit = None;
+            }
+            KeyCode::Esc => app.details.command_edit = None,
+            _ => {}
+        }
+        return Step::Redraw;
+    }
+
+    // Typing the output filter: the pane narrows as it changes
+    if let Some(ref mut edit) = app.output.filter_edit {
+        if !edit.apply_key(key) {
+            match key.code {
+                KeyCode::Char(ch) if !is_chord(&key) => edit.insert(ch),
+                // restore the filter applied before f
+                KeyCode::Esc => edit.buffer = edit.original.clone(),
+                _ => {}
+            }
+        }
+        let filter = edit.buffer.clone();
+        if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+            app.output.filter_edit = None;
+        }
+        app.output.set_filter(&filter);
+        return Step::Redraw;
+    }
+
+    // Typing the parameter filter: the list narrows as it changes
+    if let Some(ref mut edit) = app.details.filter_edit {
+        if !edit.apply_key(key) {
+            match key.code {
+                KeyCode::Char(ch) if !is_chord(&key) => edit.insert(ch),
+                KeyCode::Enter => {}
+                // restore the filter applied before `/`
+                KeyCode::Esc => edit.buffer = edit.original.clone(),
+                _ => {}
+            }
+        }
+        app.details.filter = edit.buffer.clone();
+        if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+            app.details.filter_edit = None;
+        }
+        if let Some(action) = app.nav.focused_action() {
+            let params = action.parameters.clone();
+            app.details.apply_filter(&params);
+        }
+        return Step::Redraw;
+    }
+
+    // If we're in text edit mode, handle editing keys separately
+    let suggestions = app.suggestions();
+    if let Some(ref mut edit) = app.details.edit {
+        let pidx = app.details.focused_param;
+        let key_ref = app.nav.focused_key();
+        let changed = if edit.apply_key(key) {
+            true
+        } else {
+            match key.code {
+                KeyCode::Char(ch) if !is_chord(&key) => {
+                    edit.insert(ch);
+                    true
+                }
+                // commands run in callbot's directory, so paths complete from there
+                KeyCode::Tab | KeyCode::BackTab => {
+                    edit.complete_path(Path::new("."), key.code == KeyCode::BackTab)
+                }
+                // Up/Down walk the recent values, Enter takes the highlighted one
+                KeyCode::Up | KeyCode::Down => {
+                    let delta = if key.code == KeyCode::Up { -1 } else { 1 };
+                    edit.step_suggestion(delta, suggestions.len());
+                    false
+                }
+                KeyCode::Enter if edit.suggestion.is_some() => {
+                    let value = edit.suggestion.and_then(|i| suggestions.get(i));
+                    if let (Some(value), Some(k)) = (value, key_ref) {
+                        app.params.set_value(k, pidx, value.clone());
+                    }
+                    app.details.edit = None;
+                    false
+                }
+                KeyCode::Enter => {
+                    // accept edit
+                    app.details.edit = None;
+                    false
+                }
+                KeyCode::Esc => {
+                    // cancel edit, revert original value
+                    if let Some(k) = key_ref {
+                        app.params.set_value(k, pidx, edit.original.clone());
+                    }
+                    app.details.edit = None;
+                    false
+                }
+                // unbound keys change nothing
+                _ => return Step::Idle,
+            }
+        };
+        // the parameter value follows the buffer as it is typed
+        if let (true, Some(k), Some(edit)) = (changed, key_ref, &app.details.edit) {
+            app.params.set_value(k, pidx, edit.buffer.clone());
+        }
+        return Step::Redraw;
+    }
+
+    // per-action hotkeys from the config work anywhere in browse mode
+    if !app.details.open {
+        if let Some(k) = app.hotkey_action(&key) {
+            if app.follow {
+                app.toasts.info("Follow mode is read-only");
+            } else if let Some((pidx, reason)) = app.first_invalid(k) {
+                app.prompt_invalid(k, pidx, &reason);
+            } else {
+                return app.launch(k, false);
+            }
+            return Step::Redraw;
+        }
+    }
+
+    match key.code {
+        KeyCode::Char('q') => {
+            return Step::Quit;
+        }
+        KeyCode::Char('?') => app.modal = Some(Modal::new(HelpModal)),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.open_history(),
+        KeyCode::Char('t') => app.cycle_theme(),
+        KeyCode::Char('g') => app.cycle_selector(1),
+        KeyCode::Char('G') => app.cycle_selector(-1),
+        KeyCode::Char('o') => app.split_view = !app.split_view,
+        KeyCode::Char('K') => app.abort_jobs(),
+        KeyCode::Char('y') if app.split_view => app.copy_output(),
+        KeyCode::Char('S') if app.split_view => app.open_output_export(),
+        KeyCode::Char('c') if app.split_view => app.output.diff = !app.output.diff,
+        KeyCode::Char('f') if app.split_view => {
+            app.output.filter_edit = Some(EditState::new(app.output.filter.clone()));
+        }
+        KeyCode::Char('F') if app.split_view => app.output.invert = !app.output.invert,
+        KeyCode::Char('Z') if app.split_view => app.output.cycle_zoom(&app.jobs),
+        // Alt+Up/Down move the selected action within its column
+        KeyCode::Up | KeyCode::Down
+            if key.modifiers.contains(KeyModifiers::ALT) && !app.details.open =>
+        {
+            app.move_action(if key.code == KeyCode::Up { -1 } else { 1 });
+        }
+        // Alt+<n> opens the nth recently run action
+        KeyCode::Char(ch @ '1'..='9')
+            if key.modifiers.contains(KeyModifiers::ALT) && !app.details.open =>
+        {
+            app.open_recent(ch as usize - '1' as usize);
+        }
+        // <n> focuses the nth column
+        KeyCode::Char(ch @ '1'..='9') if !app.details.open => {
+            app.nav.focus_column(ch as usize - '1' as usize);
+        }
+        // collapse/expand the focused column
+        KeyCode::Char('z') if !app.details.open => app.nav.toggle_collapsed(),
+        KeyCode::Char('s') if !app.details.open => app.cycle_sort(),
+        KeyCode::Char(' ') if !app.details.open => app.toggle_marked(),
+        KeyCode::Char('R') if !app.details.open => {
+            if app.follow {
+                app.toasts.info("Follow mode is read-only");
+            } else {
+                app.run_batch();
+            }
+        }
+        // Ctrl+Left/Right resize the focused column
+        KeyCode::Left | KeyCode::Right
+            if key.modifiers.contains(KeyModifiers::CONTROL) && !app.details.open =>
+        {
+            app.nav.resize_focused(key.code == KeyCode::Right);
+        }
+        // </> flip the first select of the action without opening the details
+        KeyCode::Char(ch @ ('<' | '>')) if !app.details.open => {
+            app.step_first_select(if ch == '<' { -1 } else { 1 });
+        }
+        // Left/Right move between columns in browse mode
+        KeyCode::Left if !app.details.open => app.nav.move_column(-1),
+        KeyCode::Right if !app.details.open => app.nav.move_column(1),
+        // Only switch columns when details view is not open
+        KeyCode::Tab if !app.details.open => app.nav.focus_next_column(),
+        KeyCode::BackTab if !app.details.open => app.nav.focus_prev_column(),
+        KeyCode::Up => {
+            if app.details.open {
+                app.details.focus_prev();
+            } else {
+                app.nav.move_selection(-1)
+            }
+        }
+        KeyCode::Down => {
+            if app.details.open {
+                app.details.focus_next();
+            } else {
+                app.nav.move_selection(1)
+            }
+        }
+        KeyCode::Left | KeyCode::Right if app.details.open => {
+            let delta = if key.code == KeyCode::Left { -1 } else { 1 };
+            if let Some((k, param)) = app.focused_param() {
+                if param.param_type == ParameterType::Select {
+                    let param = param.clone();
+                    app.params
+                        .step_option(k, app.details.focused_param, &param, delta);
+                    app.refresh_options(k, Some(app.details.focused_param));
+                }
+            }
+        }
+        // In the details view PageUp/PageDown move the focus by a screen
+        KeyCode::PageUp if app.details.open => app.details.page_up(),
+        KeyCode::PageDown if app.details.open => app.details.page_down(),
+        KeyCode::PageUp => {
+            let page = page_size(app, app.size);
+            app.nav.move_selection(-(page as isize));
+        }
+        KeyCode::PageDown => {
+            let page = page_size(app, app.size);
+            app.nav.move_selection(page as isize);
+        }
+        // jump to top/bottom (only when not showing details)
+        KeyCode::Home if !app.details.open => app.nav.select_first(),
+        KeyCode::End if !app.details.open => app.nav.select_last(),
+        KeyCode::Enter => {
+            // If details view is not shown, open it. If it is shown and the
+            // focused parameter is text, enter edit mode.
+            if !app.details.open {
+                if app.nav.focused_action().is_some() {
+                    app.open_details();
+                }
+            } else if let Some((k, param)) = app.focused_param() {
+                if param.param_type.is_typed() {
+                    let original = app.params.value(k, app.details.focused_param).to_string();
+                    app.details.edit = Some(EditState::new(original));
+                }
+            }
+        }
+        // narrow the parameters by name or description
+        KeyCode::Char('/') if app.details.open => {
+            app.details.filter_edit = Some(EditState::new(app.details.filter.clone()));
+        }
+        // drop the filter first, then close the details view
+        KeyCode::Esc if app.details.open && !app.details.filter.is_empty() => {
+            app.details.filter.clear();
+            if let Some(action) = app.nav.focused_action() {
+                let params = action.parameters.clone();
+                app.details.apply_filter(&params);
+            }
+        }
+        // close details view if open
+        KeyCode::Esc if app.details.open => app.details.close(),
+        // a follower never runs anything
+        KeyCode::Char('r') | KeyCode::Char('b') | KeyCode::Char('D') | KeyCode::Char('w')
+            if app.follow =>
+        {
+            app.toasts.info("Follow mode is read-only");
+        }
+        KeyCode::Char('d') if app.details.open => {
+            if let Some((key, param)) = app.focused_param() {
+                let (name, param) = (param.name.clone(), param.clone());
+                app.params.reset(key, app.details.focused_param, &param);
+                app.toasts.info(format!("{} reset to its default", name));
+            }
+        }
+        KeyCode::Char('w') => {
+            if let Some(k) = app.nav.focused_key() {
+                if let Some((pidx, reason)) = app.first_invalid(k) {
+                    app.prompt_invalid(k, pidx, &reason);
+                } else {
+                    app.toggle_watch(k);
+                }
+            }
+        }
+        KeyCode::Char('p') if app.details.open => app.open_presets(),
+        KeyCode::Char('x') if app.details.open => app.open_export(),
+        KeyCode::Char('P') if app.details.open => {
+            let has_params = app
+                .nav
+                .focused_action()
+                .is_some_and(|a| !a.parameters.is_empty());
+            if !has_params {
+                app.toasts.info("This action has no parameters to save");
+                return Step::Redraw;
+            }
+            app.modal = Some(Modal::new(PromptModal::new(
+                Prompt::PresetName,
+                "Save preset",
+                "Name of the preset for the current values:",
+            )));
+        }
+        // tweak the final command before running it
+        KeyCode::Char('e') if app.details.open => {
+            if let Some(k) = app.nav.focused_key() {
+                let command = app.editable_command(k);
+                app.details.command_edit = Some(EditState::new(command));
+            }
+        }
+        // run the substituted command (or its dry-run variant), from the details or
+        // straight from the list; invalid values are pointed at instead of running
+        KeyCode::Char('r') | KeyCode::Char('b') | KeyCode::Char('D') => {
+            if let Some(k) = app.nav.focused_key() {
+                if let Some((pidx, reason)) = app.first_invalid(k) {
+                    app.prompt_invalid(k, pidx, &reason);
+                } else if key.code == KeyCode::Char('D') {
+                    app.start_dry_run(k);
+                } else {
+                    return app.launch(k, key.code == KeyCode::Char('b'));
+                }
+            }
+        }
+        _ => return Step::Idle,
+    }
+    Step::Redraw
+}
+
+// Run an action in the foreground: in simulate mode its fake output is shown in a
+// popup, otherwise the terminal is handed over and the process exits with the
+// command's status
+fn run_action<B: Backend + Handover>(
+    terminal: &mut Terminal<B>,
+    events: &Events,
+    app: &mut App,
+    k: ActionKey,
+) -> io::Result<()> {
+    let Some(action) = app.nav.action(k) else {
+        return Ok(());
+    };
+    let label = action.label.clone();
+    if app.simulate {
+        let cmd = app.final_command(k);
+        let (output, code) = simulate_command(&cmd, action.simulate.as_ref());
+        app.modal = Some(Modal::new(MessageModal {
+            title: format!("Simulated: {}", label),
+            text: format!("$ {}\n\n{}\n\nexit {}", app.shown_command(k), output, code),
+            is_error: code != 0,
+        }));
+        return Ok(());
+    }
+
+    let shown = app.shown_command(k);
+    let Some(cmd) = app.run_command(k) else {
+        return Ok(());
+    };
+    // persist before handing over the terminal in case the command never returns
+    app.record_run(k);
+    if let Err(err) = app.save_session() {
+        app.toasts.error(format!("Could not save session: {}", err));
+    }
+    if let Err(err) = app.save_ui_state() {
+        tracing::warn!("could not save the UI state: {}", err);
+    }
+    // the process exits with the command; taken again if it can't start
+    app.discard_checkpoint();
+    // the command reads the keyboard now, not the input thread
+    events.pause();
+    let started = Instant::now();
+    let result = run_command(terminal, &app.config, &label, &cmd, &shown);
+    let mut entry = Entry::new(&app.action_id(k).0, &label, &shown, started.elapsed());
+    entry.exit_code = result.as_ref().ok().map(|&status| exit_code(status));
+    if let Some(ref transcript) = app.transcript {
+        if let Err(err) = transcript.append(&entry) {
+            // the terminal belongs to the command's shell by now
+            eprintln!("Could not record the run: {}", err);
+        }
+    }
+    let posts = webhook::notify(&app.config.webhooks, &entry);
+    match result {
+        Ok(status) => {
+            // the process exits below, which would cut the posts short
+            for post in posts {
+                let _ = post.join();
+            }
+            let code = exit_code(status);
+            app.record_stats(k, code, started.elapsed());
+            app.record_exit(code);
+            // Do not re-enter the TUI. Exit the process with the same status code so
+            // the user remains in the spawned shell environment after the command.
+            std::process::exit(code);
+        }
+        Err(err) => {
+            // the command never started: take the terminal back and report it
+            enter_tui(terminal)?;
+            events.resume();
+            // the error names the command with its secrets masked
+            app.modal = Some(Modal::new(MessageModal::error(
+                err.title(),
+                err.to_string(),
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Number of list rows that fit in the middle area of a `height` rows terminal
+fn page_size(app: &App, size: Rect) -> usize {
+    let title_lines = header_title(app, size);
+    let title_height = header_height(app, title_lines.len());
+    let footer = footer_height(app.preview(size.width, app.theme()).len());
+    // account for outer margin (1 top + 1 bottom)
+    let middle_height = size
+        .height
+        .saturating_sub(2)
+        .saturating_sub(title_height)
+        .saturating_sub(footer);
+    // inner height minus block borders
+    middle_height.saturating_sub(2).max(1) as usize
+}
+
+// Stack toasts in the bottom-right corner of `area`, newest at the bottom
+fn draw_toasts(f: &mut Frame, area: Rect, toasts: &Toasts, theme: &Theme) {
+    let max_width = (area.width / 2).max(20).min(area.width);
+    let mut bottom = area.y + area.height;
+    for toast in toasts.iter().collect::<Vec<_>>().into_iter().rev() {
+        if bottom < area.y + 3 {
+            break;
+        }
+        let width = (toast.message.chars().count() as u16 + 4).min(max_width);
+        let rect = Rect {
+            x: area.x + area.width - width,
+            y: bottom - 3,
+            width,
+            height: 3,
+        };
+        let border = match toast.level {
+            ToastLevel::Info => theme.accent(),
+            ToastLevel::Error => theme.failure(),
+        };
+        let widget = Paragraph::new(Span::raw(toast.message.clone()))
+            .block(Block::default().borders(Borders::ALL).border_style(border))
+            .style(theme.base());
+        f.render_widget(Clear, rect);
+        f.render_widget(widget, rect);
+        bottom -= 3;
+    }
+}
@@ -0,0 +1,716 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use std::time::Instant;
+
+use super::app_state::EditState;
+use super::theme::Theme;
+use super::{edit_spans, is_chord};
+use crate::logging;
+
+/// What the event loop should do after a modal handled a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModalOutcome {
+    /// Keep the modal open
+    Stay,
+    /// Dismiss the modal
+    Close,
+    /// Dismiss the modal and act on the answer to its question
+    Answered(Question, bool),
+    /// Dismiss the modal and use the text typed in it
+    Entered(Prompt, String),
+    /// Dismiss the modal and act on the entry chosen, by name
+    Picked(Pick, String),
+}
+
+/// What a `ConfirmModal` asks, so the app knows what to do with the answer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Question {
+    // restore the values a crashed instance left, see `Recovery`
+    RestoreEdits,
+    // let the countdown of a `delay_secs` action run out; only ever answered no
+    DelayedRun,
+    // run an action with `confirm = "typed"`, its phrase typed out
+    TypedRun,
+}
+
+/// What a `PromptModal` asks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt {
+    // name to save the focused action's values under, see `Preset`
+    PresetName,
+    // file to export the focused action's command to, see `export::script`
+    ScriptPath,
+    // file to save the output of a job to, by job id
+    OutputPath(usize),
+}
+
+/// What choosing an entry of a `PickModal` does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pick {
+    ApplyPreset,
+    DeletePreset,
+    // "script" or "alias", see `export`
+    Export,
+    // run a command of the history again, by command line
+    RerunHistory,
+    // fill the details of its action with the values of a history entry
+    FillHistory,
+}
+
+/// Content and key handling of a popup. The framework takes care of placement,
+/// clearing what's underneath, the border and routing every key here while open.
+pub trait ModalView {
+    fn title(&self) -> String;
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>>;
+
+    /// Esc dismisses by default; views add their own keys on top
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Esc => ModalOutcome::Close,
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    /// Width and height as a percentage of the screen
+    fn size(&self) -> (u16, u16) {
+        (60, 40)
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.text()
+    }
+
+    /// Hint shown on the last line
+    fn footer(&self) -> &'static str {
+        "Esc to close"
+    }
+
+    /// A growing log: only its last lines that fit are drawn, unwrapped, and the
+    /// modal is redrawn on every tick
+    fn follows_tail(&self) -> bool {
+        false
+    }
+}
+
+/// A popup drawn on top of the whole UI that captures all keys until dismissed
+pub struct Modal {
+    view: Box<dyn ModalView>,
+}
+
+impl Modal {
+    pub fn new(view: impl ModalView + 'static) -> Self {
+        Self {
+            view: Box::new(view),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        self.view.handle_key(key)
+    }
+
+    /// Whether the content changes by itself and needs redrawing on ticks
+    pub fn is_live(&self) -> bool {
+        self.view.follows_tail()
+    }
+
+    pub fn render(&self, f: &mut Frame, size: Rect, theme: &Theme) {
+        let (percent_x, percent_y) = self.view.size();
+        let area = centered_rect(percent_x, percent_y, size);
+
+        let mut lines = self.view.lines(theme);
+        if self.view.follows_tail() {
+            // borders, the blank line and the footer take four rows
+            let room = area.height.saturating_sub(4) as usize;
+            lines.drain(..lines.len().saturating_sub(room));
+        }
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(self.view.footer(), theme.faint())));
+
+        let mut popup = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.view.border_style(theme))
+                    .title(Span::styled(
+                        format!(" {} ", self.view.title()),
+                        theme.bold(),
+                    ))
+                    .title_alignment(Alignment::Center),
+            )
+            .style(theme.base());
+        if !self.view.follows_tail() {
+            popup = popup.wrap(Wrap { trim: false });
+        }
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+}
+
+/// A plain message, e.g. an error that needs more room than a toast
+pub struct MessageModal {
+    pub title: String,
+    pub text: String,
+    pub is_error: bool,
+}
+
+impl MessageModal {
+    pub fn error(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            is_error: true,
+        }
+    }
+
+    pub fn info(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            is_error: false,
+            ..Self::error(title, text)
+        }
+    }
+}
+
+impl ModalView for MessageModal {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let style = if self.is_error {
+            theme.failure()
+        } else {
+            theme.text()
+        };
+        self.text
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), style)))
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => ModalOutcome::Close,
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        if self.is_error {
+            theme.failure()
+        } else {
+            theme.text()
+        }
+    }
+
+    fn footer(&self) -> &'static str {
+        "Enter or Esc to close"
+    }
+}
+
+/// A yes/no question: y or Enter accepts, n or Esc declines
+pub struct ConfirmModal {
+    pub question: Question,
+    pub title: String,
+    pub text: String,
+}
+
+impl ModalView for ConfirmModal {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        self.text
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), theme.text())))
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => ModalOutcome::Answered(self.question, true),
+            KeyCode::Char('n') | KeyCode::Esc => ModalOutcome::Answered(self.question, false),
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.accent()
+    }
+
+    fn footer(&self) -> &'static str {
+        "y/Enter: yes   n/Esc: no"
+    }
+}
+
+/// A line of text to type: Enter hands it over unless blank, Esc cancels
+pub struct PromptModal {
+    pub prompt: Prompt,
+    pub title: String,
+    pub text: String,
+    pub input: EditState,
+}
+
+impl PromptModal {
+    pub fn new(prompt: Prompt, title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            prompt,
+            title: title.into(),
+            text: text.into(),
+            input: EditState::new(String::new()),
+        }
+    }
+
+    /// Start with `value` typed in
+    pub fn with_input(mut self, value: impl Into<String>) -> Self {
+        self.input = EditState::new(value.into());
+        self
+    }
+}
+
+impl ModalView for PromptModal {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line> = self
+            .text
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), theme.text())))
+            .collect();
+        lines.push(Line::from(""));
+        let mut input = vec![Span::styled("> ", theme.accent())];
+        input.extend(edit_spans(&self.input, true, theme.bold()));
+        lines.push(Line::from(input));
+        lines
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        if self.input.apply_key(key) {
+            return ModalOutcome::Stay;
+        }
+        match key.code {
+            KeyCode::Char(ch) if !is_chord(&key) => self.input.insert(ch),
+            KeyCode::Enter if !self.input.buffer.trim().is_empty() => {
+                return ModalOutcome::Entered(self.prompt, self.input.buffer.trim().to_string())
+            }
+            KeyCode::Esc => return ModalOutcome::Close,
+            _ => {}
+        }
+        ModalOutcome::Stay
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (50, 30)
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.accent()
+    }
+
+    fn footer(&self) -> &'static str {
+        "Enter: ok   Esc: cancel"
+    }
+}
+
+/// An entry of a `PickModal`
+pub struct PickItem {
+    pub name: String,
+    // shown after the name, dimmed
+    pub detail: String,
+    // whether Delete applies to it
+    pub deletable: bool,
+}
+
+/// A list to choose from with Up/Down and Enter; Delete removes the highlighted
+/// entry when the list allows it
+pub struct PickModal {
+    pub title: String,
+    pub items: Vec<PickItem>,
+    pub selected: usize,
+    // what Enter and Delete do with the highlighted entry
+    pub pick: Pick,
+    pub delete: Option<Pick>,
+}
+
+impl ModalView for PickModal {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let (marker, style) = if i == self.selected {
+                    ("► ", theme.selected())
+                } else {
+                    ("  ", theme.text())
+                };
+                Line::from(vec![
+                    Span::styled(format!("{}{}", marker, item.name), style),
+                    Span::styled(format!("  {}", item.detail), theme.faint()),
+                ])
+            })
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        let Some(item) = self.items.get(self.selected) else {
+            return ModalOutcome::Close;
+        };
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(self.items.len() - 1),
+            KeyCode::Enter => return ModalOutcome::Picked(self.pick, item.name.clone()),
+            KeyCode::Delete if item.deletable => {
+                if let Some(delete) = self.delete {
+                    return ModalOutcome::Picked(delete, item.name.clone());
+                }
+            }
+            KeyCode::Esc => return ModalOutcome::Close,
+            _ => {}
+        }
+        ModalOutcome::Stay
+    }
+
+    fn footer(&self) -> &'static str {
+        if self.delete.is_some() {
+            "Enter: choose   Delete: remove   Esc: close"
+        } else {
+            "Enter: choose   Esc: close"
+        }
+    }
+}
+
+// matches listed by a `HistoryModal`
+const MAX_MATCHES: usize = 15;
+
+/// Incremental search over the commands run before (Ctrl+R), newest first among
+/// equally good matches
+pub struct HistoryModal {
+    // command and label of each entry, newest first
+    pub entries: Vec<(String, String)>,
+    pub query: EditState,
+    // position in the matches
+    pub selected: usize,
+}
+
+impl HistoryModal {
+    pub fn new(entries: Vec<(String, String)>) -> Self {
+        Self {
+            entries,
+            query: EditState::new(String::new()),
+            selected: 0,
+        }
+    }
+
+    // Entries matching the query, best first
+    fn matches(&self) -> Vec<&(String, String)> {
+        let mut scored: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| Some((fuzzy_score(&self.query.buffer, &entry.0)?, i, entry)))
+            .collect();
+        scored.sort_by_key(|&(score, i, _)| (score, i));
+        scored.into_iter().map(|(_, _, entry)| entry).collect()
+    }
+}
+
+impl ModalView for HistoryModal {
+    fn title(&self) -> String {
+        "History".to_string()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let mut input = vec![Span::styled("search: ", theme.accent())];
+        input.extend(edit_spans(&self.query, true, theme.bold()));
+        let mut lines = vec![Line::from(input), Line::from("")];
+        let matches = self.matches();
+        if matches.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No matching command",
+                theme.faint(),
+            )));
+        }
+        for (i, (command, label)) in matches.into_iter().take(MAX_MATCHES).enumerate() {
+            let (marker, style) = if i == self.selected {
+                ("► ", theme.selected())
+            } else {
+                ("  ", theme.text())
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}{}", marker, command), style),
+                Span::styled(format!("  {}", label), theme.faint()),
+            ]));
+        }
+        lines
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        let count = self.matches().len().min(MAX_MATCHES);
+        let ctrl_r =
+            key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            // like the shell, Ctrl+R again goes to the next older match
+            _ if ctrl_r => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Tab => {
+                let Some((command, _)) = self.matches().get(self.selected).copied() else {
+                    return ModalOutcome::Stay;
+                };
+                let pick = if key.code == KeyCode::Enter {
+                    Pick::RerunHistory
+                } else {
+                    Pick::FillHistory
+                };
+                return ModalOutcome::Picked(pick, command.clone());
+            }
+            KeyCode::Esc => return ModalOutcome::Close,
+            KeyCode::Char(ch) if !is_chord(&key) => {
+                self.query.insert(ch);
+                self.selected = 0;
+            }
+            _ => {
+                if self.query.apply_key(key) {
+                    self.selected = 0;
+                }
+            }
+        }
+        ModalOutcome::Stay
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (80, 60)
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.accent()
+    }
+
+    fn footer(&self) -> &'static str {
+        "Enter: run again   Tab: fill in   Ctrl+R/↑/↓: move   Esc: close"
+    }
+}
+
+/// How well `query` matches `text` as a subsequence of its characters, ignoring
+/// case and spaces in the query: the characters skipped between matched ones, so
+/// lower is better. None when it does not match.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut score = 0;
+    let mut last = None;
+    for wanted in query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let (at, _) = chars.find(|&(_, c)| c == wanted)?;
+        if let Some(last) = last {
+            score += at - last - 1;
+        }
+        last = Some(at);
+    }
+    Some(score)
+}
+
+/// Countdown of an action with `delay_secs` before it runs; Esc cancels the run
+pub struct CountdownModal {
+    pub label: String,
+    pub command: String,
+    pub fires_at: Instant,
+}
+
+impl ModalView for CountdownModal {
+    fn title(&self) -> String {
+        format!("Running {}", self.label)
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let left = self.fires_at.saturating_duration_since(Instant::now());
+        let secs = left.as_millis().div_ceil(1000);
+        vec![
+            Line::from(vec![
+                Span::styled("Starting in ", theme.text()),
+                Span::styled(format!("{}s", secs), theme.failure()),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(format!("$ {}", self.command), theme.dim())),
+        ]
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Esc => ModalOutcome::Answered(Question::DelayedRun, false),
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (60, 30)
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.failure()
+    }
+
+    fn footer(&self) -> &'static str {
+        "Esc to cancel"
+    }
+}
+
+/// Confirmation of an action with `confirm = "typed"`: Enter only runs it once the
+/// phrase is typed exactly, Esc cancels
+pub struct TypedConfirmModal {
+    pub label: String,
+    pub command: String,
+    pub phrase: String,
+    pub input: EditState,
+}
+
+impl TypedConfirmModal {
+    fn matches(&self) -> bool {
+        self.input.buffer == self.phrase
+    }
+}
+
+impl ModalView for TypedConfirmModal {
+    fn title(&self) -> String {
+        format!("Run {}?", self.label)
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let style = if self.matches() {
+            theme.success()
+        } else {
+            theme.failure()
+        };
+        let mut input = vec![Span::styled("> ", theme.accent())];
+        input.extend(edit_spans(&self.input, true, style));
+        vec![
+            Line::from(Span::styled(format!("$ {}", self.command), theme.dim())),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Type ", theme.text()),
+                Span::styled(self.phrase.clone(), theme.bold()),
+                Span::styled(" to confirm", theme.text()),
+            ]),
+            Line::from(input),
+        ]
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        if self.input.apply_key(key) {
+            return ModalOutcome::Stay;
+        }
+        match key.code {
+            KeyCode::Char(ch) if !is_chord(&key) => self.input.insert(ch),
+            KeyCode::Enter if self.matches() => {
+                return ModalOutcome::Answered(Question::TypedRun, true)
+            }
+            KeyCode::Esc => return ModalOutcome::Answered(Question::TypedRun, false),
+            _ => {}
+        }
+        ModalOutcome::Stay
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (60, 30)
+    }
+
+    fn border_style(&self, theme: &Theme) -> Style {
+        theme.failure()
+    }
+
+    fn footer(&self) -> &'static str {
+        "Enter: run once typed   Esc: cancel"
+    }
+}
+
+/// Hidden debug overlay (F12): the most recent lines of the app's own log
+pub struct LogModal;
+
+impl ModalView for LogModal {
+    fn title(&self) -> String {
+        "Debug log".to_string()
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        // more than any screen fits; rendering keeps the last ones
+        let lines = logging::tail(200);
+        if lines.is_empty() {
+            return vec![Line::from(Span::styled(
+                "Nothing logged yet (CALLBOT_LOG sets the level, e.g. debug)",
+                theme.faint(),
+            ))];
+        }
+        lines
+            .into_iter()
+            .map(|line| {
+                let style = if line.contains(" ERROR ") || line.contains(" WARN ") {
+                    theme.failure()
+                } else if line.contains(" DEBUG ") || line.contains(" TRACE ") {
+                    theme.faint()
+                } else {
+                    theme.text()
+                };
+                Line::from(Span::styled(line, style))
+            })
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> ModalOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::F(12) => ModalOutcome::Close,
+            _ => ModalOutcome::Stay,
+        }
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (90, 80)
+    }
+
+    fn footer(&self) -> &'static str {
+        "F12 or Esc to close"
+    }
+
+    fn follows_tail(&self) -> bool {
+        true
+    }
+}
+
+/// Centered rect using a percentage of the available width and height
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
@@ -0,0 +1,334 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Tabs};
+use ratatui::Frame;
+use regex::{Regex, RegexBuilder};
+
+use super::app_state::EditState;
+use super::diff::{diff_lines, Change};
+use super::theme::Theme;
+use super::{draw_scrollbar, edit_spans, format_elapsed, spinner};
+use crate::jobs::{Job, JobStatus, Jobs};
+
+// jobs listed in the tab strip above the output
+const MAX_TABS: usize = 6;
+// running jobs given a tile, the most recent ones
+const MAX_TILES: usize = 6;
+// tiles are stacked in a single column below twice this width
+const MIN_TILE_WIDTH: u16 = 30;
+
+/// How the output pane shows the job: whole, diffed or filtered
+#[derive(Default)]
+pub struct OutputView {
+    // compare with the previous run under the same label, see `draw_output`
+    pub diff: bool,
+    // only lines matching, ignoring case; empty shows them all
+    pub filter: String,
+    // hide the matching lines instead
+    pub invert: bool,
+    // filter being typed with f, applied as it changes
+    pub filter_edit: Option<EditState>,
+    // `filter` as a regex, or escaped while it is not a valid one (e.g. half typed)
+    matcher: Option<Regex>,
+    // job shown in full even while others run, by id
+    pub zoom: Option<usize>,
+}
+
+impl OutputView {
+    /// Job the pane is about: the zoomed one, else the latest
+    pub fn job<'a>(&self, jobs: &'a Jobs) -> Option<&'a Job> {
+        self.zoom.and_then(|id| jobs.get(id)).or(jobs.latest())
+    }
+
+    /// Zoom the next running job to the whole pane, after the last one back to the tiles
+    pub fn cycle_zoom(&mut self, jobs: &Jobs) {
+        let running: Vec<usize> = jobs.running().map(|j| j.id).collect();
+        self.zoom = match self.zoom {
+            None => running.first().copied(),
+            Some(id) => running
+                .iter()
+                .position(|&r| r == id)
+                .and_then(|i| running.get(i + 1))
+                .copied(),
+        };
+    }
+
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.matcher = (!filter.is_empty()).then(|| {
+            RegexBuilder::new(filter)
+                .case_insensitive(true)
+                .build()
+                .or_else(|_| {
+                    RegexBuilder::new(&regex::escape(filter))
+                        .case_insensitive(true)
+                        .build()
+                })
+                .expect("escaped pattern is a valid regex")
+        });
+    }
+
+    /// Whether `line` passes the filter
+    pub fn shows(&self, line: &str) -> bool {
+        self.matcher
+            .as_ref()
+            .is_none_or(|m| m.is_match(line) != self.invert)
+    }
+}
+
+/// Output pane of the split view: the tail of the latest job's output, following
+/// new lines as they stream in. With several jobs, a tab strip lists the recent ones.
+/// While several run at once each gets a tile instead, unless one is zoomed.
+///
+/// With `diff`, the output is compared to the previous run under the same label instead,
+/// added and removed lines highlighted. While the latest job still runs, its finished
+/// predecessor is the one shown, so a watched command does not flicker. Lines not
+/// passing the filter are hidden, live as new ones stream in.
+pub fn draw_output(
+    f: &mut Frame,
+    area: Rect,
+    jobs: &Jobs,
+    view: &OutputView,
+    cursor_on: bool,
+    reduced_motion: bool,
+    theme: &Theme,
+) {
+    let running: Vec<&Job> = jobs.running().collect();
+    if running.len() > 1 && view.zoom.and_then(|id| jobs.get(id)).is_none() {
+        draw_tiles(f, area, &running, view, cursor_on, reduced_motion, theme);
+        return;
+    }
+    let diff = view.diff;
+    let job = match view.job(jobs) {
+        Some(job) if diff && job.is_running() => jobs.previous(job).or(Some(job)),
+        job => job,
+    };
+    let (title, border) = match job {
+        Some(job) => {
+            let (state, style) = match job.status {
+                JobStatus::Running => ("running".to_string(), theme.accent()),
+                JobStatus::Exited(0) => ("✓ exit 0".to_string(), theme.success()),
+                JobStatus::Exited(code) => (format!("✗ exit {}", code), theme.failure()),
+                JobStatus::Failed(ref err) => (format!("✗ {}", err), theme.failure()),
+                JobStatus::Aborted => ("✗ aborted".to_string(), theme.failure()),
+            };
+            let mut spans = vec![
+                Span::styled(format!(" {} ", job.label), theme.bold()),
+                Span::styled(format!("{} ", state), style),
+            ];
+            if diff {
+                spans.push(Span::styled("± previous run ", theme.dim()));
+            }
+            (Line::from(spans), style)
+        }
+        None => (
+            Line::from(Span::styled(" Output ", theme.bold())),
+            theme.text(),
+        ),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border)
+        .title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let recent = jobs.recent(MAX_TABS);
+    let body = if recent.len() > 1 {
+        let [strip, body] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        let titles: Vec<Line> = recent.iter().map(|job| tab_title(job, theme)).collect();
+        let tabs = Tabs::new(titles)
+            .select(
+                job.and_then(|job| recent.iter().position(|j| j.id == job.id))
+                    .unwrap_or(recent.len() - 1),
+            )
+            .style(theme.dim())
+            .highlight_style(theme.selected())
+            .divider(Span::styled("│", theme.faint()));
+        f.render_widget(tabs, strip);
+        body
+    } else {
+        inner
+    };
+
+    let body = draw_filter(f, body, view, cursor_on, theme);
+
+    let height = body.height as usize;
+    let Some(job) = job else {
+        f.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "No job yet: press b in the details view to run an action in the background",
+                theme.faint(),
+            ))),
+            body,
+        );
+        return;
+    };
+    let mut lines = vec![Line::from(Span::styled(
+        format!("$ {}", job.command),
+        theme.dim(),
+    ))];
+    // index of the first line changed since the previous run
+    let mut first_change = None;
+    match jobs.previous(job).filter(|_| diff) {
+        Some(previous) => {
+            for change in diff_lines(&previous.output, &job.output) {
+                let (mark, line, style) = match change {
+                    Change::Same(line) => (' ', line, theme.text()),
+                    Change::Added(line) => ('+', line, theme.success()),
+                    Change::Removed(line) => ('-', line, theme.failure()),
+                };
+                if !view.shows(line) {
+                    continue;
+                }
+                if mark != ' ' && first_change.is_none() {
+                    first_change = Some(lines.len());
+                }
+                lines.push(Line::from(Span::styled(
+                    format!("{} {}", mark, line),
+                    style,
+                )));
+            }
+            if first_change.is_none() {
+                lines.push(Line::from(Span::styled(
+                    "No change since the previous run",
+                    theme.faint(),
+                )));
+            }
+        }
+        None => {
+            if diff {
+                lines.push(Line::from(Span::styled(
+                    format!("No earlier run of {} to compare with", job.label),
+                    theme.faint(),
+                )));
+            }
+            lines.extend(
+                job.output
+                    .iter()
+                    .filter(|l| view.shows(l))
+                    .map(|l| Line::from(l.as_str())),
+            );
+        }
+    }
+    // keep the newest lines in view, or the first change of a diff
+    let total = lines.len();
+    let skip = total
+        .saturating_sub(height)
+        .min(first_change.unwrap_or(usize::MAX));
+    f.render_widget(Paragraph::new(lines.split_off(skip)), body);
+    draw_scrollbar(f, area, total, height, skip, theme);
+}
+
+// Filter line pinned above the output while typing it or once applied; the area left
+fn draw_filter(
+    f: &mut Frame,
+    area: Rect,
+    view: &OutputView,
+    cursor_on: bool,
+    theme: &Theme,
+) -> Rect {
+    if view.filter_edit.is_none() && view.filter.is_empty() {
+        return area;
+    }
+    let [strip, body] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+    let prompt = if view.invert { "grep -v " } else { "grep " };
+    let mut spans = vec![Span::styled(prompt, theme.accent())];
+    match view.filter_edit {
+        Some(ref edit) => spans.extend(edit_spans(edit, cursor_on, theme.bold())),
+        None => {
+            spans.push(Span::raw(view.filter.clone()));
+            spans.push(Span::styled("  f edit  F invert", theme.faint()));
+        }
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), strip);
+    body
+}
+
+// One tile per running job, two abreast when wide enough, each with the label,
+// spinner and running time of the job over the tail of its output
+fn draw_tiles(
+    f: &mut Frame,
+    area: Rect,
+    running: &[&Job],
+    view: &OutputView,
+    cursor_on: bool,
+    reduced_motion: bool,
+    theme: &Theme,
+) {
+    let title = Line::from(vec![
+        Span::styled(format!(" {} jobs running ", running.len()), theme.bold()),
+        Span::styled("Z zooms ", theme.faint()),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.accent())
+        .title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let body = draw_filter(f, inner, view, cursor_on, theme);
+
+    let tiled = &running[running.len().saturating_sub(MAX_TILES)..];
+    let per_row = if body.width >= 2 * MIN_TILE_WIDTH {
+        2
+    } else {
+        1
+    };
+    let rows = tiled.chunks(per_row);
+    let row_areas = Layout::vertical(vec![Constraint::Fill(1); rows.len()]).split(body);
+    for (row, area) in rows.zip(row_areas.iter()) {
+        let cells = Layout::horizontal(vec![Constraint::Fill(1); row.len()]).split(*area);
+        for (job, cell) in row.iter().zip(cells.iter()) {
+            draw_tile(f, *cell, job, view, reduced_motion, theme);
+        }
+    }
+}
+
+fn draw_tile(
+    f: &mut Frame,
+    area: Rect,
+    job: &Job,
+    view: &OutputView,
+    reduced_motion: bool,
+    theme: &Theme,
+) {
+    let title = Line::from(vec![
+        Span::styled(
+            format!(" {} ", spinner(job, reduced_motion)),
+            theme.accent(),
+        ),
+        Span::styled(format!("{} ", job.label), theme.bold()),
+        Span::styled(format!("{} ", format_elapsed(job.run_time())), theme.dim()),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.faint())
+        .title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let shown: Vec<&String> = job.output.iter().filter(|l| view.shows(l)).collect();
+    let tail = &shown[shown.len().saturating_sub(inner.height as usize)..];
+    let lines: Vec<Line> = if tail.is_empty() {
+        vec![Line::from(Span::styled(
+            format!("$ {}", job.command),
+            theme.faint(),
+        ))]
+    } else {
+        tail.iter().map(|l| Line::from(l.as_str())).collect()
+    };
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+// Label of a job in the tab strip with the outcome in front
+fn tab_title<'a>(job: &Job, theme: &Theme) -> Line<'a> {
+    let (mark, style) = match job.status {
+        JobStatus::Running => ("●", theme.accent()),
+        JobStatus::Exited(0) => ("✓", theme.success()),
+        _ => ("✗", theme.failure()),
+    };
+    Line::from(vec![
+        Span::styled(format!("{} ", mark), style),
+        Span::raw(job.label.clone()),
+    ])
+}
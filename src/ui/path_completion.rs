@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::expand_home;
+
+/// Paths completing `typed`, relative to `base` unless absolute or starting with ~/:
+/// the entries of its directory whose name starts with its last component, sorted,
+/// directories with a trailing slash. Hidden entries are only offered when the
+/// typed name starts with a dot.
+pub fn candidates(typed: &str, base: &Path) -> Vec<String> {
+    let (dir, name) = match typed.rfind('/') {
+        Some(i) => typed.split_at(i + 1),
+        None => ("", typed),
+    };
+    let entries = match fs::read_dir(base.join(expand_home(dir))) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut found: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            // follows symlinks, so a link to a directory completes like one
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, file_name, slash))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Longest start shared by all `candidates`
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for candidate in &candidates[1..] {
+        let shared = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(candidate.len()), |((i, _), _)| i);
+        prefix = &prefix[..shared];
+    }
+    prefix.to_string()
+}
@@ -0,0 +1,121 @@
+use ratatui::layout::{Alignment, Constraint, Rect};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Cell, Row, Table, TableState};
+use ratatui::Frame;
+
+use super::app_state::ActionKey;
+use super::theme::Theme;
+use super::{column_title, draw_scrollbar, ellipsize, mark_prefix, App};
+
+// width of the last run cell, e.g. "✗ 127 59m ago"
+const STATUS_WIDTH: u16 = 14;
+
+/// Column drawn with `layout = "table"`: one row per action with its label,
+/// description and last run status
+pub fn draw_table(f: &mut Frame, area: Rect, app: &mut App, col_idx: usize, theme: &Theme) {
+    let focused = app.nav.focused_column() == col_idx;
+    let column = &app.nav.columns[col_idx];
+    let count = column.actions.len();
+    let selected = column
+        .list_state
+        .selected()
+        .unwrap_or(0)
+        .min(count.saturating_sub(1));
+    // rows below the borders and the header; only the visible window is built
+    let viewport = (area.height.saturating_sub(3) as usize).max(1);
+    let mut offset = column.table_state.offset().min(selected);
+    if selected >= offset + viewport {
+        offset = selected + 1 - viewport;
+    }
+    let end = (offset + viewport).min(count);
+
+    // borders, highlight symbol and the spacing between the three cells
+    let inner = area.width.saturating_sub(2 + 2 + 2);
+    let longest = column
+        .actions
+        .iter()
+        .map(|a| a.label.chars().count() as u16)
+        .max()
+        .unwrap_or(0);
+    // the label cell starts with the batch mark
+    let label_width = (longest + 2)
+        .min(inner.saturating_sub(STATUS_WIDTH) / 2)
+        .max(8);
+    let description_width = inner.saturating_sub(label_width + STATUS_WIDTH);
+
+    let mut truncated_label = None;
+    let rows: Vec<Row> = column.actions[offset..end]
+        .iter()
+        .enumerate()
+        .map(|(n, action)| {
+            let i = offset + n;
+            let key = ActionKey {
+                column: col_idx,
+                action: i,
+            };
+            let (label, truncated) =
+                ellipsize(&action.label, (label_width as usize).saturating_sub(2));
+            if truncated && focused && selected == i {
+                truncated_label = Some(action.label.clone());
+            }
+            let label = format!("{}{}", mark_prefix(app.marked.contains(&key)), label);
+            let description = action.description.as_deref().unwrap_or("");
+            let (description, _) = ellipsize(description, description_width as usize);
+            let status = match app.running_badge(key) {
+                Some(badge) => Span::styled(badge, theme.accent()),
+                None => app
+                    .last_run_badge(key, theme)
+                    .unwrap_or_else(|| Span::styled("never", theme.faint())),
+            };
+            Row::new(vec![
+                Cell::from(label),
+                Cell::from(Span::styled(description, theme.dim())),
+                Cell::from(status),
+            ])
+        })
+        .collect();
+    if truncated_label.is_some() {
+        app.truncated_label = truncated_label;
+    }
+
+    let header = Row::new(vec![
+        Cell::from(Span::styled("  Action", theme.bold())),
+        Cell::from(Span::styled("Description", theme.bold())),
+        Cell::from(Span::styled("Last run", theme.bold())),
+    ]);
+    let widths = [
+        Constraint::Length(label_width),
+        Constraint::Length(description_width),
+        Constraint::Length(STATUS_WIDTH),
+    ];
+    let column = &mut app.nav.columns[col_idx];
+    let title = column_title(&column.title, count, area.width as usize);
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(title, theme.bold()))
+                .title_alignment(Alignment::Center),
+        )
+        // same highlight as the list layout
+        .row_highlight_style(if focused {
+            theme.selected()
+        } else {
+            theme.dim()
+        })
+        .highlight_symbol(if focused { "► " } else { "  " });
+
+    // the selection is owned by the list state; the table only gets the window and
+    // the column's table state keeps the scroll
+    let mut window = TableState::default().with_selected(Some(selected - offset));
+    f.render_stateful_widget(table, area, &mut window);
+    *column.table_state.offset_mut() = offset;
+    // below the borders and the header row
+    let rows_area = Rect {
+        y: area.y + 1,
+        height: area.height.saturating_sub(1),
+        ..area
+    };
+    draw_scrollbar(f, rows_area, count, viewport, offset, theme);
+}
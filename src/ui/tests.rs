@@ -0,0 +1,1290 @@
+//! UI tests: events are fed to `handle_event` and frames drawn on a `TestBackend`,
+//! then compared with the snapshots in `snapshots/` (`cargo insta review` to update).
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::layout::Constraint;
+use ratatui::Terminal;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use super::events::AppEvent;
+use super::figlet::Font;
+use super::keymap::{parse_key, KeyContext, KEYMAP};
+use super::modal::{ConfirmModal, Modal, Question};
+use super::{draw, handle_event, ActionKey, App, Step, CHECKPOINT_INTERVAL};
+use crate::config::{ColumnSort, Config, Confirm, ParameterType, TemplateEngine};
+use crate::control::Request;
+use crate::jobs::JobStatus;
+use crate::recovery::{Recovery, SavedEdit};
+use crate::session::SavedParam;
+use crate::state::{HistoryEntry, State};
+
+// terminal size of the snapshots
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+fn app() -> App {
+    let config = Config::load(FIXTURE).expect("fixture config loads");
+    App::new(config, State::default())
+}
+
+// Like the main loop, a frame is drawn before each event: some state (the listed
+// parameters, the page size) is only known once the layout has been computed
+fn press(app: &mut App, code: KeyCode) -> Step {
+    render(app, WIDTH, HEIGHT);
+    handle_event(app, AppEvent::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for ch in text.chars() {
+        press(app, KeyCode::Char(ch));
+    }
+}
+
+// Draw a frame and return its text, one line per row
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| draw(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut lines = Vec::new();
+    for y in 0..height {
+        let line: String = (0..width).map(|x| buffer[(x, y)].symbol()).collect();
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn browse_view() {
+    let mut app = app();
+    insta::assert_snapshot!(render(&mut app, WIDTH, HEIGHT));
+}
+
+#[test]
+fn navigation_moves_the_selection() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    assert_eq!(
+        app.nav.focused_key(),
+        Some(ActionKey {
+            column: 0,
+            action: 1
+        })
+    );
+    press(&mut app, KeyCode::Right);
+    assert_eq!(app.nav.focused_column(), 1);
+    insta::assert_snapshot!(render(&mut app, WIDTH, HEIGHT));
+}
+
+#[test]
+fn details_view() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    assert!(app.details.open);
+    insta::assert_snapshot!(render(&mut app, WIDTH, HEIGHT));
+}
+
+#[test]
+fn editing_a_text_parameter() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    // ENV is a select, BRANCH the text parameter below it
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "main");
+    press(&mut app, KeyCode::Enter);
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    assert_eq!(app.final_command(key), "deploy.sh prod main");
+    insta::assert_snapshot!(render(&mut app, WIDTH, HEIGHT));
+}
+
+#[test]
+fn moving_the_cursor_while_editing() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "fix bug");
+    let ctrl_left = KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL);
+    handle_event(&mut app, AppEvent::Key(ctrl_left));
+    type_text(&mut app, "the ");
+    press(&mut app, KeyCode::End);
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Home);
+    press(&mut app, KeyCode::Delete);
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    assert_eq!(app.params.value(key, 1), "ix the bu");
+}
+
+#[test]
+fn pasting_at_the_cursor() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "main");
+    press(&mut app, KeyCode::Home);
+    // the trailing line break of a copied line is dropped
+    handle_event(&mut app, AppEvent::Paste("release/\n".to_string()));
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    assert_eq!(app.params.value(key, 1), "release/main");
+}
+
+#[test]
+fn undo_redo_and_reset() {
+    let mut app = app();
+    let ctrl = |app: &mut App, ch: char| {
+        handle_event(
+            app,
+            AppEvent::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL)),
+        );
+    };
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    // typing is undone a run at a time
+    type_text(&mut app, "main");
+    ctrl(&mut app, 'u');
+    type_text(&mut app, "dev");
+    ctrl(&mut app, 'z');
+    assert_eq!(app.params.value(key, 1), "");
+    ctrl(&mut app, 'z');
+    assert_eq!(app.params.value(key, 1), "main");
+    ctrl(&mut app, 'y');
+    ctrl(&mut app, 'y');
+    assert_eq!(app.params.value(key, 1), "dev");
+    press(&mut app, KeyCode::Enter);
+
+    // the select goes back to its first option
+    press(&mut app, KeyCode::Up);
+    press(&mut app, KeyCode::Right);
+    assert_eq!(app.params.value(key, 0), "prod");
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(app.params.value(key, 0), "qlf");
+}
+
+#[test]
+fn picking_a_recent_value() {
+    let mut app = app();
+    for branch in ["feature/login", "main", "feature/search"] {
+        app.state.record_value("BRANCH", branch);
+    }
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    // newest first, narrowed to the values containing what was typed
+    type_text(&mut app, "feat");
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    assert_eq!(app.params.value(key, 1), "feature/login");
+    assert!(app.details.edit.is_none());
+}
+
+#[test]
+fn completing_a_path() {
+    // tests run from the crate directory
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    type_text(&mut app, "tests/fix");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.params.value(key, 1), "tests/fixtures/");
+    // the shared part first, then each candidate in turn
+    type_text(&mut app, "prov");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.params.value(key, 1), "tests/fixtures/provider");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.params.value(key, 1), "tests/fixtures/provider.json");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.params.value(key, 1), "tests/fixtures/providers.toml");
+    press(&mut app, KeyCode::BackTab);
+    assert_eq!(app.params.value(key, 1), "tests/fixtures/provider.json");
+    assert!(app.details.edit.is_some());
+}
+
+#[test]
+fn filters_transform_values() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].template =
+        "deploy.sh {ENV|upper} {BRANCH|slug} {BRANCH|basename|shell-quote} {BRANCH}".to_string();
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    app.params
+        .set_value(key, 1, "Feature/Login Page".to_string());
+    let action = app.nav.action(key).unwrap();
+    assert_eq!(
+        app.params.substitute(key, action),
+        "deploy.sh QLF feature-login-page 'Login Page' Feature/Login Page"
+    );
+}
+
+#[test]
+fn repeated_flags() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].parameters[1].repeat_format = Some("-b {value}".to_string());
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    app.params
+        .set_value(key, 1, "main, dev,,hotfix".to_string());
+    let action = app.nav.action(key).unwrap();
+    assert_eq!(
+        app.params.substitute(key, action),
+        "deploy.sh qlf -b main -b dev -b hotfix"
+    );
+}
+
+#[test]
+fn optional_groups() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].template =
+        "deploy.sh [--env {ENV}] [--branch={BRANCH}] [ -n x ] [abc]".to_string();
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let action = app.nav.action(key).unwrap().clone();
+    // only groups with a placeholder are optional
+    assert_eq!(
+        app.params.substitute(key, &action),
+        "deploy.sh --env qlf [ -n x ] [abc]"
+    );
+    app.params.set_value(key, 1, "main".to_string());
+    assert_eq!(
+        app.params.substitute(key, &action),
+        "deploy.sh --env qlf --branch=main [ -n x ] [abc]"
+    );
+}
+
+#[test]
+fn handlebars_templates() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    let deploy = &mut config.columns[0].actions[1];
+    deploy.engine = TemplateEngine::Handlebars;
+    deploy.template = concat!(
+        "deploy.sh {ENV} --branch {{default BRANCH \"main\"}}",
+        "{{#if (eq ENV \"prod\")}} --confirm{{/if}}",
+        "{{#each (split BRANCH \",\")}} -b {{this}}{{/each}}"
+    )
+    .to_string();
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let action = app.nav.action(key).unwrap().clone();
+    assert_eq!(
+        app.params.substitute(key, &action),
+        "deploy.sh qlf --branch main"
+    );
+    app.params.set_value(key, 0, "prod".to_string());
+    app.params.set_value(key, 1, "a, b".to_string());
+    assert_eq!(
+        app.params.substitute(key, &action),
+        "deploy.sh prod --branch a, b --confirm -b a -b b"
+    );
+}
+
+#[test]
+fn global_selector() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.selector = Some(
+        toml::from_str(
+            r#"
+            name = "REGION"
+            placeholder = "{REGION}"
+            param_type = "select"
+            default = "eu"
+            options = [
+                { value = "us", label = "America" },
+                { value = "eu", label = "Europe" },
+            ]
+            "#,
+        )
+        .unwrap(),
+    );
+    config.columns[0].actions[0].template = "make build --region {REGION|upper}".to_string();
+    let mut app = App::new(config, State::default());
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("REGION: Europe"));
+    let key = ActionKey {
+        column: 0,
+        action: 0,
+    };
+    assert_eq!(app.command_for(key), "make build --region EU");
+    // wraps around
+    press(&mut app, KeyCode::Char('g'));
+    assert_eq!(app.command_for(key), "make build --region US");
+}
+
+#[test]
+fn branches_from_git() {
+    let repo = std::env::temp_dir().join(format!("callbot-branches-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&repo);
+    std::fs::create_dir_all(&repo).unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .expect("git runs")
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q", "-b", "main"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+    git(&["branch", "feature/login"]);
+
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].parameters[1] = toml::from_str(&format!(
+        r#"
+        name = "BRANCH"
+        placeholder = "{{BRANCH}}"
+        param_type = "select"
+        source = "git-branches"
+        default = "main"
+        repo = "{}"
+        "#,
+        repo.display()
+    ))
+    .unwrap();
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    let branches: Vec<_> = app.nav.action(key).unwrap().parameters[1]
+        .options
+        .iter()
+        .map(|o| o.value.as_str())
+        .collect();
+    assert_eq!(branches.len(), 2);
+    assert!(branches.contains(&"feature/login"));
+    assert_eq!(app.command_for(key), "deploy.sh qlf main");
+
+    // a branch deleted since is no longer offered
+    git(&["branch", "-q", "-D", "feature/login"]);
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.nav.action(key).unwrap().parameters[1].options.len(), 1);
+    std::fs::remove_dir_all(&repo).unwrap();
+}
+
+#[test]
+fn applying_a_preset() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].presets = vec![toml::from_str(
+        r#"name = "hotfix"
+values = { ENV = "prod", BRANCH = "hotfix/1.2" }"#,
+    )
+    .unwrap()];
+    let mut app = App::new(config, State::default());
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('p'));
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("BRANCH=hotfix/1.2 ENV=prod (config)"));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.modal.is_none());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let action = app.nav.action(key).unwrap();
+    assert_eq!(
+        app.params.substitute(key, action),
+        "deploy.sh prod hotfix/1.2"
+    );
+    // the select follows the value
+    assert_eq!(app.params.selected(key, 0), 1);
+}
+
+#[test]
+fn exporting_an_alias() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "it's");
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    assert!(render(&mut app, WIDTH, HEIGHT).contains(r#"alias deploy='deploy.sh qlf it'\''s'"#));
+}
+
+#[test]
+fn usage_statistics() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].sort = ColumnSort::MostUsed;
+    let mut state = State::default();
+    state.record_stats("projects", "Deploy", 0, Duration::from_secs(3));
+    state.record_stats("projects", "Deploy", 2, Duration::from_secs(5));
+    let mut app = App::new(config, state);
+    // the most run action comes first
+    let labels: Vec<&str> = app.nav.columns[0]
+        .actions
+        .iter()
+        .map(|a| a.label.as_str())
+        .collect();
+    assert_eq!(labels, ["Deploy", "Build"]);
+    press(&mut app, KeyCode::Enter);
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("Usage: 2 runs, 1 failed, 4s on average"));
+}
+
+#[test]
+fn delayed_run() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[0].delay_secs = 5;
+    let mut app = App::new(config, State::default());
+    let build = ActionKey {
+        column: 0,
+        action: 0,
+    };
+    assert_eq!(press(&mut app, KeyCode::Char('r')), Step::Redraw);
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("Starting in 5s"));
+    // Esc cancels
+    press(&mut app, KeyCode::Esc);
+    assert!(app.countdown.is_none() && app.modal.is_none());
+    // otherwise the run starts once the countdown is over
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(handle_event(&mut app, AppEvent::Tick), Step::Redraw);
+    app.countdown.as_mut().unwrap().fires_at = Instant::now();
+    assert_eq!(handle_event(&mut app, AppEvent::Tick), Step::Run(build));
+    assert!(app.modal.is_none());
+}
+
+#[test]
+fn typed_confirmation() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[0].confirm = Confirm::Typed;
+    config.columns[0].actions[0].confirm_phrase = Some("drop it".to_string());
+    let mut app = App::new(config, State::default());
+    let build = ActionKey {
+        column: 0,
+        action: 0,
+    };
+    assert_eq!(press(&mut app, KeyCode::Char('r')), Step::Redraw);
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("Type drop it to confirm"));
+    // Enter does nothing until the phrase is typed exactly
+    type_text(&mut app, "drop");
+    assert_eq!(press(&mut app, KeyCode::Enter), Step::Redraw);
+    assert!(app.modal.is_some());
+    type_text(&mut app, " it");
+    assert_eq!(press(&mut app, KeyCode::Enter), Step::Run(build));
+    assert!(app.modal.is_none() && app.typed_run.is_none());
+    // Esc cancels
+    press(&mut app, KeyCode::Char('r'));
+    press(&mut app, KeyCode::Esc);
+    assert!(app.modal.is_none() && app.typed_run.is_none());
+}
+
+#[test]
+fn run_asks_for_missing_values_first() {
+    let mut app = app();
+    press(&mut app, KeyCode::Down);
+    // BRANCH is required and empty: its details open instead of running
+    assert_eq!(press(&mut app, KeyCode::Char('r')), Step::Redraw);
+    assert!(app.details.open);
+    assert_eq!(app.details.focused_param, 1);
+}
+
+#[test]
+fn run_from_the_list() {
+    let mut app = app();
+    assert_eq!(
+        press(&mut app, KeyCode::Char('r')),
+        Step::Run(ActionKey {
+            column: 0,
+            action: 0
+        })
+    );
+}
+
+#[test]
+fn escape_closes_the_details() {
+    let mut app = app();
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Esc);
+    assert!(!app.details.open);
+}
+
+#[test]
+fn help_overlay() {
+    let mut app = app();
+    press(&mut app, KeyCode::Char('?'));
+    insta::assert_snapshot!(render(&mut app, WIDTH, 60));
+}
+
+#[test]
+fn too_small_terminal() {
+    let mut app = app();
+    insta::assert_snapshot!(render(&mut app, 30, 10));
+}
+
+#[test]
+fn quit() {
+    let mut app = app();
+    assert_eq!(press(&mut app, KeyCode::Char('q')), Step::Quit);
+}
+
+#[test]
+fn control_requests() {
+    let mut app = app();
+    app.simulate = true;
+    let deploy = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let shown = app.command_for(deploy);
+    let mut request = |request: Request| {
+        let (tx, rx) = mpsc::channel();
+        handle_event(&mut app, AppEvent::Control(request, tx));
+        rx.recv().unwrap()
+    };
+    let run = |action: &str, params: &[(&str, &str)]| Request::Run {
+        column: "projects".to_string(),
+        action: action.to_string(),
+        params: params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    };
+
+    let reply = request(run("Deploy", &[("ENV", "prod")]));
+    assert_eq!(reply["error"], "BRANCH: required");
+    let reply = request(run("Deploy", &[("ENV", "prod"), ("BRANCH", "main")]));
+    assert_eq!(reply["ok"], true, "{}", reply);
+    let reply = request(Request::Jobs);
+    assert_eq!(reply["jobs"][0]["command"], "deploy.sh prod main");
+    // the values shown in the UI are left alone
+    assert_eq!(app.command_for(deploy), shown);
+}
+
+#[test]
+fn searching_the_history() {
+    let mut state = State::default();
+    for (env, branch) in [("qlf", "main"), ("prod", "release/2.1")] {
+        state.record_command(HistoryEntry {
+            column: "projects".to_string(),
+            label: "Deploy".to_string(),
+            command: format!("deploy.sh {} {}", env, branch),
+            values: [("ENV", env), ("BRANCH", branch)]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            timestamp: 0,
+            edited: None,
+        });
+    }
+    let mut app = App::new(Config::load(FIXTURE).unwrap(), state);
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    handle_event(&mut app, AppEvent::Key(ctrl_r));
+    type_text(&mut app, "dpl mn");
+    // Tab fills the details in without running
+    assert_eq!(press(&mut app, KeyCode::Tab), Step::Redraw);
+    assert!(app.details.open);
+    assert_eq!(app.command_for(key), "deploy.sh qlf main");
+
+    // newest first, Enter runs it again
+    handle_event(&mut app, AppEvent::Key(ctrl_r));
+    assert_eq!(press(&mut app, KeyCode::Enter), Step::Run(key));
+    assert_eq!(app.command_for(key), "deploy.sh prod release/2.1");
+}
+
+#[test]
+fn dry_runs() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].dry_template = Some("deploy.sh --plan {ENV} {BRANCH}".to_string());
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "main");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(press(&mut app, KeyCode::Char('D')), Step::Redraw);
+    let job = app.jobs.latest().unwrap();
+    assert_eq!(job.label, "Deploy (dry run)");
+    assert_eq!(job.command, "deploy.sh --plan qlf main");
+    assert!(app.job_actions.is_empty());
+}
+
+#[test]
+fn watching_an_action() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[0].watch_secs = 5;
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    press(&mut app, KeyCode::Char('r'));
+    let first = app.jobs.latest().unwrap().id;
+    assert!(app.jobs.latest().unwrap().label.ends_with("↻ 5s"));
+    // not due yet
+    handle_event(&mut app, AppEvent::Tick);
+    assert_eq!(app.jobs.latest().unwrap().id, first);
+    for _ in 0..3 {
+        app.watches[0].next = Instant::now();
+        handle_event(&mut app, AppEvent::Tick);
+    }
+    // only the last three runs are kept
+    assert_eq!(app.watches[0].runs.len(), 3);
+    assert!(app.jobs.get(first).is_none());
+    press(&mut app, KeyCode::Char('w'));
+    assert!(app.watches.is_empty());
+}
+
+#[test]
+fn aborting_every_job() {
+    let mut app = app();
+    // the background sleep keeps the output open: the job only ends once its whole
+    // process group is gone
+    let command = "sleep 30 & sleep 30; wait";
+    let id = app.jobs.spawn("Sleep", command, command).unwrap();
+    press(&mut app, KeyCode::Char('K'));
+    let message = &app.toasts.iter().last().unwrap().message;
+    assert_eq!(message, "Aborted 1 job");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.jobs.get(id).unwrap().is_running() && Instant::now() < deadline {
+        handle_event(&mut app, AppEvent::JobOutput);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.jobs.get(id).unwrap().status, JobStatus::Aborted);
+    press(&mut app, KeyCode::Char('K'));
+    let message = &app.toasts.iter().last().unwrap().message;
+    assert_eq!(message, "No job running");
+}
+
+#[test]
+fn quitting_on_a_signal_ends_the_jobs() {
+    let mut app = app();
+    // as above, the job only ends once the background sleep is gone too
+    let command = "sleep 30 & sleep 30; wait";
+    let id = app.jobs.spawn("Sleep", command, command).unwrap();
+    let step = handle_event(&mut app, AppEvent::Signal(libc::SIGTERM));
+    assert_eq!(step, Step::Quit);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.jobs.get(id).unwrap().is_running() && Instant::now() < deadline {
+        handle_event(&mut app, AppEvent::JobOutput);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!app.jobs.get(id).unwrap().is_running());
+}
+
+#[test]
+fn tiling_concurrent_jobs() {
+    let mut app = app();
+    let api = app
+        .jobs
+        .spawn("Api", "echo api up; sleep 30", "api")
+        .unwrap();
+    let web = app
+        .jobs
+        .spawn("Web", "echo web up; sleep 30", "web")
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while [api, web]
+        .iter()
+        .any(|&id| app.jobs.get(id).unwrap().output.is_empty())
+        && Instant::now() < deadline
+    {
+        handle_event(&mut app, AppEvent::JobOutput);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    press(&mut app, KeyCode::Char('o'));
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("2 jobs running"));
+    assert!(screen.contains("api up") && screen.contains("web up"));
+    // Z zooms each in turn, then goes back to the tiles
+    press(&mut app, KeyCode::Char('Z'));
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("Api running") && !screen.contains("web up"));
+    press(&mut app, KeyCode::Char('Z'));
+    assert_eq!(app.output.zoom, Some(web));
+    press(&mut app, KeyCode::Char('Z'));
+    assert!(render(&mut app, WIDTH, HEIGHT).contains("2 jobs running"));
+    app.jobs.kill_running();
+}
+
+#[test]
+fn diffing_consecutive_runs() {
+    let mut app = app();
+    let pods = "NAME READY\napi-1 1/1\nweb-1 1/1";
+    app.jobs.push_finished("Pods", "kubectl get pods", pods, 0);
+    let pods = "NAME READY\napi-2 0/1\nweb-1 1/1";
+    app.jobs.push_finished("Pods", "kubectl get pods", pods, 0);
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('c'));
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("- api-1 1/1"));
+    assert!(screen.contains("+ api-2 0/1"));
+    assert!(screen.contains("  web-1 1/1"));
+    // nothing earlier to compare the first run with
+    app.jobs.forget(1);
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("No earlier run of Pods"));
+}
+
+#[test]
+fn filtering_the_output() {
+    let mut app = app();
+    let log = "INFO start\nERROR disk full\nINFO retry\nerror: gave up";
+    app.jobs.push_finished("Deploy", "deploy.sh", log, 1);
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('f'));
+    type_text(&mut app, "^error");
+    press(&mut app, KeyCode::Enter);
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("ERROR disk full"));
+    assert!(screen.contains("error: gave up"));
+    assert!(!screen.contains("INFO retry"));
+    press(&mut app, KeyCode::Char('F'));
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("grep -v ^error"));
+    assert!(screen.contains("INFO retry"));
+    assert!(!screen.contains("disk full"));
+    // a pattern that is not a valid regex matches literally
+    press(&mut app, KeyCode::Char('f'));
+    let ctrl_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+    handle_event(&mut app, AppEvent::Key(ctrl_u));
+    type_text(&mut app, "(retry");
+    assert!(app.output.shows("INFO start"));
+    assert!(!app.output.shows("a (retry"));
+}
+
+#[test]
+fn saving_the_output() {
+    let path = std::env::temp_dir().join(format!("callbot-output-{}.log", std::process::id()));
+    let mut app = app();
+    app.jobs
+        .push_finished("Deploy", "deploy.sh", "step 1\nstep 2", 0);
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('S'));
+    let ctrl_u = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+    handle_event(&mut app, AppEvent::Key(ctrl_u));
+    type_text(&mut app, &path.to_string_lossy());
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "step 1\nstep 2\n");
+    // an existing file is not overwritten
+    press(&mut app, KeyCode::Char('S'));
+    handle_event(&mut app, AppEvent::Key(ctrl_u));
+    type_text(&mut app, &path.to_string_lossy());
+    press(&mut app, KeyCode::Enter);
+    let toast = app.toasts.iter().last().unwrap();
+    assert!(toast.message.starts_with("Could not write"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn password_values_stay_masked() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].parameters[1].param_type = ParameterType::Password;
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "hunter2");
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(!screen.contains("hunter2"));
+    press(&mut app, KeyCode::Enter);
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(!screen.contains("hunter2"));
+    assert!(screen.contains("deploy.sh qlf ••••••"));
+
+    press(&mut app, KeyCode::Char('b'));
+    assert_eq!(app.jobs.latest().unwrap().command, "deploy.sh qlf ••••••");
+    // not checkpointed for crash recovery
+    let recovery = app.snapshot_recovery();
+    assert!(recovery.params.iter().all(|p| p.param != "BRANCH"));
+
+    // masked where it is substituted only, however short
+    let key = app.nav.focused_key().unwrap();
+    app.params.set_value(key, 1, "l".to_string());
+    assert_eq!(app.shown_command(key), "deploy.sh qlf ••••••");
+    // a hand edit starts with its placeholder, filled in again to run
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(
+        app.details.command_edit.as_ref().unwrap().buffer,
+        "deploy.sh qlf {BRANCH}"
+    );
+    type_text(&mut app, " -v");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.final_command(key), "deploy.sh qlf l -v");
+    assert_eq!(app.shown_command(key), "deploy.sh qlf •••••• -v");
+    // and it is not exported in clear
+    press(&mut app, KeyCode::Char('x'));
+    assert!(app.modal.is_none());
+}
+
+#[test]
+fn filtered_passwords_are_masked_and_filled_after_filtering() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    let deploy = &mut config.columns[0].actions[1];
+    deploy.template =
+        "deploy.sh {ENV} {BRANCH|shell-quote} [--pw {BRANCH|slug}] {BRANCH|urlencode}".to_string();
+    deploy.parameters[1].param_type = ParameterType::Password;
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    app.params.set_value(key, 1, "It's Me".to_string());
+    assert_eq!(
+        app.final_command(key),
+        r"deploy.sh qlf 'It'\''s Me' --pw it-s-me It%27s%20Me"
+    );
+    assert_eq!(
+        app.shown_command(key),
+        "deploy.sh qlf •••••• --pw •••••• ••••••"
+    );
+
+    // the hand edit keeps each placeholder with its filters
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(
+        app.details.command_edit.as_ref().unwrap().buffer,
+        "deploy.sh qlf {BRANCH|shell-quote} --pw {BRANCH|slug} {BRANCH|urlencode}"
+    );
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(
+        app.final_command(key),
+        r"deploy.sh qlf 'It'\''s Me' --pw it-s-me It%27s%20Me"
+    );
+    assert_eq!(
+        app.shown_command(key),
+        "deploy.sh qlf •••••• --pw •••••• ••••••"
+    );
+    press(&mut app, KeyCode::Esc);
+
+    // a value the slug empties drops its group from what is shown too
+    app.params.set_value(key, 1, "!!".to_string());
+    assert_eq!(app.final_command(key), "deploy.sh qlf '!!' %21%21");
+    assert_eq!(app.shown_command(key), "deploy.sh qlf •••••• ••••••");
+}
+
+#[test]
+fn recalled_edits_keep_their_secret_placeholders() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].parameters[1].param_type = ParameterType::Password;
+    let mut state = State::default();
+    state.record_command(HistoryEntry {
+        column: "projects".to_string(),
+        label: "Deploy".to_string(),
+        command: "deploy.sh prod •••••• --pw ••••••".to_string(),
+        values: [("ENV".to_string(), "prod".to_string())].into(),
+        timestamp: 0,
+        edited: Some("deploy.sh prod {BRANCH|shell-quote} --pw {BRANCH|slug}".to_string()),
+    });
+    let mut app = App::new(config, state);
+    app.simulate = true;
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    handle_event(&mut app, AppEvent::Key(ctrl_r));
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(
+        app.details.command_override.as_deref(),
+        Some("deploy.sh prod {BRANCH|shell-quote} --pw {BRANCH|slug}")
+    );
+    // the password is not kept, once typed again it fills every placeholder
+    app.params.set_value(key, 1, "It's Me".to_string());
+    assert_eq!(
+        app.final_command(key),
+        r"deploy.sh prod 'It'\''s Me' --pw it-s-me"
+    );
+    assert_eq!(app.shown_command(key), "deploy.sh prod •••••• --pw ••••••");
+}
+
+#[test]
+fn secret_references_resolve_at_run_time() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[1].parameters[1].secret = Some("pass:deploy/token".to_string());
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    // the reference is no secret, it shows as is
+    let screen = render(&mut app, WIDTH, HEIGHT);
+    assert!(screen.contains("deploy.sh qlf pass:deploy/token"));
+    press(&mut app, KeyCode::Char('b'));
+    assert_eq!(
+        app.jobs.latest().unwrap().command,
+        "deploy.sh qlf pass:deploy/token"
+    );
+
+    let key = app.nav.focused_key().unwrap();
+    app.secret_cache
+        .insert("pass:deploy/token".to_string(), "s3cret".to_string());
+    let command = app.final_command(key);
+    assert_eq!(app.run_command(key).unwrap(), "deploy.sh qlf s3cret");
+    assert_eq!(app.shown_command(key), command);
+    // once typed over, the value is a secret like a password
+    app.params.set_value(key, 1, "hunter2".to_string());
+    assert_eq!(app.shown_command(key), "deploy.sh qlf ••••••");
+}
+
+#[test]
+fn filters_apply_to_the_secret_of_a_reference() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    let deploy = &mut config.columns[0].actions[1];
+    deploy.template =
+        "deploy.sh {ENV} {BRANCH|shell-quote} --url=x?t={BRANCH|urlencode}".to_string();
+    deploy.parameters[1].secret = Some("pass:deploy/token".to_string());
+    let mut app = App::new(config, State::default());
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    app.secret_cache
+        .insert("pass:deploy/token".to_string(), "it's a/b".to_string());
+    assert_eq!(
+        app.run_command(key).unwrap(),
+        r"deploy.sh qlf 'it'\''s a/b' --url=x?t=it%27s%20a%2Fb"
+    );
+}
+
+#[test]
+fn clean_exit_leaves_no_checkpoint() {
+    let mut app = app();
+    app.config.app.crash_recovery = true;
+    let deploy = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    app.params.set_value(deploy, 1, "main".to_string());
+    app.checkpoint_at = Instant::now() - CHECKPOINT_INTERVAL;
+    app.checkpoint();
+    let path = Recovery::dir()
+        .unwrap()
+        .join(format!("{}.json", std::process::id()));
+    assert!(path.exists());
+    // what a foreground run does before the process exits with the command
+    app.discard_checkpoint();
+    assert!(!path.exists());
+    assert!(app.checkpoint.is_none());
+}
+
+#[test]
+fn column_weights() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].weight = Some(3);
+    let app = App::new(config, State::default());
+    assert_eq!(
+        app.nav.constraints(),
+        [Constraint::Ratio(30, 40), Constraint::Ratio(10, 40)]
+    );
+}
+
+#[test]
+fn cycling_the_sort_order() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[0].label = "build".to_string();
+    let mut state = State::default();
+    state.record_run("projects", "Deploy", 10);
+    let mut app = App::new(config, state);
+    let labels = |app: &App| -> Vec<String> {
+        app.nav.columns[0]
+            .actions
+            .iter()
+            .map(|a| a.label.clone())
+            .collect()
+    };
+    let config_order = labels(&app);
+    // alphabetically, ignoring case
+    press(&mut app, KeyCode::Char('s'));
+    let mut alpha = config_order.clone();
+    alpha.sort_by_key(|l| l.to_lowercase());
+    assert_eq!(labels(&app), alpha);
+    // last run first
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(labels(&app)[0], "Deploy");
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(labels(&app), config_order);
+}
+
+#[test]
+fn saved_action_order() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0]
+        .actions
+        .push(toml::from_str("label = \"Lint\"\ntemplate = \"make lint\"").unwrap());
+    let mut state = State::default();
+    // saved before Lint was added to the config
+    state.set_order("projects", vec!["Deploy".to_string(), "Build".to_string()]);
+    let app = App::new(config, state);
+    let labels: Vec<_> = app.nav.columns[0]
+        .actions
+        .iter()
+        .map(|a| a.label.as_str())
+        .collect();
+    assert_eq!(labels, ["Deploy", "Build", "Lint"]);
+}
+
+#[test]
+fn background_actions() {
+    let mut config = Config::load(FIXTURE).unwrap();
+    config.columns[0].actions[0].background = true;
+    let mut app = App::new(config, State::default());
+    app.simulate = true;
+    press(&mut app, KeyCode::Enter);
+    // r, not only b, runs it in the background
+    assert_eq!(press(&mut app, KeyCode::Char('r')), Step::Redraw);
+    assert!(app.split_view);
+    assert_eq!(app.jobs.latest().unwrap().command, "make build");
+}
+
+#[test]
+fn long_columns_scroll_to_the_selection() {
+    let mut config = Config::load(FIXTURE).expect("fixture config loads");
+    let build = config.columns[0].actions[0].clone();
+    config.columns[0].actions = (0..5000)
+        .map(|i| {
+            let mut action = build.clone();
+            action.label = format!("Generated {}", i);
+            action
+        })
+        .collect();
+    let mut app = App::new(config, State::default());
+    let selected_line = |app: &mut App| {
+        let frame = render(app, WIDTH, HEIGHT);
+        let line = frame.lines().find(|line| line.contains('►')).unwrap_or("");
+        // the marker and the label, without the scrollbar thumb
+        let row = line.split('│').nth(1).unwrap_or("");
+        row.split_whitespace().take(3).collect::<Vec<_>>().join(" ")
+    };
+    press(&mut app, KeyCode::End);
+    assert_eq!(selected_line(&mut app), "► Generated 4999");
+    press(&mut app, KeyCode::Home);
+    assert_eq!(selected_line(&mut app), "► Generated 0");
+}
+
+#[test]
+fn restoring_a_crashed_edit() {
+    let mut app = app();
+    let saved = |param: &str, value: &str| SavedParam {
+        column: "projects".to_string(),
+        action: "Deploy".to_string(),
+        param: param.to_string(),
+        value: value.to_string(),
+    };
+    app.recovery = Some(Recovery {
+        // a pid that is never a running callbot, so the test leaves no file behind
+        pid: 0,
+        params: vec![saved("ENV", "prod")],
+        edit: Some(SavedEdit {
+            param: saved("BRANCH", "release/2"),
+            original: String::new(),
+        }),
+    });
+    app.modal = Some(Modal::new(ConfirmModal {
+        question: Question::RestoreEdits,
+        title: "Restore unsaved values".to_string(),
+        text: String::new(),
+    }));
+    press(&mut app, KeyCode::Char('y'));
+    let key = ActionKey {
+        column: 0,
+        action: 1,
+    };
+    assert_eq!(app.params.value(key, 0), "prod");
+    assert_eq!(app.details.focused_param, 1);
+    let edit = app.details.edit.as_ref().expect("the edit is resumed");
+    assert_eq!(edit.buffer, "release/2");
+    assert!(app.recovery.is_none());
+}
+
+#[test]
+fn banner_follows_the_font_layout() {
+    // a space, a two-column bar with a hardblank, and a glyph made of end marks
+    let font = |layout: &str| {
+        Font::parse(&format!(
+            "flf2a$ 2 2 3 {layout} 0\n$@\n$@@\n|$ @\n|$ @@\n@@\n@@@\n"
+        ))
+        .expect("the font parses")
+    };
+    // old layout -1: full width
+    assert_eq!(font("-1").render("!!"), ["|  |  ", "|  |  "]);
+    // old layout 0: fitting, glyphs move together until they touch, hardblanks included
+    assert_eq!(font("0 0").render("!!"), ["| |  ", "| |  "]);
+    assert_eq!(font("0 0 0 64").render("!!"), ["| |  ", "| |  "]);
+    // controlled smushing never overlaps a hardblank with a bar
+    assert_eq!(font("0 0 0 129").render("!!"), ["| |  ", "| |  "]);
+    // universal smushing overlaps one more column, the hardblank giving way
+    assert_eq!(font("0 0 0 128").render("!!"), ["||  ", "||  "]);
+    // only the trailing end marks are dropped
+    assert_eq!(font("0").render("\""), ["@", "@"]);
+}
+
+// Key events of a binding of the keymap: "Ctrl+Left/Right" is Ctrl+Left and
+// Ctrl+Right, "1..9" the first and last of the range; keys that aren't a fixed
+// key (hotkeys, the mouse wheel) have none
+fn binding_keys(keys: &str) -> Vec<KeyEvent> {
+    let parts: Vec<String> = match keys {
+        "/" => vec!["/".to_string()],
+        _ => {
+            let mut parts = keys.split('/');
+            let first = parts.next().unwrap_or_default();
+            let prefix = first.rsplit_once('+').map_or("", |(prefix, _)| prefix);
+            std::iter::once(first.to_string())
+                .chain(
+                    parts.map(|part| match part.contains('+') || prefix.is_empty() {
+                        true => part.to_string(),
+                        false => format!("{}+{}", prefix, part),
+                    }),
+                )
+                .collect()
+        }
+    };
+    parts
+        .iter()
+        .flat_map(|part| match part.split_once("..") {
+            Some((from, to)) => {
+                let prefix = from.rsplit_once('+').map_or("", |(prefix, _)| prefix);
+                let to = if prefix.is_empty() {
+                    to.to_string()
+                } else {
+                    format!("{}+{}", prefix, to)
+                };
+                vec![from.to_string(), to]
+            }
+            None => vec![part.clone()],
+        })
+        .filter_map(|part| parse_key(&part))
+        .map(|(code, modifiers)| match (code, modifiers) {
+            (KeyCode::Tab, KeyModifiers::SHIFT) => KeyEvent::new(KeyCode::BackTab, modifiers),
+            // the terminal sends Ctrl+U as Ctrl and a lowercase u
+            (KeyCode::Char(ch), KeyModifiers::CONTROL) => {
+                KeyEvent::new(KeyCode::Char(ch.to_ascii_lowercase()), modifiers)
+            }
+            _ => KeyEvent::new(code, modifiers),
+        })
+        .collect()
+}
+
+// An app where the keys of `context` apply; follow mode keeps the run keys from
+// starting anything
+fn app_in(context: KeyContext) -> App {
+    let mut app = app();
+    app.follow = true;
+    match context {
+        // moving an action writes the state file; out of config order it is refused
+        KeyContext::Main | KeyContext::Global | KeyContext::Output => {
+            app.config.columns[0].sort = ColumnSort::Alpha;
+            app.split_view = context == KeyContext::Output;
+        }
+        KeyContext::Details | KeyContext::Edit => {
+            press(&mut app, KeyCode::Down);
+            press(&mut app, KeyCode::Enter);
+            if context == KeyContext::Edit {
+                press(&mut app, KeyCode::Down);
+                press(&mut app, KeyCode::Enter);
+                assert!(app.details.edit.is_some());
+            }
+        }
+    }
+    app
+}
+
+#[test]
+fn every_binding_of_the_keymap_is_handled() {
+    let mut pressed = 0;
+    for binding in KEYMAP {
+        let keys = binding_keys(binding.keys);
+        assert!(
+            !keys.is_empty() || matches!(binding.keys, "hotkey" | "Mouse wheel"),
+            "{:?} {} names no key",
+            binding.context,
+            binding.keys
+        );
+        for key in keys {
+            let mut app = app_in(binding.context);
+            render(&mut app, WIDTH, HEIGHT);
+            let step = handle_event(&mut app, AppEvent::Key(key));
+            assert!(
+                !matches!(step, Step::Idle),
+                "{:?} {} ({:?}) is not handled",
+                binding.context,
+                binding.keys,
+                key
+            );
+            pressed += 1;
+        }
+    }
+    assert!(pressed > KEYMAP.len());
+
+    // the output pane keys do nothing while the pane is hidden
+    for binding in KEYMAP.iter().filter(|b| b.context == KeyContext::Output) {
+        for key in binding_keys(binding.keys) {
+            let mut app = app_in(KeyContext::Main);
+            render(&mut app, WIDTH, HEIGHT);
+            let step = handle_event(&mut app, AppEvent::Key(key));
+            assert!(
+                matches!(step, Step::Idle),
+                "{} acts without the pane",
+                binding.keys
+            );
+        }
+    }
+}
+
+#[test]
+fn reduced_motion_drops_the_banner_gradient() {
+    let mut app = app();
+    app.config.app.banner_gradient = Some("magenta".to_string());
+    assert!(app.banner_colors().1.is_some());
+    app.config.app.reduced_motion = true;
+    assert_eq!(app.banner_colors().1, None);
+}
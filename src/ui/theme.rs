@@ -0,0 +1,218 @@
+use ratatui::style::{Color, Modifier, Style};
+
+/// Colors used by the render code. Every style drawn by the UI comes from here so
+/// switching theme recolors the whole app.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    // screen background/foreground (Reset keeps the terminal's own colors)
+    pub background: Color,
+    pub foreground: Color,
+    // figlet banner
+    pub banner: Color,
+    // secondary text: subtitle, descriptions, unfocused selection, help line
+    pub dim: Color,
+    // hints that should stay out of the way
+    pub faint: Color,
+    // focus and key highlights
+    pub accent: Color,
+    // background of the focused parameter while editing it
+    pub edit_background: Color,
+    // option values by how risky they are to pick, see `option_colors`
+    pub safe: Color,
+    pub caution: Color,
+    pub danger: Color,
+}
+
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "dark",
+        background: Color::Reset,
+        foreground: Color::Reset,
+        banner: Color::Rgb(255, 165, 0),
+        dim: Color::Rgb(150, 150, 150),
+        faint: Color::Rgb(100, 100, 100),
+        accent: Color::Yellow,
+        edit_background: Color::Rgb(40, 40, 40),
+        safe: Color::Green,
+        caution: Color::Rgb(255, 165, 0),
+        danger: Color::Red,
+    },
+    Theme {
+        name: "light",
+        background: Color::Rgb(250, 250, 250),
+        foreground: Color::Rgb(30, 30, 30),
+        banner: Color::Rgb(200, 90, 0),
+        dim: Color::Rgb(100, 100, 100),
+        faint: Color::Rgb(150, 150, 150),
+        accent: Color::Rgb(0, 90, 180),
+        edit_background: Color::Rgb(225, 225, 225),
+        safe: Color::Rgb(0, 130, 0),
+        caution: Color::Rgb(190, 110, 0),
+        danger: Color::Rgb(190, 0, 0),
+    },
+    Theme {
+        name: "solarized",
+        background: Color::Rgb(0, 43, 54),
+        foreground: Color::Rgb(131, 148, 150),
+        banner: Color::Rgb(203, 75, 22),
+        dim: Color::Rgb(88, 110, 117),
+        faint: Color::Rgb(7, 54, 66),
+        accent: Color::Rgb(181, 137, 0),
+        edit_background: Color::Rgb(7, 54, 66),
+        safe: Color::Rgb(133, 153, 0),
+        caution: Color::Rgb(203, 75, 22),
+        danger: Color::Rgb(220, 50, 47),
+    },
+    Theme {
+        name: "high-contrast",
+        background: Color::Black,
+        foreground: Color::White,
+        banner: Color::White,
+        dim: Color::White,
+        faint: Color::Gray,
+        accent: Color::LightYellow,
+        edit_background: Color::Blue,
+        safe: Color::LightGreen,
+        caution: Color::LightYellow,
+        danger: Color::LightRed,
+    },
+];
+
+/// Index of the named theme, falling back to the first (dark) one
+pub fn theme_index(name: &str) -> usize {
+    THEMES.iter().position(|t| t.name == name).unwrap_or(0)
+}
+
+impl Theme {
+    pub fn base(&self) -> Style {
+        Style::default().bg(self.background).fg(self.foreground)
+    }
+
+    pub fn text(&self) -> Style {
+        Style::default().fg(self.foreground)
+    }
+
+    pub fn bold(&self) -> Style {
+        Style::default().add_modifier(Modifier::BOLD)
+    }
+
+    pub fn dim(&self) -> Style {
+        Style::default().fg(self.dim)
+    }
+
+    pub fn faint(&self) -> Style {
+        Style::default().fg(self.faint)
+    }
+
+    pub fn accent(&self) -> Style {
+        Style::default().fg(self.accent)
+    }
+
+    /// Highlight of the selected list item in the focused column
+    pub fn selected(&self) -> Style {
+        self.accent().add_modifier(Modifier::BOLD)
+    }
+
+    /// Focus pointer of the parameter being edited
+    pub fn editing(&self) -> Style {
+        self.accent().bg(self.edit_background)
+    }
+
+    pub fn success(&self) -> Style {
+        Style::default().fg(self.safe)
+    }
+
+    pub fn failure(&self) -> Style {
+        Style::default().fg(self.danger)
+    }
+
+    /// Color of the `idx`th parameter's value in the preview
+    pub fn param(&self, idx: usize) -> Style {
+        let colors = [self.accent, self.safe, self.caution, self.banner];
+        Style::default().fg(colors[idx % colors.len()])
+    }
+
+    /// Color from the config: a role of the theme ("safe", "caution", "danger",
+    /// "accent", "dim") so it follows theme changes, or a fixed color
+    pub fn color(&self, spec: &str) -> Option<Color> {
+        match spec {
+            "safe" => Some(self.safe),
+            "caution" => Some(self.caution),
+            "danger" => Some(self.danger),
+            "accent" => Some(self.accent),
+            "dim" => Some(self.dim),
+            other => parse_color(other),
+        }
+    }
+}
+
+// Named colors accepted in the config, with the RGB used to blend them in gradients
+const NAMED_COLORS: &[(&str, Color, (u8, u8, u8))] = &[
+    ("black", Color::Black, (0, 0, 0)),
+    ("red", Color::Red, (205, 0, 0)),
+    ("green", Color::Green, (0, 205, 0)),
+    ("yellow", Color::Yellow, (205, 205, 0)),
+    ("blue", Color::Blue, (0, 0, 238)),
+    ("magenta", Color::Magenta, (205, 0, 205)),
+    ("cyan", Color::Cyan, (0, 205, 205)),
+    ("gray", Color::Gray, (229, 229, 229)),
+    ("darkgray", Color::DarkGray, (127, 127, 127)),
+    ("lightred", Color::LightRed, (255, 0, 0)),
+    ("lightgreen", Color::LightGreen, (0, 255, 0)),
+    ("lightyellow", Color::LightYellow, (255, 255, 0)),
+    ("lightblue", Color::LightBlue, (92, 92, 255)),
+    ("lightmagenta", Color::LightMagenta, (255, 0, 255)),
+    ("lightcyan", Color::LightCyan, (0, 255, 255)),
+    ("white", Color::White, (255, 255, 255)),
+];
+
+/// Whether `spec` is a color `Theme::color` understands
+pub fn is_color(spec: &str) -> bool {
+    THEMES[0].color(spec).is_some()
+}
+
+/// Parse a color from the config: "#rrggbb" or a terminal color name such as "cyan"
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let name = s.to_lowercase().replace(['-', '_', ' '], "");
+    NAMED_COLORS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, color, _)| *color)
+}
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        other => NAMED_COLORS
+            .iter()
+            .find(|(_, c, _)| *c == other)
+            .map(|(_, _, rgb)| *rgb),
+    }
+}
+
+/// `steps` colors blending from `from` to `to`. Colors without a known RGB value
+/// (e.g. the terminal default) can't be blended and yield `from` throughout.
+pub fn gradient(from: Color, to: Color, steps: usize) -> Vec<Color> {
+    let (Some(a), Some(b)) = (rgb(from), rgb(to)) else {
+        return vec![from; steps];
+    };
+    let blend = |x: u8, y: u8, t: f32| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    (0..steps)
+        .map(|i| {
+            let t = if steps > 1 {
+                i as f32 / (steps - 1) as f32
+            } else {
+                0.0
+            };
+            Color::Rgb(blend(a.0, b.0, t), blend(a.1, b.1, t), blend(a.2, b.2, t))
+        })
+        .collect()
+}
@@ -1,119 +1,83 @@
-use ratatui::style::{Color, Style};
-use ratatui::text::{Span, Spans};
-use std::process::Command;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::sync::OnceLock;
 
-// Try to render the title using the `figlet` program. If unavailable, fall back to
-// a built-in ASCII art. Returns lines already wrapped as `Spans` so the caller can
-// render them directly in a Paragraph. The function does NOT include the subtitle
-// line; the UI appends that explicitly to guarantee it's visible.
-pub fn title_spans(title: &str) -> Vec<Spans<'static>> {
-    // If CALLBOT_FIGLET_FONT is set, try to use that font first.
-    if let Ok(font) = std::env::var("CALLBOT_FIGLET_FONT") {
-        if let Ok(output) = Command::new("figlet")
-            .arg("-f")
-            .arg(&font)
-            .arg(title)
-            .output()
-        {
-            if output.status.success() {
-                if let Ok(s) = String::from_utf8(output.stdout) {
-                    // collect lines and trim leading/trailing empty lines produced by some figlet fonts
-                    let mut lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
-                    while lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                        lines.pop();
-                    }
-                    while lines.first().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                        lines.remove(0);
-                    }
-                    return lines
-                        .into_iter()
-                        .map(|l| {
-                            Spans::from(Span::styled(
-                                l,
-                                Style::default().fg(Color::Rgb(255, 165, 0)),
-                            ))
-                        })
-                        .collect();
-                }
-            }
-        }
-    }
+use super::ellipsize;
+use super::figlet::Font;
+use super::theme::gradient;
 
-    // If a bundled font exists in assets/fonts (e.g. "ANSI Shadow.flf"), try that.
-    let bundled_font = "assets/fonts/ANSI Shadow.flf";
-    if std::path::Path::new(bundled_font).exists() {
-        if let Ok(output) = Command::new("figlet")
-            .arg("-f")
-            .arg(bundled_font)
-            .arg(title)
-            .output()
-        {
-            if output.status.success() {
-                if let Ok(s) = String::from_utf8(output.stdout) {
-                    let mut lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
-                    while lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                        lines.pop();
-                    }
-                    while lines.first().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                        lines.remove(0);
-                    }
-                    return lines
-                        .into_iter()
-                        .map(|l| {
-                            Spans::from(Span::styled(
-                                l,
-                                Style::default().fg(Color::Rgb(255, 165, 0)),
-                            ))
-                        })
-                        .collect();
-                }
+// Fonts compiled into the binary, selectable by name with CALLBOT_FIGLET_FONT
+const EMBEDDED_FONTS: &[(&str, &str)] = &[(
+    "ANSI Shadow",
+    include_str!("../../assets/fonts/ANSI Shadow.flf"),
+)];
+
+// Font of the banner, loaded once: CALLBOT_FIGLET_FONT names an embedded font or
+// points to a `.flf` file, otherwise the first embedded font is used
+fn banner_font() -> Option<&'static Font> {
+    static FONT: OnceLock<Option<Font>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        if let Ok(font) = std::env::var("CALLBOT_FIGLET_FONT") {
+            let embedded = EMBEDDED_FONTS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&font))
+                .map(|(_, source)| source.to_string());
+            if let Some(parsed) = embedded
+                .or_else(|| std::fs::read_to_string(&font).ok())
+                .and_then(|source| Font::parse(&source).ok())
+            {
+                return Some(parsed);
             }
         }
-    }
+        EMBEDDED_FONTS
+            .first()
+            .and_then(|(_, source)| Font::parse(source).ok())
+    })
+    .as_ref()
+}
 
-    // Try figlet without font (system default)
-    if let Ok(output) = Command::new("figlet").arg(title).output() {
-        if output.status.success() {
-            if let Ok(s) = String::from_utf8(output.stdout) {
-                let mut lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
-                while lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                    lines.pop();
-                }
-                while lines.first().map(|l| l.trim().is_empty()).unwrap_or(false) {
-                    lines.remove(0);
-                }
-                return lines
-                    .into_iter()
-                    .map(|l| {
-                        Spans::from(Span::styled(
-                            l,
-                            Style::default().fg(Color::Rgb(255, 165, 0)),
-                        ))
-                    })
-                    .collect();
-            }
+/// Render the configured title as a FIGlet banner at most `width` cells wide.
+/// A title the font can't draw, or whose banner is too wide, is shown as a single
+/// plain line instead (ellipsized if needed). With `gradient_to` the banner rows
+/// blend from `color` to that color, top to bottom. The subtitle is not included;
+/// the UI appends that explicitly to guarantee it's visible.
+pub fn title_spans(
+    title: &str,
+    color: Color,
+    gradient_to: Option<Color>,
+    width: usize,
+) -> Vec<Line<'static>> {
+    if let Some(font) = banner_font().filter(|font| font.can_render(title)) {
+        let mut lines = font.render(title);
+        // trim the empty rows some fonts reserve for descenders
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        while lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines.remove(0);
+        }
+        let banner_width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        if !lines.is_empty() && banner_width <= width {
+            let colors = match gradient_to {
+                Some(to) => gradient(color, to, lines.len()),
+                None => vec![color; lines.len()],
+            };
+            return lines
+                .into_iter()
+                .zip(colors)
+                .map(|(l, c)| Line::from(Span::styled(l, Style::default().fg(c))))
+                .collect();
         }
     }
 
-    // Fallback static ASCII
-    let ascii = [
-        r" $$$$$$\   $$$$$$\  $$\       $$\       $$$$$$$\   $$$$$$\ $$$$$$$$\ ",
-        r"$$  __$$\ $$  __$$\ $$ |      $$ |      $$  __$$\ $$  __$$\\__$$  __|",
-        r"$$ /  \__|$$ /  $$ |$$ |      $$ |      $$ |  $$ |$$ /  $$ |  $$ |   ",
-        r"$$ |      $$$$$$$$ |$$ |      $$ |      $$$$$$$\ |$$ |  $$ |  $$ |   ",
-        r"$$ |      $$  __$$ |$$ |      $$ |      $$  __$$\ $$ |  $$ |  $$ |   ",
-        r"$$ |  $$\ $$ |  $$ |$$ |      $$ |      $$ |  $$ |$$ |  $$ |  $$ |   ",
-        r"\$$$$$$  |$$ |  $$ |$$$$$$$$\ $$$$$$$$\ $$$$$$$  | $$$$$$  |  $$ |   ",
-        r" \______/ \__|  \__|\________|\________|\_______/  \______/   \__|   ",
-    ];
+    vec![title_line(title, color, width)]
+}
 
-    ascii
-        .iter()
-        .map(|l| {
-            Spans::from(Span::styled(
-                l.to_string(),
-                Style::default().fg(Color::Rgb(255, 165, 0)),
-            ))
-        })
-        .collect()
+/// The title as a single bold line, ellipsized to `width`
+pub fn title_line(title: &str, color: Color, width: usize) -> Line<'static> {
+    let (line, _) = ellipsize(title, width);
+    Line::from(Span::styled(
+        line,
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ))
 }
@@ -0,0 +1,65 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Severity of a toast, used to pick its color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Error,
+}
+
+/// A short-lived notification drawn in the bottom-right corner of the middle area
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub level: ToastLevel,
+    created: Instant,
+    ttl: Duration,
+}
+
+/// Queue of active toasts, oldest first
+#[derive(Debug, Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+// how many toasts are kept; older ones are dropped first
+const MAX_TOASTS: usize = 4;
+
+impl Toasts {
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastLevel::Info, Duration::from_secs(3));
+    }
+
+    /// Errors stay up longer so they aren't missed
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastLevel::Error, Duration::from_secs(8));
+    }
+
+    fn push(&mut self, message: String, level: ToastLevel, ttl: Duration) {
+        self.queue.push_back(Toast {
+            message,
+            level,
+            created: Instant::now(),
+            ttl,
+        });
+        while self.queue.len() > MAX_TOASTS {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Drop expired toasts, returning whether any was dropped
+    pub fn prune(&mut self) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|t| t.created.elapsed() < t.ttl);
+        self.queue.len() != before
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.queue.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
@@ -0,0 +1,119 @@
+use handlebars::Handlebars;
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+use crate::config::{Webhook, WebhookOn};
+use crate::transcript::Entry;
+
+/// Body posted when a webhook has no `payload`: a Slack-style message
+pub const DEFAULT_PAYLOAD: &str = r#"{"text": "{{action}} {{status}} in {{duration}}s ({{user}})\n```{{command}}\n{{output}}```"}"#;
+
+impl Webhook {
+    /// Whether a run of `label` that exited with `code` is posted
+    pub fn matches(&self, label: &str, code: Option<i32>) -> bool {
+        let outcome = match self.on {
+            WebhookOn::All => true,
+            WebhookOn::Failure => code != Some(0),
+            WebhookOn::Success => code == Some(0),
+        };
+        let action = self.actions.is_empty()
+            || self.actions.iter().any(|a| match a.strip_suffix('*') {
+                Some(prefix) => label.starts_with(prefix),
+                None => a == label,
+            });
+        outcome && action
+    }
+
+    /// Body posted for `run`: the payload rendered with the run's values, escaped to
+    /// sit inside JSON strings
+    pub fn body(&self, run: &Entry) -> Result<String, String> {
+        let mut engine = Handlebars::new();
+        engine.register_escape_fn(|text| {
+            let quoted = Value::from(text).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        });
+        let output = run.output.as_deref().unwrap_or_default();
+        let tail = &output[output.len().saturating_sub(self.tail_lines)..];
+        let status = match run.exit_code {
+            Some(0) => "succeeded".to_string(),
+            Some(code) => format!("failed (exit {})", code),
+            None => "could not start".to_string(),
+        };
+        let values = [
+            ("column", run.column.clone()),
+            ("action", run.label.clone()),
+            ("command", run.command.clone()),
+            ("status", status),
+            (
+                "exit_code",
+                run.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+            ),
+            ("duration", format!("{:.1}", run.duration_secs)),
+            ("output", tail.join("\n")),
+            ("user", std::env::var("USER").unwrap_or_default()),
+        ];
+        let data: Map<String, Value> = values
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Value::from(value)))
+            .collect();
+        let payload = self.payload.as_deref().unwrap_or(DEFAULT_PAYLOAD);
+        engine
+            .render_template(payload, &data)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// POST `run` to every webhook it matches, with curl in the background; the handles
+/// let a caller about to exit wait for them. Failures are only logged: a webhook must
+/// never get in the way of a run.
+pub fn notify(webhooks: &[Webhook], run: &Entry) -> Vec<JoinHandle<()>> {
+    webhooks
+        .iter()
+        .filter(|hook| hook.matches(&run.label, run.exit_code))
+        .filter_map(|hook| match hook.body(run) {
+            Ok(body) => Some((hook.url.clone(), body)),
+            Err(err) => {
+                tracing::warn!(url = hook.url, "webhook payload: {}", err);
+                None
+            }
+        })
+        .map(|(url, body)| thread::spawn(move || post(&url, &body)))
+        .collect()
+}
+
+fn post(url: &str, body: &str) {
+    let child = Command::new("curl")
+        .args(["-sS", "--fail", "--max-time", "10", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::warn!(url, "webhook: could not run curl: {}", err);
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(body.as_bytes());
+    }
+    match child.wait_with_output() {
+        Ok(out) if out.status.success() => tracing::info!(url, "webhook posted"),
+        Ok(out) => tracing::warn!(
+            url,
+            "webhook failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ),
+        Err(err) => tracing::warn!(url, "webhook failed: {}", err),
+    }
+}
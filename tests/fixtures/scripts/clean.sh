@@ -0,0 +1,3 @@
+#!/bin/sh
+# Remove build artifacts
+rm -rf target